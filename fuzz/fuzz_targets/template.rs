@@ -0,0 +1,31 @@
+//! Parses arbitrary templates and renders the accepted ones at arbitrary widths
+//!
+//! Run with `cargo +nightly fuzz run template` from the repository root.
+
+#![no_main]
+
+use indicatif::{InMemoryTerm, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, &str, u16, u64, u64)| {
+    let (template, msg, width, len, pos) = input;
+
+    // The strict parser must accept a subset of what the lenient one accepts
+    let strict = ProgressStyle::with_template_strict(template);
+    let style = match ProgressStyle::with_template(template) {
+        Ok(style) => style,
+        Err(_) => {
+            assert!(strict.is_err());
+            return;
+        }
+    };
+
+    let term = InMemoryTerm::new(4, width.clamp(1, 512));
+    let pb = ProgressBar::with_draw_target(Some(len), ProgressDrawTarget::term_like(Box::new(term)));
+    pb.set_style(style);
+    pb.set_message(msg.to_owned());
+    pb.set_prefix(msg.to_owned());
+    pb.set_position(pos);
+    pb.tick();
+    pb.finish();
+});
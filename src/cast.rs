@@ -0,0 +1,131 @@
+//! Recording of drawn frames in the asciicast v2 format, see `MultiProgress::record_to()`
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::draw_target::LineType;
+
+/// Writes frames as output events of an asciicast v2 file
+///
+/// Each frame replaces the progress bars of the previous one, while printed lines stay above
+/// them, like on a terminal. Frames are recorded while drawing, so they are buffered; the first
+/// error stops the recording and is returned by [`CastRecorder::finish()`].
+#[derive(Debug)]
+pub(crate) struct CastRecorder {
+    file: BufWriter<File>,
+    started: Instant,
+    /// The number of progress bar lines the next frame replaces
+    bar_lines: usize,
+    /// The first error writing to the file
+    error: Option<io::Error>,
+}
+
+impl CastRecorder {
+    /// Creates a recorder writing the header of a `width` by `height` recording to `file`
+    pub(crate) fn new(file: File, width: u16, height: u16) -> io::Result<Self> {
+        let mut file = BufWriter::new(file);
+        writeln!(
+            file,
+            r#"{{"version": 2, "width": {width}, "height": {height}}}"#
+        )?;
+        Ok(Self {
+            file,
+            started: Instant::now(),
+            bar_lines: 0,
+            error: None,
+        })
+    }
+
+    /// Records a frame drawn at `now`, unless recording failed before
+    pub(crate) fn frame(&mut self, now: Instant, lines: &[LineType]) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let mut data = String::new();
+        if self.bar_lines > 0 {
+            // Move to the first line of the previous progress bars and clear everything below
+            if self.bar_lines > 1 {
+                let _ = write!(data, "\x1b[{}A", self.bar_lines - 1);
+            }
+            data.push_str("\r\x1b[J");
+        }
+
+        let mut bar_lines = 0;
+        for line in lines {
+            match line {
+                LineType::Text(text) => {
+                    data.push_str(text);
+                    data.push_str("\r\n");
+                }
+                LineType::Empty => data.push_str("\r\n"),
+                LineType::Bar(bar) => {
+                    if bar_lines > 0 {
+                        data.push_str("\r\n");
+                    }
+                    data.push_str(bar);
+                    bar_lines += 1;
+                }
+            }
+        }
+        self.bar_lines = bar_lines;
+
+        let time = now.saturating_duration_since(self.started).as_secs_f64();
+        let mut event = format!("[{time:.6}, \"o\", ");
+        write_json_str(&mut event, &data);
+        event.push_str("]\n");
+        if let Err(err) = self.file.write_all(event.as_bytes()) {
+            self.error = Some(err);
+        }
+    }
+
+    /// Flushes the recording, returning the first error writing it
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.file.flush(),
+        }
+    }
+
+    /// Keeps the first `lines` progress bar lines of the last frame in place
+    pub(crate) fn keep(&mut self, lines: usize) {
+        self.bar_lines = self.bar_lines.saturating_sub(lines);
+    }
+}
+
+/// Appends `s` to `out` as a JSON string literal
+fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_json_str;
+
+    #[test]
+    fn json_escaping() {
+        let mut out = String::new();
+        write_json_str(&mut out, "a\"b\\c\r\n\x1b[1Aé");
+        assert_eq!(out, r#""a\"b\\c\r\n\u001b[1Aé""#);
+    }
+}
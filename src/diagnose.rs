@@ -0,0 +1,90 @@
+use std::env;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use console::Term;
+
+use crate::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Prints what indicatif detects about the terminal and renders a test pattern to stderr
+///
+/// This is meant for debugging reports about broken rendering: the output shows the detected
+/// terminal size and capabilities along with the enabled crate features, followed by a few
+/// short-lived progress bars exercising plain ASCII, Unicode, colors and spinner refreshes. If
+/// one of them looks wrong, the report above it usually tells why.
+///
+/// Run `cargo run --example diagnose --features diagnose` for a ready-made binary.
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnose")))]
+pub fn diagnose() -> io::Result<()> {
+    let term = Term::stderr();
+    let features = term.features();
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+    term.write_line(&format!("indicatif {}", env!("CARGO_PKG_VERSION")))?;
+    term.write_line(&format!(
+        "  stderr is a terminal: {}",
+        yes_no(term.is_term())
+    ))?;
+    let size = match term.size_checked() {
+        Some((rows, cols)) => format!("{cols}x{rows}"),
+        None => "unknown (falling back to 80x24)".into(),
+    };
+    term.write_line(&format!("  size: {size}"))?;
+    term.write_line(&format!(
+        "  colors: {}",
+        yes_no(console::colors_enabled_stderr())
+    ))?;
+    term.write_line(&format!("  emoji: {}", yes_no(features.wants_emoji())))?;
+    for var in ["TERM", "COLORTERM", "NO_COLOR", "CLICOLOR_FORCE"] {
+        let value = env::var(var).unwrap_or_else(|_| "(unset)".into());
+        term.write_line(&format!("  ${var}: {value}"))?;
+    }
+    term.write_line(&format!(
+        "  features: unicode-width={} improved_unicode={}",
+        yes_no(cfg!(feature = "unicode-width")),
+        yes_no(cfg!(feature = "improved_unicode")),
+    ))?;
+
+    if !term.is_term() {
+        term.write_line("progress bars are hidden because stderr is not a terminal")?;
+        return Ok(());
+    }
+
+    let patterns = [
+        ("ascii", "{prefix:>8} [{bar:30}] {pos:>3}/{len}", "#>-"),
+        (
+            "unicode",
+            "{prefix:>8} {bar:30} {pos:>3}/{len}",
+            "█▉▊▋▌▍▎▏  ",
+        ),
+        (
+            "colors",
+            "{prefix:>8} [{bar:30.cyan/blue}] {pos:>3}/{len}",
+            "#>-",
+        ),
+    ];
+    for (name, template, chars) in patterns {
+        let pb = ProgressBar::with_draw_target(Some(100), ProgressDrawTarget::stderr())
+            .with_style(
+                ProgressStyle::with_template(template)
+                    .unwrap()
+                    .progress_chars(chars),
+            )
+            .with_prefix(name);
+        for _ in 0..100 {
+            pb.inc(1);
+            thread::sleep(Duration::from_millis(5));
+        }
+        pb.finish();
+    }
+
+    let spinner = ProgressBar::new_spinner()
+        .with_style(ProgressStyle::with_template("{prefix:>8} {spinner} {msg}").unwrap())
+        .with_prefix("refresh")
+        .with_message("should spin smoothly");
+    spinner.enable_steady_tick(Duration::from_millis(80));
+    thread::sleep(Duration::from_secs(1));
+    spinner.finish_with_message("done");
+    Ok(())
+}
@@ -1,7 +1,9 @@
+use std::fmt;
 use std::io;
+use std::mem;
 use std::ops::{Add, AddAssign, Sub};
 use std::slice::SliceIndex;
-use std::sync::{Arc, RwLock, RwLockWriteGuard};
+use std::sync::{Arc, Mutex, RwLock, RwLockWriteGuard};
 use std::thread::panicking;
 use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
@@ -11,7 +13,11 @@ use console::Term;
 #[cfg(target_arch = "wasm32")]
 use web_time::Instant;
 
-use crate::multi::{MultiProgressAlignment, MultiState};
+#[cfg(feature = "ipc")]
+use crate::ipc::IpcEvent;
+use crate::multi::{MultiProgressAlignment, MultiState, ProgressEvent};
+use crate::rate::RateLimiter;
+use crate::sync::IgnorePoison;
 use crate::TermLike;
 
 /// Target for draw operations
@@ -85,7 +91,7 @@ impl ProgressDrawTarget {
     pub fn term_like(term_like: Box<dyn TermLike>) -> Self {
         Self {
             kind: TargetKind::TermLike {
-                inner: term_like,
+                inner: term_like.into(),
                 last_line_count: VisualLines::default(),
                 rate_limiter: None,
                 draw_state: DrawState::default(),
@@ -98,7 +104,7 @@ impl ProgressDrawTarget {
     pub fn term_like_with_hz(term_like: Box<dyn TermLike>, refresh_rate: u8) -> Self {
         Self {
             kind: TargetKind::TermLike {
-                inner: term_like,
+                inner: term_like.into(),
                 last_line_count: VisualLines::default(),
                 rate_limiter: Option::from(RateLimiter::new(refresh_rate)),
                 draw_state: DrawState::default(),
@@ -106,6 +112,123 @@ impl ProgressDrawTarget {
         }
     }
 
+    /// Emit compact binary progress events to `writer` at a max of 20 times a second.
+    ///
+    /// Instead of rendering the progress bar, every update is sent as a structured
+    /// [`IpcEvent`] frame. This is useful to report progress to a parent process, a terminal
+    /// multiplexer or a build orchestrator, which can decode the stream with an
+    /// [`IpcReader`](crate::IpcReader).
+    ///
+    /// Only progress bars that draw directly to this target emit events; a [`MultiProgress`]
+    /// using it as its draw target renders nothing.
+    ///
+    /// [`MultiProgress`]: crate::MultiProgress
+    #[cfg(feature = "ipc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ipc")))]
+    pub fn ipc(writer: impl io::Write + Send + 'static) -> Self {
+        Self {
+            kind: TargetKind::Ipc {
+                writer: SharedWriter(Mutex::new(Box::new(writer))),
+                rate_limiter: RateLimiter::new(20),
+            },
+        }
+    }
+
+    /// Print the progress as plain-text lines to `writer`, at most once every `interval`
+    ///
+    /// Instead of redrawing the progress bar in place, a line like `45% (450/1000) ETA 2m` is
+    /// appended, followed by the prefix and message if they are set. Progress bars without a
+    /// length print their position instead. The final state is printed once when the progress
+    /// bar finishes, regardless of `interval`. This is useful for logs and terminals that can't
+    /// move the cursor.
+    ///
+    /// Only progress bars that draw directly to this target print lines; a [`MultiProgress`]
+    /// using it as its draw target renders nothing.
+    ///
+    /// [`MultiProgress`]: crate::MultiProgress
+    pub fn plain(writer: impl io::Write + Send + 'static, interval: Duration) -> Self {
+        Self {
+            kind: TargetKind::Plain {
+                writer: SharedWriter(Mutex::new(Box::new(writer))),
+                interval,
+                last_print: None,
+                printed_final: false,
+            },
+        }
+    }
+
+    /// Draw to stderr, or print plain-text progress lines every 10 seconds if it is not a terminal
+    ///
+    /// If stderr is a terminal, this is the same as [`ProgressDrawTarget::stderr`]. Otherwise,
+    /// e.g. when it is redirected to a file, progress is printed as with
+    /// [`ProgressDrawTarget::plain`] instead of being hidden.
+    pub fn stderr_plain() -> Self {
+        match Term::stderr().is_term() {
+            true => Self::stderr(),
+            false => Self::plain(io::stderr(), Duration::from_secs(10)),
+        }
+    }
+
+    /// Draw progress bars in a fixed area at the bottom of the terminal
+    ///
+    /// Instead of moving the cursor relative to its current position, the bars are drawn at
+    /// absolute positions in rows reserved at the bottom of the terminal, and the rest of the
+    /// terminal is set up as a scrolling region for other output. This avoids artifacts when
+    /// other code writes partial lines while the bars are being rendered. The terminal is
+    /// restored when the draw target is dropped.
+    ///
+    /// Finished progress bars are not kept in the scrollback in this mode. Support for scrolling
+    /// regions (DECSTBM) cannot be detected, so only opt in for terminals known to support them.
+    /// This has no effect on draw targets that do not draw to an interactive terminal.
+    pub fn with_absolute_positioning(mut self) -> Self {
+        match &mut self.kind {
+            TargetKind::Term {
+                term, draw_state, ..
+            } => draw_state.absolute = term.is_term(),
+            TargetKind::TermLike { draw_state, .. } => draw_state.absolute = true,
+            _ => {}
+        }
+        self
+    }
+
+    /// Only redraw the parts of lines that changed since the previous frame
+    ///
+    /// When the progress bars occupy the same rows as in the previous frame, unchanged lines are
+    /// skipped and changed lines are rewritten starting at the first differing column. This
+    /// reduces the amount of data written for remote or slow terminals (e.g. over SSH), at the
+    /// cost of relying on the terminal's contents matching what was drawn before. Frames with
+    /// printed lines or a different layout are drawn in full.
+    ///
+    /// This has no effect on draw targets that do not draw to a terminal.
+    pub fn with_delta_encoding(mut self) -> Self {
+        match &mut self.kind {
+            TargetKind::Term { draw_state, .. } | TargetKind::TermLike { draw_state, .. } => {
+                draw_state.delta = true
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Wrap each frame in synchronized update escape sequences
+    ///
+    /// Terminals supporting synchronized output (DEC private mode 2026, e.g. kitty, WezTerm,
+    /// iTerm2 and Windows Terminal) apply each frame atomically, which removes flicker and
+    /// tearing. Other terminals are expected to ignore the sequences, but as support is not
+    /// detected, only opt in for terminals that at least understand escape sequences.
+    ///
+    /// This has no effect on draw targets that do not draw to an interactive terminal.
+    pub fn with_synchronized_output(mut self) -> Self {
+        match &mut self.kind {
+            TargetKind::Term {
+                term, draw_state, ..
+            } => draw_state.synchronized = term.is_term(),
+            TargetKind::TermLike { draw_state, .. } => draw_state.synchronized = true,
+            _ => {}
+        }
+        self
+    }
+
     /// A hidden draw target.
     ///
     /// This forces a progress bar to be not rendered at all.
@@ -123,18 +246,179 @@ impl ProgressDrawTarget {
         match self.kind {
             TargetKind::Hidden => true,
             TargetKind::Term { ref term, .. } => !term.is_term(),
-            TargetKind::Multi { ref state, .. } => state.read().unwrap().is_hidden(),
+            TargetKind::Multi { ref state, .. } => state.read().ignore_poison().is_hidden(),
             _ => false,
         }
     }
 
+    /// Writes `line` to a terminal target that is not user attended, where nothing is drawn
+    ///
+    /// Does nothing for other targets, in particular for [`ProgressDrawTarget::hidden()`].
+    #[cfg(feature = "tracing")]
+    pub(crate) fn write_unattended_line(&self, line: &str) -> io::Result<()> {
+        match self.kind {
+            TargetKind::Term { ref term, .. } if !term.is_term() => term.write_line(line),
+            _ => Ok(()),
+        }
+    }
+
     /// Returns the current width of the draw target.
     pub(crate) fn width(&self) -> Option<u16> {
         match self.kind {
             TargetKind::Term { ref term, .. } => Some(term.size().1),
-            TargetKind::Multi { ref state, .. } => state.read().unwrap().width(),
+            TargetKind::Multi { ref state, .. } => state.read().ignore_poison().width(),
             TargetKind::TermLike { ref inner, .. } => Some(inner.width()),
-            TargetKind::Hidden => None,
+            TargetKind::Hidden | TargetKind::Plain { .. } => None,
+            #[cfg(feature = "ipc")]
+            TargetKind::Ipc { .. } => None,
+        }
+    }
+
+    /// Returns the current height of the terminal, if any
+    pub(crate) fn height(&self) -> Option<u16> {
+        match self.kind {
+            TargetKind::Term { ref term, .. } => Some(term.size().0),
+            TargetKind::Multi { ref state, .. } => state.read().ignore_poison().height(),
+            TargetKind::TermLike { ref inner, .. } => Some(inner.height()),
+            TargetKind::Hidden | TargetKind::Plain { .. } => None,
+            #[cfg(feature = "ipc")]
+            TargetKind::Ipc { .. } => None,
+        }
+    }
+
+    /// Rings the terminal bell, unless the draw target is hidden
+    pub(crate) fn bell(&self) -> io::Result<()> {
+        self.write_control("\x07")
+    }
+
+    /// Sets the window title of the terminal, unless the draw target is hidden
+    pub(crate) fn set_title(&self, title: &str) -> io::Result<()> {
+        self.write_control(&format!("\x1b]0;{title}\x07"))
+    }
+
+    /// Saves the window title of the terminal on its title stack
+    pub(crate) fn push_title(&self) -> io::Result<()> {
+        self.write_control("\x1b[22;0t")
+    }
+
+    /// Restores the window title last saved with [`ProgressDrawTarget::push_title()`]
+    pub(crate) fn pop_title(&self) -> io::Result<()> {
+        self.write_control("\x1b[23;0t")
+    }
+
+    /// Writes a control sequence that doesn't affect the drawn lines
+    pub(crate) fn write_control(&self, seq: &str) -> io::Result<()> {
+        match &self.kind {
+            TargetKind::Term { term, .. } if term.is_term() => {
+                term.write_str(seq)?;
+                term.flush()
+            }
+            TargetKind::TermLike { inner, .. } => {
+                inner.write_str(seq)?;
+                inner.flush()
+            }
+            TargetKind::Multi { state, .. } => state.read().ignore_poison().write_control(seq),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sends an event if this is an IPC draw target.
+    ///
+    /// Returns `None` if this is not an IPC draw target, in which case the caller should draw
+    /// through [`ProgressDrawTarget::drawable`] instead.
+    #[cfg(feature = "ipc")]
+    pub(crate) fn send_ipc(
+        &mut self,
+        force_draw: bool,
+        now: Instant,
+        event: impl FnOnce() -> IpcEvent,
+    ) -> Option<io::Result<()>> {
+        match &mut self.kind {
+            TargetKind::Ipc {
+                writer,
+                rate_limiter,
+            } => match force_draw || rate_limiter.allow(now) {
+                true => Some(event().write_to(&mut **writer.0.lock().ignore_poison())),
+                false => Some(Ok(())), // rate limited
+            },
+            _ => None,
+        }
+    }
+
+    /// Prints a plain-text progress line if this is a plain draw target and one is due.
+    ///
+    /// Returns `None` if this is not a plain draw target, otherwise whether a line was printed.
+    pub(crate) fn send_plain(
+        &mut self,
+        now: Instant,
+        finished: bool,
+        line: impl FnOnce() -> String,
+    ) -> Option<io::Result<bool>> {
+        let TargetKind::Plain {
+            writer,
+            interval,
+            last_print,
+            printed_final,
+        } = &mut self.kind
+        else {
+            return None;
+        };
+
+        let due = match finished {
+            true => !*printed_final,
+            false => last_print.map_or(true, |last| {
+                now.saturating_duration_since(last) >= *interval
+            }),
+        };
+        if !due {
+            return Some(Ok(false));
+        }
+
+        *last_print = Some(now);
+        *printed_final = finished;
+        let mut writer = writer.0.lock().ignore_poison();
+        Some(
+            writeln!(writer, "{}", line())
+                .and_then(|_| writer.flush())
+                .map(|_| true),
+        )
+    }
+
+    /// Prints `msg` if this is a plain draw target, see [`ProgressDrawTarget::send_plain`]
+    pub(crate) fn println_plain(&self, msg: impl FnOnce() -> String) -> Option<io::Result<()>> {
+        let TargetKind::Plain { writer, .. } = &self.kind else {
+            return None;
+        };
+
+        let mut writer = writer.0.lock().ignore_poison();
+        Some(writeln!(writer, "{}", msg()).and_then(|_| writer.flush()))
+    }
+
+    /// Returns the progress bar lines that were drawn most recently.
+    pub(crate) fn last_drawn_lines(&self) -> Vec<LineType> {
+        let lines = match &self.kind {
+            TargetKind::Term { draw_state, .. } => &draw_state.lines,
+            TargetKind::TermLike { draw_state, .. } => &draw_state.lines,
+            TargetKind::Multi { state, idx } => {
+                return state.read().ignore_poison().member_lines(*idx)
+            }
+            _ => return Vec::new(),
+        };
+
+        lines
+            .iter()
+            .filter(|line| matches!(line, LineType::Bar(_)))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns true if the last draw had to be clamped to the terminal height.
+    pub(crate) fn is_truncated(&self) -> bool {
+        match &self.kind {
+            TargetKind::Term { draw_state, .. } => draw_state.truncated,
+            TargetKind::TermLike { draw_state, .. } => draw_state.truncated,
+            TargetKind::Multi { state, .. } => state.read().ignore_poison().is_truncated(),
+            _ => false,
         }
     }
 
@@ -142,7 +426,17 @@ impl ProgressDrawTarget {
     /// be marked a zombie.
     pub(crate) fn mark_zombie(&self) {
         if let TargetKind::Multi { idx, state } = &self.kind {
-            state.write().unwrap().mark_zombie(*idx);
+            state.write().ignore_poison().mark_zombie(*idx);
+        }
+    }
+
+    /// Forgets the previously drawn frame, so that the next draw redraws all lines
+    pub(crate) fn invalidate(&mut self) {
+        match &mut self.kind {
+            TargetKind::Term { draw_state, .. } => draw_state.invalidate(),
+            TargetKind::TermLike { draw_state, .. } => draw_state.invalidate(),
+            TargetKind::Multi { state, .. } => state.write().ignore_poison().invalidate(),
+            _ => {}
         }
     }
 
@@ -173,16 +467,18 @@ impl ProgressDrawTarget {
                         term,
                         last_line_count,
                         draw_state,
+                        force_draw,
                     }),
                     false => None, // rate limited
                 }
             }
             TargetKind::Multi { idx, state, .. } => {
-                let state = state.write().unwrap();
+                let state = state.write().ignore_poison();
                 Some(Drawable::Multi {
                     idx: *idx,
                     state,
                     force_draw,
+                    steady_tick: false,
                     now,
                 })
             }
@@ -193,9 +489,10 @@ impl ProgressDrawTarget {
                 draw_state,
             } => match force_draw || rate_limiter.as_mut().map_or(true, |r| r.allow(now)) {
                 true => Some(Drawable::TermLike {
-                    term_like: &**inner,
+                    term_like: inner,
                     last_line_count,
                     draw_state,
+                    force_draw,
                 }),
                 false => None, // rate limited
             },
@@ -204,22 +501,49 @@ impl ProgressDrawTarget {
         }
     }
 
+    /// Whether a frame would be drawn at `now`, without consuming capacity of the rate limiter
+    pub(crate) fn would_draw(&self, now: Instant) -> bool {
+        match &self.kind {
+            TargetKind::Term {
+                term, rate_limiter, ..
+            } => term.is_term() && rate_limiter.would_allow(now),
+            TargetKind::Multi { state, .. } => state.read().ignore_poison().would_draw(now),
+            TargetKind::TermLike { rate_limiter, .. } => {
+                rate_limiter.as_ref().map_or(true, |r| r.would_allow(now))
+            }
+            #[cfg(feature = "ipc")]
+            TargetKind::Ipc { rate_limiter, .. } => rate_limiter.would_allow(now),
+            TargetKind::Plain {
+                interval,
+                last_print,
+                ..
+            } => last_print.map_or(true, |last| {
+                now.saturating_duration_since(last) >= *interval
+            }),
+            TargetKind::Hidden => false,
+        }
+    }
+
     /// Properly disconnects from the draw target
     pub(crate) fn disconnect(&self, now: Instant) {
         match self.kind {
             TargetKind::Term { .. } => {}
             TargetKind::Multi { idx, ref state, .. } => {
-                let state = state.write().unwrap();
+                let state = state.write().ignore_poison();
                 let _ = Drawable::Multi {
                     state,
                     idx,
                     force_draw: true,
+                    steady_tick: false,
                     now,
                 }
                 .clear();
             }
             TargetKind::Hidden => {}
             TargetKind::TermLike { .. } => {}
+            #[cfg(feature = "ipc")]
+            TargetKind::Ipc { .. } => {}
+            TargetKind::Plain { .. } => {}
         };
     }
 
@@ -235,6 +559,24 @@ impl ProgressDrawTarget {
     }
 }
 
+impl Drop for ProgressDrawTarget {
+    fn drop(&mut self) {
+        let (term, draw_state): (&dyn TermLike, _) = match &mut self.kind {
+            TargetKind::Term {
+                term, draw_state, ..
+            } => (term, draw_state),
+            TargetKind::TermLike {
+                inner, draw_state, ..
+            } => (&**inner, draw_state),
+            _ => return,
+        };
+
+        if draw_state.absolute && draw_state.reserved_rows > 0 {
+            let _ = draw_state.release_fixed_area(term);
+        }
+    }
+}
+
 #[derive(Debug)]
 enum TargetKind {
     Term {
@@ -249,11 +591,31 @@ enum TargetKind {
     },
     Hidden,
     TermLike {
-        inner: Box<dyn TermLike>,
+        inner: Arc<dyn TermLike>,
         last_line_count: VisualLines,
         rate_limiter: Option<RateLimiter>,
         draw_state: DrawState,
     },
+    #[cfg(feature = "ipc")]
+    Ipc {
+        writer: SharedWriter,
+        rate_limiter: RateLimiter,
+    },
+    Plain {
+        writer: SharedWriter,
+        interval: Duration,
+        last_print: Option<Instant>,
+        /// Whether the last line showed a finished progress bar
+        printed_final: bool,
+    },
+}
+
+struct SharedWriter(Mutex<Box<dyn io::Write + Send>>);
+
+impl fmt::Debug for SharedWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedWriter").finish_non_exhaustive()
+    }
 }
 
 impl TargetKind {
@@ -281,21 +643,33 @@ pub(crate) enum Drawable<'a> {
         term: &'a Term,
         last_line_count: &'a mut VisualLines,
         draw_state: &'a mut DrawState,
+        force_draw: bool,
     },
     Multi {
         state: RwLockWriteGuard<'a, MultiState>,
         idx: usize,
         force_draw: bool,
+        /// True if the draw was caused by a steady tick, which may be coalesced
+        steady_tick: bool,
         now: Instant,
     },
     TermLike {
-        term_like: &'a dyn TermLike,
+        term_like: &'a Arc<dyn TermLike>,
         last_line_count: &'a mut VisualLines,
         draw_state: &'a mut DrawState,
+        force_draw: bool,
     },
 }
 
 impl Drawable<'_> {
+    /// Sends an event to the subscribers of the `MultiProgress` drawn to, if any
+    pub(crate) fn notify(&mut self, event: impl FnOnce(usize) -> ProgressEvent) {
+        if let Drawable::Multi { state, idx, .. } = self {
+            let idx = *idx;
+            state.notify(|| event(idx));
+        }
+    }
+
     /// Adjust `last_line_count` such that the next draw operation keeps/clears additional lines
     pub(crate) fn adjust_last_line_count(&mut self, adjust: LineAdjust) {
         let last_line_count: &mut VisualLines = match self {
@@ -314,6 +688,13 @@ impl Drawable<'_> {
         }
     }
 
+    /// Marks the draw as caused by a steady tick
+    pub(crate) fn set_steady_tick(&mut self) {
+        if let Drawable::Multi { steady_tick, .. } = self {
+            *steady_tick = true;
+        }
+    }
+
     pub(crate) fn state(&mut self) -> DrawStateWrapper<'_> {
         let mut state = match self {
             Drawable::Term { draw_state, .. } => DrawStateWrapper::for_term(draw_state),
@@ -328,30 +709,90 @@ impl Drawable<'_> {
     pub(crate) fn clear(mut self) -> io::Result<()> {
         let state = self.state();
         drop(state);
-        self.draw()
+        self.draw().map(|_| ())
     }
 
-    pub(crate) fn draw(self) -> io::Result<()> {
+    /// Draws the frame, returning whether it was actually written to the terminal
+    ///
+    /// A `MultiProgress` may skip the frame because of its own rate limit or a hidden target.
+    pub(crate) fn draw(self) -> io::Result<bool> {
         match self {
             Drawable::Term {
                 term,
                 last_line_count,
                 draw_state,
-            } => draw_state.draw_to_term(term, last_line_count),
+                force_draw,
+            } => draw_state
+                .draw_to_term(term, last_line_count, force_draw)
+                .map(|_| true),
             Drawable::Multi {
                 mut state,
                 force_draw,
+                steady_tick,
                 now,
                 ..
-            } => state.draw(force_draw, None, now),
+            } => match steady_tick && !force_draw && !state.allow_steady_tick(now) {
+                true => Ok(false),
+                false => {
+                    let last_frame = state.last_frame();
+                    state.queue_draw(force_draw, None, now)?;
+                    let drawn = state.last_frame() != last_frame;
+                    // Write to the terminal without blocking updates of other progress bars
+                    let output = state.output();
+                    drop(state);
+                    output.flush()?;
+                    Ok(drawn)
+                }
+            },
             Drawable::TermLike {
                 term_like,
                 last_line_count,
                 draw_state,
-            } => draw_state.draw_to_term(term_like, last_line_count),
+                force_draw,
+            } => draw_state
+                .draw_to_term(&**term_like, last_line_count, force_draw)
+                .map(|_| true),
         }
     }
 
+    /// Draws the frame like [`Drawable::draw()`], but queues the terminal output on `output`
+    ///
+    /// This lets a `MultiProgress` write to the terminal after releasing the lock on its state.
+    pub(crate) fn defer(self, output: &DeferredOutput) -> io::Result<bool> {
+        let (term, last_line_count, draw_state, force_draw): (Arc<dyn TermLike>, _, _, _) =
+            match self {
+                Drawable::Term {
+                    term,
+                    last_line_count,
+                    draw_state,
+                    force_draw,
+                } => (
+                    Arc::new(term.clone()),
+                    last_line_count,
+                    draw_state,
+                    force_draw,
+                ),
+                Drawable::TermLike {
+                    term_like,
+                    last_line_count,
+                    draw_state,
+                    force_draw,
+                } => (term_like.clone(), last_line_count, draw_state, force_draw),
+                multi @ Drawable::Multi { .. } => return multi.draw(),
+            };
+
+        let recording = RecordingTerm {
+            term: &*term,
+            ops: Mutex::default(),
+        };
+        draw_state.draw_to_term(&recording, last_line_count, force_draw)?;
+        let ops = recording.ops.into_inner().ignore_poison();
+        if !ops.is_empty() {
+            output.push((term, ops))?;
+        }
+        Ok(true)
+    }
+
     pub(crate) fn width(&self) -> Option<u16> {
         match self {
             Self::Term { term, .. } => Some(term.size().1),
@@ -361,6 +802,139 @@ impl Drawable<'_> {
     }
 }
 
+/// Terminal output of a `MultiProgress` that is written after the lock on its state is released
+///
+/// Frames are queued while the state is locked, so they are in the order they were drawn. Any
+/// thread can then write all queued frames, one thread at a time. Frames can't be dropped, as
+/// each one only updates the previous one; instead, once [`MAX_QUEUED_FRAMES`] are queued,
+/// the thread queueing a frame waits for them to be written, like when writing directly. This
+/// keeps a slow terminal from accumulating a backlog of frames.
+#[derive(Debug, Default)]
+pub(crate) struct DeferredOutput {
+    queue: Mutex<Vec<QueuedFrame>>,
+    /// Held while writing queued frames, so that they don't interleave
+    writer: Mutex<()>,
+}
+
+impl DeferredOutput {
+    /// Queues a frame, writing the queue if it is full
+    fn push(&self, frame: QueuedFrame) -> io::Result<()> {
+        let mut queue = self.queue.lock().ignore_poison();
+        queue.push(frame);
+        let full = queue.len() >= MAX_QUEUED_FRAMES;
+        drop(queue);
+        match full {
+            true => self.flush(),
+            false => Ok(()),
+        }
+    }
+
+    /// Writes all queued frames, returning the first error
+    pub(crate) fn flush(&self) -> io::Result<()> {
+        let _writer = self.writer.lock().ignore_poison();
+        let frames = mem::take(&mut *self.queue.lock().ignore_poison());
+        let mut result = Ok(());
+        for (term, ops) in frames {
+            let written = ops.into_iter().try_for_each(|op| op.apply(&*term));
+            if result.is_ok() {
+                result = written;
+            }
+        }
+        result
+    }
+}
+
+/// The number of frames queued on a [`DeferredOutput`] before drawing waits for them to be
+/// written
+const MAX_QUEUED_FRAMES: usize = 8;
+
+/// The terminal operations of a frame and the terminal they're for
+type QueuedFrame = (Arc<dyn TermLike>, Vec<TermOp>);
+
+/// A terminal operation recorded by [`RecordingTerm`]
+#[derive(Debug)]
+enum TermOp {
+    MoveCursorUp(usize),
+    MoveCursorDown(usize),
+    MoveCursorRight(usize),
+    MoveCursorLeft(usize),
+    WriteLine(String),
+    WriteStr(String),
+    ClearLine,
+    Flush,
+}
+
+impl TermOp {
+    fn apply(self, term: &dyn TermLike) -> io::Result<()> {
+        match self {
+            Self::MoveCursorUp(n) => term.move_cursor_up(n),
+            Self::MoveCursorDown(n) => term.move_cursor_down(n),
+            Self::MoveCursorRight(n) => term.move_cursor_right(n),
+            Self::MoveCursorLeft(n) => term.move_cursor_left(n),
+            Self::WriteLine(s) => term.write_line(&s),
+            Self::WriteStr(s) => term.write_str(&s),
+            Self::ClearLine => term.clear_line(),
+            Self::Flush => term.flush(),
+        }
+    }
+}
+
+/// Records the operations on a terminal instead of performing them, see [`DeferredOutput`]
+#[derive(Debug)]
+struct RecordingTerm<'a> {
+    term: &'a dyn TermLike,
+    ops: Mutex<Vec<TermOp>>,
+}
+
+impl RecordingTerm<'_> {
+    fn record(&self, op: TermOp) -> io::Result<()> {
+        self.ops.lock().ignore_poison().push(op);
+        Ok(())
+    }
+}
+
+impl TermLike for RecordingTerm<'_> {
+    fn width(&self) -> u16 {
+        self.term.width()
+    }
+
+    fn height(&self) -> u16 {
+        self.term.height()
+    }
+
+    fn move_cursor_up(&self, n: usize) -> io::Result<()> {
+        self.record(TermOp::MoveCursorUp(n))
+    }
+
+    fn move_cursor_down(&self, n: usize) -> io::Result<()> {
+        self.record(TermOp::MoveCursorDown(n))
+    }
+
+    fn move_cursor_right(&self, n: usize) -> io::Result<()> {
+        self.record(TermOp::MoveCursorRight(n))
+    }
+
+    fn move_cursor_left(&self, n: usize) -> io::Result<()> {
+        self.record(TermOp::MoveCursorLeft(n))
+    }
+
+    fn write_line(&self, s: &str) -> io::Result<()> {
+        self.record(TermOp::WriteLine(s.to_owned()))
+    }
+
+    fn write_str(&self, s: &str) -> io::Result<()> {
+        self.record(TermOp::WriteStr(s.to_owned()))
+    }
+
+    fn clear_line(&self) -> io::Result<()> {
+        self.record(TermOp::ClearLine)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.record(TermOp::Flush)
+    }
+}
+
 pub(crate) enum LineAdjust {
     /// Adds to `last_line_count` so that the next draw also clears those lines
     Clear(VisualLines),
@@ -422,57 +996,13 @@ impl Drop for DrawStateWrapper<'_> {
     }
 }
 
-#[derive(Debug)]
-struct RateLimiter {
-    interval: u16, // in milliseconds
-    capacity: u8,
-    prev: Instant,
-}
-
-/// Rate limit but allow occasional bursts above desired rate
-impl RateLimiter {
-    fn new(rate: u8) -> Self {
-        Self {
-            interval: 1000 / (rate as u16), // between 3 and 1000 milliseconds
-            capacity: MAX_BURST,
-            prev: Instant::now(),
-        }
-    }
-
-    fn allow(&mut self, now: Instant) -> bool {
-        if now < self.prev {
-            return false;
-        }
+/// Begin synchronized update (DEC private mode 2026)
+const BEGIN_SYNCHRONIZED_UPDATE: &str = "\x1b[?2026h";
+/// End synchronized update (DEC private mode 2026)
+const END_SYNCHRONIZED_UPDATE: &str = "\x1b[?2026l";
 
-        let elapsed = now - self.prev;
-        // If `capacity` is 0 and not enough time (`self.interval` ms) has passed since
-        // `self.prev` to add new capacity, return `false`. The goal of this method is to
-        // make this decision as efficient as possible.
-        if self.capacity == 0 && elapsed < Duration::from_millis(self.interval as u64) {
-            return false;
-        }
-
-        // We now calculate `new`, the number of ms, since we last returned `true`,
-        // and `remainder`, which represents a number of ns less than 1ms which we cannot
-        // convert into capacity now, so we're saving it for later.
-        let (new, remainder) = (
-            elapsed.as_millis() / self.interval as u128,
-            elapsed.as_nanos() % (self.interval as u128 * 1_000_000),
-        );
-
-        // We add `new` to `capacity`, subtract one for returning `true` from here,
-        // then make sure it does not exceed a maximum of `MAX_BURST`, then store it.
-        self.capacity = Ord::min(MAX_BURST as u128, (self.capacity as u128) + new - 1) as u8;
-        // Store `prev` for the next iteration after subtracting the `remainder`.
-        // Just use `unwrap` here because it shouldn't be possible for this to underflow.
-        self.prev = now
-            .checked_sub(Duration::from_nanos(remainder as u64))
-            .unwrap();
-        true
-    }
-}
-
-const MAX_BURST: u8 = 20;
+/// Drawn in place of the lines of a progress bar that do not fit the terminal height
+const TRUNCATION_INDICATOR: &str = "...";
 
 /// The drawn state of an element.
 #[derive(Clone, Debug, Default)]
@@ -483,6 +1013,23 @@ pub(crate) struct DrawState {
     pub(crate) move_cursor: bool,
     /// Controls how the multi progress is aligned if some of its progress bars get removed, default is `Top`
     pub(crate) alignment: MultiProgressAlignment,
+    /// True if the lines are drawn on behalf of a `MultiProgress`
+    pub(crate) is_multi: bool,
+    /// True if the last draw did not fit the terminal height
+    pub(crate) truncated: bool,
+    /// True if bars are drawn in a fixed area at the bottom of the terminal
+    pub(crate) absolute: bool,
+    /// The number of rows reserved at the bottom of the terminal in absolute mode
+    reserved_rows: usize,
+    /// True if only changed parts of lines should be redrawn
+    pub(crate) delta: bool,
+    /// True if frames are wrapped in synchronized update sequences
+    pub(crate) synchronized: bool,
+    /// The terminal width and bar lines of the previous frame in delta mode
+    prev_frame: Option<(usize, Vec<String>)>,
+    /// The terminal size and bar lines of the previous frame, and the number of lines it left
+    /// on the terminal, if it may be skipped when repeated
+    prev_drawn: Option<((u16, u16), Vec<String>, VisualLines)>,
 }
 
 impl DrawState {
@@ -494,11 +1041,85 @@ impl DrawState {
         &mut self,
         term: &(impl TermLike + ?Sized),
         bar_count: &mut VisualLines, // The number of dynamic lines printed at the previous tick
+        force_draw: bool,
     ) -> io::Result<()> {
         if panicking() {
             return Ok(());
         }
 
+        // Skip frames identical to what is on the terminal, e.g. ticks that change nothing
+        // visible, unless the caller asked for the frame to be drawn
+        if !force_draw && self.is_prev_drawn(term, *bar_count) {
+            return Ok(());
+        }
+
+        let result = match self.synchronized {
+            false => self.draw_frame(term, bar_count),
+            true => {
+                // Let the terminal apply the whole frame at once
+                term.write_str(BEGIN_SYNCHRONIZED_UPDATE)?;
+                let result = self.draw_frame(term, bar_count);
+                term.write_str(END_SYNCHRONIZED_UPDATE)?;
+                term.flush()?;
+                result
+            }
+        };
+
+        // Text lines are printed once above the bars, so frames containing them are never skipped
+        let skippable = !self.absolute
+            && self
+                .lines
+                .iter()
+                .all(|line| matches!(line, LineType::Bar(_)));
+        self.prev_drawn = match result {
+            Ok(()) if skippable => {
+                Some(((term.width(), term.height()), self.bar_lines(), *bar_count))
+            }
+            _ => None,
+        };
+        result
+    }
+
+    /// Whether the lines and terminal size are the same as when the previous frame was drawn
+    fn is_prev_drawn(&self, term: &(impl TermLike + ?Sized), bar_count: VisualLines) -> bool {
+        let Some((size, prev, prev_count)) = &self.prev_drawn else {
+            return false;
+        };
+
+        *prev_count == bar_count
+            && *size == (term.width(), term.height())
+            && self.lines.len() == prev.len()
+            && self
+                .lines
+                .iter()
+                .zip(prev)
+                .all(|(line, prev)| matches!(line, LineType::Bar(bar) if bar == prev))
+    }
+
+    fn draw_frame(
+        &mut self,
+        term: &(impl TermLike + ?Sized),
+        bar_count: &mut VisualLines,
+    ) -> io::Result<()> {
+        if self.absolute {
+            return self.draw_to_fixed_area(term, bar_count);
+        }
+
+        let term_width = term.width() as usize;
+        if self.delta {
+            match self.prev_frame.take() {
+                Some((width, prev))
+                    if width == term_width
+                        && self.is_delta_compatible(term_width, &prev, *bar_count) =>
+                {
+                    self.draw_delta(term, &prev)?;
+                    self.prev_frame = Some((term_width, self.bar_lines()));
+                    return Ok(());
+                }
+                _ => self.prev_frame = Some((term_width, self.bar_lines())),
+            }
+        }
+
         if !self.lines.is_empty() && self.move_cursor {
             // Move up to first line (assuming the last line doesn't contain a '\n') and then move to then front of the line
             term.move_cursor_up(bar_count.as_usize().saturating_sub(1))?;
@@ -516,8 +1137,6 @@ impl DrawState {
             term.move_cursor_up(n.saturating_sub(1))?;
         }
 
-        let term_width = term.width() as usize;
-
         // Here we calculate the terminal vertical real estate that the state requires
         let full_height = self.visual_line_count(.., term_width);
 
@@ -539,13 +1158,31 @@ impl DrawState {
         // full height exceeds the terminal height.
         let mut real_height = VisualLines::default();
 
+        let term_height = VisualLines::from(term.height());
+        let bars_height = self
+            .lines
+            .iter()
+            .filter(|line| matches!(line, LineType::Bar(_)))
+            .fold(VisualLines::default(), |acc, line| {
+                acc.saturating_add(line.wrapped_height(term_width))
+            });
+        self.truncated = bars_height > term_height;
+
+        // If a single progress bar does not fit on the terminal, reserve the last row for an
+        // indicator rather than leaving it cut off at an arbitrary line.
+        let indicator = self.truncated && !self.is_multi && term_height > VisualLines::default();
+        let max_height = match indicator {
+            true => term_height.saturating_sub(VisualLines::from(1usize)),
+            false => term_height,
+        };
+
         for (idx, line) in self.lines.iter().enumerate() {
             let line_height = line.wrapped_height(term_width);
 
             // Check here for bar lines that exceed the terminal height
             if matches!(line, LineType::Bar(_)) {
                 // Stop here if printing this bar would exceed the terminal height
-                if real_height + line_height > term.height().into() {
+                if real_height + line_height > max_height {
                     break;
                 }
 
@@ -568,16 +1205,162 @@ impl DrawState {
             }
         }
 
+        if indicator {
+            if real_height > VisualLines::default() {
+                term.write_line("")?;
+            }
+
+            let line = console::truncate_str(TRUNCATION_INDICATOR, term_width, "");
+            term.write_str(&line)?;
+            let last_line_filler = term_width.saturating_sub(console::measure_text_width(&line));
+            term.write_str(&" ".repeat(last_line_filler))?;
+            real_height += VisualLines::from(1usize);
+        }
+
         term.flush()?;
         *bar_count = real_height + shift;
 
         Ok(())
     }
 
+    /// Whether the current lines can be drawn as changes relative to the previous frame `prev`
+    ///
+    /// This requires the previous frame to occupy exactly the rows that are drawn now, with each
+    /// line fitting into a single row.
+    fn is_delta_compatible(&self, width: usize, prev: &[String], bar_count: VisualLines) -> bool {
+        !self.lines.is_empty()
+            && self.lines.len() == prev.len()
+            && bar_count == VisualLines::from(prev.len())
+            && self
+                .lines
+                .iter()
+                .all(|line| matches!(line, LineType::Bar(_)) && line.console_width() < width)
+            && prev
+                .iter()
+                .all(|line| console::measure_text_width(line) < width)
+    }
+
+    /// Redraw only the parts of the lines that differ from `prev`
+    ///
+    /// Like a regular draw, this leaves the cursor after the padded last line.
+    fn draw_delta(&self, term: &(impl TermLike + ?Sized), prev: &[String]) -> io::Result<()> {
+        let term_width = term.width() as usize;
+        term.move_cursor_up(prev.len() - 1)?;
+
+        let last = self.lines.len() - 1;
+        for (idx, (line, prev)) in self.lines.iter().zip(prev).enumerate() {
+            let line = line.as_ref();
+            if idx != 0 {
+                term.move_cursor_down(1)?;
+            }
+
+            if idx != last && line == prev {
+                continue;
+            }
+
+            let (col, rest) = changed_run(prev, line);
+            term.write_str("\r")?;
+            if col > 0 {
+                term.move_cursor_right(col)?;
+            }
+            term.write_str(rest)?;
+            match idx == last {
+                // Keep the cursor on the right terminal side, like a regular draw
+                true => {
+                    let filler = term_width.saturating_sub(console::measure_text_width(line));
+                    term.write_str(&" ".repeat(filler))?;
+                }
+                false => term.write_str("\x1b[K")?,
+            }
+        }
+
+        term.flush()
+    }
+
+    fn bar_lines(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .filter(|line| matches!(line, LineType::Bar(_)))
+            .map(|line| line.as_ref().to_owned())
+            .collect()
+    }
+
+    /// Draw the current state using absolute cursor positioning
+    ///
+    /// Text lines are printed at the cursor, which stays in the scrolling region above the
+    /// reserved rows, while bar lines are drawn in the reserved rows without moving the cursor.
+    fn draw_to_fixed_area(
+        &mut self,
+        term: &(impl TermLike + ?Sized),
+        bar_count: &mut VisualLines,
+    ) -> io::Result<()> {
+        let term_width = term.width() as usize;
+        let term_height = term.height() as usize;
+
+        for line in self.lines.iter().filter(|l| !matches!(l, LineType::Bar(_))) {
+            term.write_str(line.as_ref())?;
+            term.write_line("")?;
+        }
+
+        let bars = self
+            .lines
+            .iter()
+            .filter(|line| matches!(line, LineType::Bar(_)))
+            .collect::<Vec<_>>();
+        let bars_height = bars
+            .iter()
+            .map(|line| line.wrapped_height(term_width).as_usize())
+            .sum::<usize>();
+
+        // Keep at least one row for the scrolling region
+        let reserved = Ord::min(bars_height, term_height.saturating_sub(1));
+        self.truncated = bars_height > reserved;
+
+        if reserved != self.reserved_rows {
+            resize_fixed_area(term, self.reserved_rows, reserved)?;
+            self.reserved_rows = reserved;
+        }
+
+        if reserved > 0 {
+            term.write_str("\x1b7")?;
+            let mut row = term_height - reserved;
+            for line in bars {
+                let height = line.wrapped_height(term_width).as_usize();
+                if row + height > term_height {
+                    break;
+                }
+
+                term.write_str(&format!("\x1b[{};1H\x1b[2K", row + 1))?;
+                term.write_str(line.as_ref())?;
+                row += height;
+            }
+            term.write_str("\x1b8")?;
+        }
+
+        term.flush()?;
+        *bar_count = VisualLines::from(reserved);
+        Ok(())
+    }
+
+    /// Reset the scrolling region and move the cursor below the bars, leaving them in place
+    fn release_fixed_area(&mut self, term: &dyn TermLike) -> io::Result<()> {
+        let term_height = term.height() as usize;
+        term.write_str(&format!("\x1b[r\x1b[{term_height};1H"))?;
+        term.write_line("")?;
+        term.flush()?;
+        self.reserved_rows = 0;
+        Ok(())
+    }
+
     fn reset(&mut self) {
         self.lines.clear();
     }
 
+    fn invalidate(&mut self) {
+        self.prev_frame = None;
+        self.prev_drawn = None;
+    }
+
     pub(crate) fn visual_line_count(
         &self,
         range: impl SliceIndex<[LineType], Output = [LineType]>,
@@ -587,6 +1370,60 @@ impl DrawState {
     }
 }
 
+/// The column and the remainder of `new` starting at the first character that differs from `old`
+///
+/// Lines with escape codes are rewritten entirely, as their columns cannot be mapped to bytes.
+fn changed_run<'a>(old: &str, new: &'a str) -> (usize, &'a str) {
+    if old.contains('\x1b') || new.contains('\x1b') {
+        return (0, new);
+    }
+
+    let split = new
+        .char_indices()
+        .zip(old.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((i, _), _)| i)
+        .unwrap_or_else(|| {
+            // One line is a prefix of the other
+            let common = old.chars().count();
+            new.char_indices().nth(common).map_or(new.len(), |(i, _)| i)
+        });
+
+    let col = console::measure_text_width(&new[..split]);
+    (col, &new[split..])
+}
+
+/// Grow or shrink the reserved area at the bottom of the terminal from `prev` to `reserved` rows
+fn resize_fixed_area(
+    term: &(impl TermLike + ?Sized),
+    prev: usize,
+    reserved: usize,
+) -> io::Result<()> {
+    let term_height = term.height() as usize;
+
+    if reserved > prev {
+        // Scroll the existing output up so that it is not overwritten by the bars
+        let grow = reserved - prev;
+        term.write_str(&"\n".repeat(grow))?;
+        term.move_cursor_up(grow)?;
+    }
+
+    // Setting the scrolling region moves the cursor to the home position
+    term.write_str("\x1b7")?;
+    match reserved {
+        0 => term.write_str("\x1b[r")?,
+        _ => term.write_str(&format!("\x1b[1;{}r", term_height - reserved))?,
+    }
+
+    // Clear rows that are no longer part of the reserved area
+    for row in term_height.saturating_sub(prev)..term_height.saturating_sub(reserved) {
+        term.write_str(&format!("\x1b[{};1H\x1b[2K", row + 1))?;
+    }
+    term.write_str("\x1b8")?;
+
+    Ok(())
+}
+
 #[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
 pub(crate) struct VisualLines(usize);
 
@@ -682,8 +1519,114 @@ impl PartialEq<str> for LineType {
 
 #[cfg(test)]
 mod tests {
-    use crate::draw_target::LineType;
-    use crate::{MultiProgress, ProgressBar, ProgressDrawTarget};
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use console::Term;
+
+    use crate::draw_target::{
+        changed_run, DeferredOutput, LineType, TargetKind, TermOp, MAX_QUEUED_FRAMES,
+    };
+    use crate::{MultiProgress, ProgressBar, ProgressDrawTarget, TermLike};
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A terminal counting the lines written to it
+    #[derive(Debug, Default)]
+    struct CountingTerm(Mutex<usize>);
+
+    impl TermLike for CountingTerm {
+        fn width(&self) -> u16 {
+            80
+        }
+
+        fn move_cursor_up(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn move_cursor_down(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn move_cursor_right(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn move_cursor_left(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn write_line(&self, _: &str) -> io::Result<()> {
+            *self.0.lock().unwrap() += 1;
+            Ok(())
+        }
+
+        fn write_str(&self, _: &str) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn clear_line(&self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn deferred_output_is_bounded() {
+        let term = Arc::new(CountingTerm::default());
+        let output = DeferredOutput::default();
+        let frame = || {
+            (
+                term.clone() as Arc<dyn TermLike>,
+                vec![TermOp::WriteLine("x".into())],
+            )
+        };
+
+        for _ in 1..MAX_QUEUED_FRAMES {
+            output.push(frame()).unwrap();
+        }
+        assert_eq!(*term.0.lock().unwrap(), 0);
+
+        // Queueing one more frame writes the queue instead of growing it further
+        output.push(frame()).unwrap();
+        assert_eq!(*term.0.lock().unwrap(), MAX_QUEUED_FRAMES);
+        assert!(output.queue.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn plain_lines() {
+        let buf = SharedBuf::default();
+        let target = ProgressDrawTarget::plain(buf.clone(), Duration::from_secs(3600));
+        let pb = ProgressBar::with_draw_target(Some(1000), target).with_message("copying");
+        pb.inc(450);
+        pb.inc(50);
+        pb.println("log line");
+        pb.finish();
+        pb.tick();
+
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3, "{output}");
+        assert!(lines[0].starts_with("45% (450/1000) ETA "), "{}", lines[0]);
+        assert!(lines[0].ends_with(" copying"));
+        assert_eq!(lines[1], "log line");
+        assert_eq!(lines[2], "100% (1000/1000) finished copying");
+    }
 
     #[test]
     fn multi_is_hidden() {
@@ -694,6 +1637,39 @@ mod tests {
         assert!(pb.is_hidden());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn escape_sequences_need_a_terminal() {
+        let null = || {
+            std::fs::File::options()
+                .read(true)
+                .write(true)
+                .open("/dev/null")
+                .unwrap()
+        };
+        let term = Term::read_write_pair(null(), null());
+        let target = ProgressDrawTarget::term(term, 20)
+            .with_absolute_positioning()
+            .with_synchronized_output();
+        match &target.kind {
+            TargetKind::Term { draw_state, .. } => {
+                assert!(!draw_state.absolute);
+                assert!(!draw_state.synchronized);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn delta_changed_run() {
+        assert_eq!(changed_run("one 1/10", "one 2/10"), (4, "2/10"));
+        assert_eq!(changed_run("one 1/10", "one 1/10"), (8, ""));
+        assert_eq!(changed_run("one 1/10", "one 1/100"), (8, "0"));
+        assert_eq!(changed_run("one 10/10", "one 1"), (5, ""));
+        assert_eq!(changed_run("ä 1", "ä 2"), (2, "2"));
+        assert_eq!(changed_run("\x1b[1mone\x1b[0m", "one"), (0, "one"));
+    }
+
     #[test]
     fn real_line_count_test() {
         #[derive(Debug)]
@@ -0,0 +1,51 @@
+use std::fmt;
+use std::io;
+use std::sync::PoisonError;
+
+/// An error of an operation on a [`ProgressBar`](crate::ProgressBar) or
+/// [`MultiProgress`](crate::MultiProgress)
+///
+/// Returned by the `try_` variants of operations that otherwise return an [`io::Error`] or
+/// panic, like [`MultiProgress::try_println()`](crate::MultiProgress::try_println).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Writing to the terminal failed
+    Io(io::Error),
+    /// Another thread panicked while updating the state of a progress bar or `MultiProgress`
+    Poisoned,
+    /// The arguments are not valid for the operation, e.g. a progress bar that is not a member
+    /// of the `MultiProgress`
+    InvalidUsage(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "terminal I/O failed: {err}"),
+            Self::Poisoned => write!(f, "a thread panicked while updating the progress state"),
+            Self::InvalidUsage(msg) => write!(f, "invalid usage: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<T> From<PoisonError<T>> for Error {
+    fn from(_: PoisonError<T>) -> Self {
+        Self::Poisoned
+    }
+}
@@ -0,0 +1,207 @@
+//! A minimal C ABI for driving progress bars from other languages
+//!
+//! Progress bars and multi progress objects are handed out as opaque pointers, which must be
+//! released with [`indicatif_bar_free`] and [`indicatif_multi_free`] respectively. Functions
+//! returning `int` return `0` on success and `-1` if a handle is null, a string is not valid
+//! UTF-8 or a panic occurred; panics never unwind into the caller.
+//!
+//! To link against these functions, build a `cdylib` or `staticlib` crate that depends on
+//! indicatif with the `ffi` feature enabled and re-exports this module.
+
+use std::ffi::{c_char, c_int, CStr};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{MultiProgress, ProgressBar};
+
+/// Runs `f`, turning panics into an error code
+fn guard(f: impl FnOnce() -> Option<()>) -> c_int {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Some(())) => 0,
+        Ok(None) | Err(_) => -1,
+    }
+}
+
+/// Creates a progress bar with the given length, drawn to stderr
+///
+/// Returns null if a panic occurred.
+#[no_mangle]
+pub extern "C" fn indicatif_bar_new(len: u64) -> *mut ProgressBar {
+    panic::catch_unwind(|| Box::into_raw(Box::new(ProgressBar::new(len))))
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Releases a progress bar, which is finished according to its style if it isn't already
+///
+/// # Safety
+///
+/// `bar` must be null or a pointer returned by [`indicatif_bar_new`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_bar_free(bar: *mut ProgressBar) {
+    if !bar.is_null() {
+        let bar = Box::from_raw(bar);
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(bar)));
+    }
+}
+
+/// Sets the length of a progress bar
+///
+/// # Safety
+///
+/// `bar` must be null or a valid pointer returned by [`indicatif_bar_new`].
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_bar_set_length(bar: *const ProgressBar, len: u64) -> c_int {
+    let bar = bar.as_ref();
+    guard(|| {
+        bar?.set_length(len);
+        Some(())
+    })
+}
+
+/// Sets the position of a progress bar
+///
+/// # Safety
+///
+/// `bar` must be null or a valid pointer returned by [`indicatif_bar_new`].
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_bar_set_position(bar: *const ProgressBar, pos: u64) -> c_int {
+    let bar = bar.as_ref();
+    guard(|| {
+        bar?.set_position(pos);
+        Some(())
+    })
+}
+
+/// Advances the position of a progress bar
+///
+/// # Safety
+///
+/// `bar` must be null or a valid pointer returned by [`indicatif_bar_new`].
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_bar_inc(bar: *const ProgressBar, delta: u64) -> c_int {
+    let bar = bar.as_ref();
+    guard(|| {
+        bar?.inc(delta);
+        Some(())
+    })
+}
+
+/// Sets the message of a progress bar from a NUL-terminated UTF-8 string
+///
+/// # Safety
+///
+/// `bar` must be null or a valid pointer returned by [`indicatif_bar_new`], and `msg` must be
+/// null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_bar_set_message(
+    bar: *const ProgressBar,
+    msg: *const c_char,
+) -> c_int {
+    let bar = bar.as_ref();
+    let msg = match msg.is_null() {
+        true => None,
+        false => CStr::from_ptr(msg).to_str().ok(),
+    };
+    guard(|| {
+        bar?.set_message(msg?.to_owned());
+        Some(())
+    })
+}
+
+/// Finishes a progress bar, leaving the current message
+///
+/// # Safety
+///
+/// `bar` must be null or a valid pointer returned by [`indicatif_bar_new`].
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_bar_finish(bar: *const ProgressBar) -> c_int {
+    let bar = bar.as_ref();
+    guard(|| {
+        bar?.finish();
+        Some(())
+    })
+}
+
+/// Creates a multi progress object drawn to stderr
+///
+/// Returns null if a panic occurred.
+#[no_mangle]
+pub extern "C" fn indicatif_multi_new() -> *mut MultiProgress {
+    panic::catch_unwind(|| Box::into_raw(Box::new(MultiProgress::new())))
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Releases a multi progress object
+///
+/// Progress bars added to it remain valid and must be released separately.
+///
+/// # Safety
+///
+/// `multi` must be null or a pointer returned by [`indicatif_multi_new`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_multi_free(multi: *mut MultiProgress) {
+    if !multi.is_null() {
+        let multi = Box::from_raw(multi);
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(multi)));
+    }
+}
+
+/// Adds a progress bar to a multi progress object, see [`MultiProgress::add`]
+///
+/// # Safety
+///
+/// `multi` and `bar` must be null or valid pointers returned by [`indicatif_multi_new`] and
+/// [`indicatif_bar_new`].
+#[no_mangle]
+pub unsafe extern "C" fn indicatif_multi_add(
+    multi: *const MultiProgress,
+    bar: *const ProgressBar,
+) -> c_int {
+    let (multi, bar) = (multi.as_ref(), bar.as_ref());
+    guard(|| {
+        multi?.add(bar?.clone());
+        Some(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+
+    #[test]
+    fn drive_bar() {
+        unsafe {
+            let multi = indicatif_multi_new();
+            let bar = indicatif_bar_new(10);
+            assert_eq!(indicatif_multi_add(multi, bar), 0);
+            assert_eq!(indicatif_bar_set_length(bar, 20), 0);
+            assert_eq!(indicatif_bar_set_position(bar, 5), 0);
+            assert_eq!(indicatif_bar_inc(bar, 2), 0);
+            let msg = CString::new("downloading").unwrap();
+            assert_eq!(indicatif_bar_set_message(bar, msg.as_ptr()), 0);
+
+            let pb = &*bar;
+            assert_eq!(pb.position(), 7);
+            assert_eq!(pb.length(), Some(20));
+            assert_eq!(pb.message(), "downloading");
+
+            assert_eq!(indicatif_bar_finish(bar), 0);
+            assert!(pb.is_finished());
+            indicatif_bar_free(bar);
+            indicatif_multi_free(multi);
+        }
+    }
+
+    #[test]
+    fn null_handles() {
+        unsafe {
+            let bar = indicatif_bar_new(1);
+            assert_eq!(indicatif_bar_inc(std::ptr::null(), 1), -1);
+            assert_eq!(indicatif_bar_set_message(bar, std::ptr::null()), -1);
+            assert_eq!(indicatif_multi_add(std::ptr::null(), bar), -1);
+            indicatif_bar_free(bar);
+            indicatif_bar_free(std::ptr::null_mut());
+        }
+    }
+}
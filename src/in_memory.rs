@@ -4,6 +4,7 @@ use std::sync::{Arc, Mutex};
 
 use vt100::Parser;
 
+use crate::sync::IgnorePoison;
 use crate::TermLike;
 
 /// A thin wrapper around [`vt100::Parser`].
@@ -25,12 +26,22 @@ impl InMemoryTerm {
     }
 
     pub fn reset(&self) {
-        let mut state = self.state.lock().unwrap();
+        let mut state = self.state.lock().ignore_poison();
         *state = InMemoryTermState::new(state.height, state.width);
     }
 
+    /// Changes the size of the terminal, like resizing a terminal window
+    pub fn resize(&self, rows: u16, cols: u16) {
+        assert!(rows > 0, "rows must be > 0");
+        assert!(cols > 0, "cols must be > 0");
+        let mut state = self.state.lock().ignore_poison();
+        state.parser.set_size(rows, cols);
+        state.height = rows;
+        state.width = cols;
+    }
+
     pub fn contents(&self) -> String {
-        let state = self.state.lock().unwrap();
+        let state = self.state.lock().ignore_poison();
 
         // For some reason, the `Screen::contents` method doesn't include newlines in what it
         // returns, making it useless for our purposes. So we need to manually reconstruct the
@@ -55,7 +66,7 @@ impl InMemoryTerm {
     }
 
     pub fn contents_formatted(&self) -> Vec<u8> {
-        let state = self.state.lock().unwrap();
+        let state = self.state.lock().ignore_poison();
 
         // For some reason, the `Screen::contents` method doesn't include newlines in what it
         // returns, making it useless for our purposes. So we need to manually reconstruct the
@@ -95,7 +106,7 @@ impl InMemoryTerm {
 
     pub fn moves_since_last_check(&self) -> String {
         let mut s = String::new();
-        for line in std::mem::take(&mut self.state.lock().unwrap().history) {
+        for line in std::mem::take(&mut self.state.lock().ignore_poison().history) {
             writeln!(s, "{line:?}").unwrap();
         }
         s
@@ -104,18 +115,18 @@ impl InMemoryTerm {
 
 impl TermLike for InMemoryTerm {
     fn width(&self) -> u16 {
-        self.state.lock().unwrap().width
+        self.state.lock().ignore_poison().width
     }
 
     fn height(&self) -> u16 {
-        self.state.lock().unwrap().height
+        self.state.lock().ignore_poison().height
     }
 
     fn move_cursor_up(&self, n: usize) -> std::io::Result<()> {
         match n {
             0 => Ok(()),
             _ => {
-                let mut state = self.state.lock().unwrap();
+                let mut state = self.state.lock().ignore_poison();
                 state.history.push(Move::Up(n));
                 state.write_str(&format!("\x1b[{n}A"))
             }
@@ -126,7 +137,7 @@ impl TermLike for InMemoryTerm {
         match n {
             0 => Ok(()),
             _ => {
-                let mut state = self.state.lock().unwrap();
+                let mut state = self.state.lock().ignore_poison();
                 state.history.push(Move::Down(n));
                 state.write_str(&format!("\x1b[{n}B"))
             }
@@ -137,7 +148,7 @@ impl TermLike for InMemoryTerm {
         match n {
             0 => Ok(()),
             _ => {
-                let mut state = self.state.lock().unwrap();
+                let mut state = self.state.lock().ignore_poison();
                 state.history.push(Move::Right(n));
                 state.write_str(&format!("\x1b[{n}C"))
             }
@@ -148,7 +159,7 @@ impl TermLike for InMemoryTerm {
         match n {
             0 => Ok(()),
             _ => {
-                let mut state = self.state.lock().unwrap();
+                let mut state = self.state.lock().ignore_poison();
                 state.history.push(Move::Left(n));
                 state.write_str(&format!("\x1b[{n}D"))
             }
@@ -156,7 +167,7 @@ impl TermLike for InMemoryTerm {
     }
 
     fn write_line(&self, s: &str) -> std::io::Result<()> {
-        let mut state = self.state.lock().unwrap();
+        let mut state = self.state.lock().ignore_poison();
         state.history.push(Move::Str(s.into()));
         state.history.push(Move::NewLine);
 
@@ -174,19 +185,19 @@ impl TermLike for InMemoryTerm {
     }
 
     fn write_str(&self, s: &str) -> std::io::Result<()> {
-        let mut state = self.state.lock().unwrap();
+        let mut state = self.state.lock().ignore_poison();
         state.history.push(Move::Str(s.into()));
         state.write_str(s)
     }
 
     fn clear_line(&self) -> std::io::Result<()> {
-        let mut state = self.state.lock().unwrap();
+        let mut state = self.state.lock().ignore_poison();
         state.history.push(Move::Clear);
         state.write_str("\r\x1b[2K")
     }
 
     fn flush(&self) -> std::io::Result<()> {
-        let mut state = self.state.lock().unwrap();
+        let mut state = self.state.lock().ignore_poison();
         state.history.push(Move::Flush);
         state.parser.flush()
     }
@@ -0,0 +1,335 @@
+use std::io;
+#[cfg(unix)]
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvError, RecvTimeoutError, TryRecvError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use console::{Key, Term};
+
+use crate::ProgressBar;
+
+/// An event produced by a key press while progress bars are being rendered
+///
+/// See [`listen_for_keys()`].
+#[cfg_attr(docsrs, doc(cfg(feature = "input")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InputEvent {
+    /// The user asked to pause (`p` or space while running)
+    Pause,
+    /// The user asked to resume (`p` or space while paused)
+    Resume,
+    /// The user asked to cancel (`q`, escape or `Ctrl-C`)
+    Cancel,
+}
+
+/// Reads key presses from `term` on a background thread and returns a [`KeyListener`] that
+/// receives them as [`InputEvent`]s
+///
+/// `p` and space toggle between [`InputEvent::Pause`] and [`InputEvent::Resume`], while `q`,
+/// escape and `Ctrl-C` send [`InputEvent::Cancel`]. Other keys are ignored. It is up to the
+/// caller to act on the events, e.g. by stopping work and finishing the progress bars on cancel.
+///
+/// The terminal is switched to raw mode while listening, so terminal line editing is unavailable
+/// and `Ctrl-C` does not interrupt the process. Dropping the [`KeyListener`] stops the thread and
+/// restores the terminal, so keep it alive only while the progress bars are shown. The thread
+/// also exits after sending [`InputEvent::Cancel`]. If `term` is not an interactive terminal, no
+/// thread is started and the listener is disconnected right away.
+///
+/// Use [`pause_on_keys()`] to pause and resume a progress bar along with the events.
+///
+/// ```rust,no_run
+/// # use console::Term;
+/// # use indicatif::{listen_for_keys, InputEvent, ProgressBar};
+/// let pb = ProgressBar::new(100);
+/// let events = listen_for_keys(Term::stderr());
+/// for _ in 0..100 {
+///     match events.try_recv() {
+///         Ok(InputEvent::Cancel) => break,
+///         Ok(InputEvent::Pause) => {
+///             // Block until the user resumes (or cancels)
+///             if events.recv() != Ok(InputEvent::Resume) {
+///                 break;
+///             }
+///         }
+///         _ => {}
+///     }
+///     pb.inc(1);
+/// }
+/// pb.finish();
+/// // Restore the terminal before reading from it again
+/// drop(events);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "input")))]
+pub fn listen_for_keys(term: Term) -> KeyListener {
+    spawn_listener(term, |_| {})
+}
+
+/// Like [`listen_for_keys()`], but also pauses and resumes `pb` on the respective events
+///
+/// The progress bar is [paused][ProgressBar::pause()] on [`InputEvent::Pause`] and
+/// [resumed][ProgressBar::resume()] on [`InputEvent::Resume`] before the event is sent, so the
+/// time spent paused does not skew its elapsed time and ETA.
+///
+/// ```rust,no_run
+/// # use console::Term;
+/// # use indicatif::{pause_on_keys, InputEvent, ProgressBar};
+/// let pb = ProgressBar::new(100);
+/// let events = pause_on_keys(&pb, Term::stderr());
+/// for _ in 0..100 {
+///     match events.try_recv() {
+///         Ok(InputEvent::Cancel) => break,
+///         // Block until the user resumes (or cancels)
+///         Ok(InputEvent::Pause) if events.recv() != Ok(InputEvent::Resume) => break,
+///         _ => {}
+///     }
+///     pb.inc(1);
+/// }
+/// pb.finish();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "input")))]
+pub fn pause_on_keys(pb: &ProgressBar, term: Term) -> KeyListener {
+    let pb = pb.downgrade();
+    spawn_listener(term, move |event| {
+        let Some(pb) = pb.upgrade() else {
+            return;
+        };
+        match event {
+            InputEvent::Pause => pb.pause(),
+            InputEvent::Resume => pb.resume(),
+            InputEvent::Cancel => {}
+        }
+    })
+}
+
+/// Receives the [`InputEvent`]s of [`listen_for_keys()`] and [`pause_on_keys()`]
+///
+/// Dropping the listener stops reading keys and restores the terminal.
+#[cfg_attr(docsrs, doc(cfg(feature = "input")))]
+#[derive(Debug)]
+#[must_use = "keys are only read until the listener is dropped"]
+pub struct KeyListener {
+    events: Receiver<InputEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl KeyListener {
+    /// Returns the next event without blocking, see [`Receiver::try_recv()`]
+    pub fn try_recv(&self) -> Result<InputEvent, TryRecvError> {
+        self.events.try_recv()
+    }
+
+    /// Blocks until the next event, see [`Receiver::recv()`]
+    pub fn recv(&self) -> Result<InputEvent, RecvError> {
+        self.events.recv()
+    }
+
+    /// Blocks until the next event or until `timeout` elapses, see [`Receiver::recv_timeout()`]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<InputEvent, RecvTimeoutError> {
+        self.events.recv_timeout(timeout)
+    }
+}
+
+impl Drop for KeyListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+
+        // Without polling, the thread only notices the flag after the next key press
+        if cfg!(unix) {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn spawn_listener(
+    term: Term,
+    mut on_event: impl FnMut(InputEvent) + Send + 'static,
+) -> KeyListener {
+    let (tx, events) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut listener = KeyListener {
+        events,
+        stop: stop.clone(),
+        thread: None,
+    };
+
+    // Reading keys from a non-interactive terminal returns `Key::Unknown` right away, forever
+    if !term.is_term() {
+        return listener;
+    }
+
+    listener.thread = Some(thread::spawn(move || {
+        let Ok(mut keys) = KeyReader::new(term) else {
+            return;
+        };
+
+        let mut paused = false;
+        while !stop.load(Ordering::Relaxed) {
+            let key = match keys.read_key(POLL_INTERVAL) {
+                Ok(Some(key)) => key,
+                Ok(None) => continue,
+                Err(_) => break,
+            };
+            let Some(event) = event_for_key(&key, paused) else {
+                continue;
+            };
+
+            paused = event == InputEvent::Pause;
+            on_event(event);
+            if tx.send(event).is_err() || event == InputEvent::Cancel {
+                break;
+            }
+        }
+    }));
+
+    listener
+}
+
+/// Reads keys from the terminal, keeping it in raw mode until dropped
+#[cfg(unix)]
+struct KeyReader {
+    term: Term,
+    input: Option<std::fs::File>,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl KeyReader {
+    fn new(term: Term) -> io::Result<Self> {
+        use std::os::fd::AsRawFd;
+
+        // Like `console`, read from stdin if it is a terminal and from the controlling one if not
+        let input = match unsafe { libc::isatty(libc::STDIN_FILENO) } {
+            1 => None,
+            _ => Some(std::fs::File::open("/dev/tty")?),
+        };
+        let fd = input.as_ref().map_or(libc::STDIN_FILENO, |f| f.as_raw_fd());
+
+        let mut termios = mem::MaybeUninit::uninit();
+        if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let original = unsafe { termios.assume_init() };
+
+        // Read raw keys so that `Ctrl-C` arrives as a key instead of a signal
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        raw.c_oflag = original.c_oflag;
+        if unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            term,
+            input,
+            original,
+        })
+    }
+
+    fn fd(&self) -> libc::c_int {
+        use std::os::fd::AsRawFd;
+        self.input
+            .as_ref()
+            .map_or(libc::STDIN_FILENO, |f| f.as_raw_fd())
+    }
+
+    /// Returns `None` if no key was pressed within `timeout`
+    fn read_key(&mut self, timeout: Duration) -> io::Result<Option<Key>> {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = timeout.as_millis().try_into().unwrap_or(libc::c_int::MAX);
+        match unsafe { libc::poll(&mut pollfd, 1, timeout) } {
+            n if n < 0 => match io::Error::last_os_error() {
+                err if err.kind() == io::ErrorKind::Interrupted => Ok(None),
+                err => Err(err),
+            },
+            _ if pollfd.revents & libc::POLLIN == 0 => Ok(None),
+            _ => self.term.read_key_raw().map(Some),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for KeyReader {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(self.fd(), libc::TCSADRAIN, &self.original) };
+    }
+}
+
+/// Reads keys from the terminal, which is only in raw mode while a key is read
+#[cfg(not(unix))]
+struct KeyReader {
+    term: Term,
+}
+
+#[cfg(not(unix))]
+impl KeyReader {
+    fn new(term: Term) -> io::Result<Self> {
+        Ok(Self { term })
+    }
+
+    fn read_key(&mut self, _timeout: Duration) -> io::Result<Option<Key>> {
+        self.term.read_key_raw().map(Some)
+    }
+}
+
+/// How often the listener checks whether it was dropped
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+fn event_for_key(key: &Key, paused: bool) -> Option<InputEvent> {
+    match key {
+        Key::Char('p' | 'P' | ' ') if paused => Some(InputEvent::Resume),
+        Key::Char('p' | 'P' | ' ') => Some(InputEvent::Pause),
+        Key::Char('q' | 'Q') | Key::Escape | Key::CtrlC => Some(InputEvent::Cancel),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_mapping() {
+        assert_eq!(
+            event_for_key(&Key::Char('p'), false),
+            Some(InputEvent::Pause)
+        );
+        assert_eq!(
+            event_for_key(&Key::Char(' '), true),
+            Some(InputEvent::Resume)
+        );
+        assert_eq!(event_for_key(&Key::Escape, true), Some(InputEvent::Cancel));
+        assert_eq!(
+            event_for_key(&Key::Char('q'), false),
+            Some(InputEvent::Cancel)
+        );
+        assert_eq!(event_for_key(&Key::CtrlC, false), Some(InputEvent::Cancel));
+        assert_eq!(event_for_key(&Key::Enter, false), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn not_a_terminal() {
+        let null = || {
+            std::fs::File::options()
+                .read(true)
+                .write(true)
+                .open("/dev/null")
+        };
+        let term = Term::read_write_pair(null().unwrap(), null().unwrap());
+        // The listener disconnects instead of a thread spinning on `Key::Unknown`
+        let events = listen_for_keys(term);
+        assert_eq!(events.recv(), Err(RecvError));
+    }
+}
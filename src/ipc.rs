@@ -0,0 +1,340 @@
+use std::io::{self, Read, Write};
+
+use crate::state::IpcProgress;
+
+/// An event emitted by a [`ProgressDrawTarget::ipc`] draw target
+///
+/// Events are written as compact binary frames and can be decoded on the receiving side with
+/// an [`IpcReader`].
+///
+/// [`ProgressDrawTarget::ipc`]: crate::ProgressDrawTarget::ipc
+#[cfg_attr(docsrs, doc(cfg(feature = "ipc")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IpcEvent {
+    /// The progress bar was updated
+    Progress(IpcProgress),
+    /// The progress bar finished
+    Finish(IpcProgress),
+    /// A line was printed above the progress bar via [`ProgressBar::println`]
+    ///
+    /// [`ProgressBar::println`]: crate::ProgressBar::println
+    Println {
+        /// The identifier of the progress bar, if one was set
+        id: Option<String>,
+        /// The printed line
+        line: String,
+    },
+}
+
+const TAG_PROGRESS: u8 = 1;
+const TAG_FINISH: u8 = 2;
+const TAG_PRINTLN: u8 = 3;
+
+impl IpcEvent {
+    /// Encode this event as a single binary frame
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match self {
+            Self::Progress(progress) => {
+                buf.push(TAG_PROGRESS);
+                encode_progress(&mut buf, progress);
+            }
+            Self::Finish(progress) => {
+                buf.push(TAG_FINISH);
+                encode_progress(&mut buf, progress);
+            }
+            Self::Println { id, line } => {
+                buf.push(TAG_PRINTLN);
+                encode_opt_str(&mut buf, id.as_deref());
+                encode_str(&mut buf, line);
+            }
+        }
+        buf
+    }
+
+    pub(crate) fn write_to(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(&self.encode())?;
+        w.flush()
+    }
+}
+
+fn encode_progress(buf: &mut Vec<u8>, progress: &IpcProgress) {
+    encode_opt_str(buf, progress.id.as_deref());
+    encode_varint(buf, progress.pos);
+    match progress.len {
+        Some(len) => {
+            buf.push(1);
+            encode_varint(buf, len);
+        }
+        None => buf.push(0),
+    }
+    encode_str(buf, &progress.message);
+    encode_str(buf, &progress.prefix);
+}
+
+fn encode_opt_str(buf: &mut Vec<u8>, s: Option<&str>) {
+    match s {
+        Some(s) => {
+            buf.push(1);
+            encode_str(buf, s);
+        }
+        None => buf.push(0),
+    }
+}
+
+fn encode_str(buf: &mut Vec<u8>, s: &str) {
+    encode_varint(buf, s.len() as u64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// LEB128-style variable length encoding: small values take a single byte
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Decodes [`IpcEvent`]s from a stream written by a [`ProgressDrawTarget::ipc`] draw target
+///
+/// ```rust
+/// # use indicatif::{IpcEvent, IpcReader, ProgressBar, ProgressDrawTarget};
+/// # use std::sync::{Arc, Mutex};
+/// # #[derive(Clone, Default)]
+/// # struct Shared(Arc<Mutex<Vec<u8>>>);
+/// # impl std::io::Write for Shared {
+/// #     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.lock().unwrap().write(buf) }
+/// #     fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+/// # }
+/// # let shared = Shared::default();
+/// let pb = ProgressBar::with_draw_target(Some(10), ProgressDrawTarget::ipc(shared.clone()));
+/// pb.finish();
+///
+/// # let bytes = shared.0.lock().unwrap().clone();
+/// let events = IpcReader::new(&bytes[..]).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert!(matches!(events.last(), Some(IpcEvent::Finish(p)) if p.pos == 10));
+/// ```
+///
+/// [`ProgressDrawTarget::ipc`]: crate::ProgressDrawTarget::ipc
+#[cfg_attr(docsrs, doc(cfg(feature = "ipc")))]
+#[derive(Debug)]
+pub struct IpcReader<R> {
+    inner: R,
+}
+
+impl<R: Read> IpcReader<R> {
+    /// Creates a new reader decoding events from `inner`
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+
+    /// Reads the next event
+    ///
+    /// Returns `Ok(None)` if the stream ended cleanly between two frames.
+    pub fn read_event(&mut self) -> io::Result<Option<IpcEvent>> {
+        let mut tag = [0u8];
+        if self.inner.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+
+        let event = match tag[0] {
+            TAG_PROGRESS => IpcEvent::Progress(self.read_progress()?),
+            TAG_FINISH => IpcEvent::Finish(self.read_progress()?),
+            TAG_PRINTLN => IpcEvent::Println {
+                id: self.read_opt_str()?,
+                line: self.read_str()?,
+            },
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown ipc event tag {tag}"),
+                ))
+            }
+        };
+
+        Ok(Some(event))
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_progress(&mut self) -> io::Result<IpcProgress> {
+        let id = self.read_opt_str()?;
+        let pos = self.read_varint()?;
+        let len = match self.read_u8()? {
+            0 => None,
+            _ => Some(self.read_varint()?),
+        };
+
+        Ok(IpcProgress {
+            id,
+            pos,
+            len,
+            message: self.read_str()?,
+            prefix: self.read_str()?,
+        })
+    }
+
+    fn read_opt_str(&mut self) -> io::Result<Option<String>> {
+        match self.read_u8()? {
+            0 => Ok(None),
+            _ => self.read_str().map(Some),
+        }
+    }
+
+    fn read_str(&mut self) -> io::Result<String> {
+        let len = self.read_varint()?;
+        let mut buf = Vec::new();
+        (&mut self.inner).take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn read_varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "ipc varint is too long",
+        ))
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut byte = [0u8];
+        self.inner.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+}
+
+impl<R: Read> Iterator for IpcReader<R> {
+    type Item = io::Result<IpcEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{ProgressBar, ProgressDrawTarget};
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let events = vec![
+            IpcEvent::Progress(IpcProgress {
+                id: Some("crate:serde".into()),
+                pos: 300,
+                len: Some(u64::MAX),
+                message: "héllo".into(),
+                prefix: String::new(),
+            }),
+            IpcEvent::Println {
+                id: None,
+                line: "log line".into(),
+            },
+            IpcEvent::Finish(IpcProgress {
+                id: None,
+                pos: 0,
+                len: None,
+                message: String::new(),
+                prefix: "done".into(),
+            }),
+        ];
+
+        let bytes = events.iter().flat_map(IpcEvent::encode).collect::<Vec<_>>();
+        let decoded = IpcReader::new(&bytes[..])
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(decoded, events);
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let mut bytes = IpcEvent::Println {
+            id: None,
+            line: "truncated".into(),
+        }
+        .encode();
+        bytes.pop();
+
+        let err = IpcReader::new(&bytes[..]).read_event().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn progress_bar_emits_events() {
+        let buf = SharedBuf::default();
+        let pb = ProgressBar::with_draw_target(Some(10), ProgressDrawTarget::ipc(buf.clone()))
+            .with_id("build");
+        pb.set_message("working");
+        pb.println("hello");
+        pb.finish();
+
+        let bytes = buf.0.lock().unwrap().clone();
+        let events = IpcReader::new(&bytes[..])
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(
+            events[0],
+            IpcEvent::Progress(IpcProgress {
+                id: Some("build".into()),
+                pos: 0,
+                len: Some(10),
+                message: "working".into(),
+                prefix: String::new(),
+            })
+        );
+        assert_eq!(
+            events[1],
+            IpcEvent::Println {
+                id: Some("build".into()),
+                line: "hello".into()
+            }
+        );
+        assert_eq!(
+            events.last(),
+            Some(&IpcEvent::Finish(IpcProgress {
+                id: Some("build".into()),
+                pos: 10,
+                len: Some(10),
+                message: "working".into(),
+                prefix: String::new(),
+            }))
+        );
+    }
+}
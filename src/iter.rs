@@ -1,8 +1,11 @@
 use std::borrow::Cow;
+use std::fmt;
 use std::io::{self, IoSliceMut};
 use std::iter::FusedIterator;
 #[cfg(feature = "tokio")]
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 #[cfg(feature = "tokio")]
 use std::task::{Context, Poll};
 use std::time::Duration;
@@ -55,6 +58,117 @@ where
         let bar = ProgressBar::new(len).with_style(style);
         self.progress_with(bar)
     }
+
+    /// Wrap an iterator with a progress bar showing a message derived from each item
+    ///
+    /// The message is set from the item as it is yielded, e.g. to show the name of the file or
+    /// test being processed. The callback is only called for items that are drawn, so it is
+    /// fine for it to allocate even if the iterator yields items much faster than the refresh
+    /// rate.
+    ///
+    /// Uses [`Iterator::size_hint()`] to get the length if it is exact, falling back to a
+    /// spinner otherwise. The default bar style doesn't show the message, so set a style with
+    /// the `{msg}` placeholder:
+    ///
+    /// ```rust
+    /// use indicatif::{ProgressIterator, ProgressStyle};
+    ///
+    /// let style = ProgressStyle::with_template("{wide_bar} {pos}/{len} {msg}").unwrap();
+    /// for file in ["a.txt", "b.txt"]
+    ///     .into_iter()
+    ///     .progress_with_message(|file| file.to_string())
+    ///     .with_style(style)
+    /// {
+    ///     // ...
+    /// }
+    /// ```
+    fn progress_with_message<F, M>(self, f: F) -> ProgressBarIter<WithMessage<Self, F>>
+    where
+        F: FnMut(&Self::Item) -> M,
+        M: Into<Cow<'static, str>>,
+    {
+        let progress = match exact_len(&self) {
+            Some(len) => ProgressBar::new(len),
+            None => ProgressBar::new_spinner(),
+        };
+        WithMessage {
+            it: self,
+            f,
+            progress: progress.clone(),
+        }
+        .progress_with(progress)
+    }
+
+    /// Wrap an iterator of [`Result`]s with a progress bar that also counts the errors
+    ///
+    /// Every item advances the position, and every [`Err`] additionally increments the number of
+    /// failures (see [`ProgressBar::inc_failures()`]). The default style shows the failures next
+    /// to the position; use the `{failures}` and `{successes}` placeholders in a custom style,
+    /// and [`ProgressStyle::failure_threshold()`] to highlight the bar when too many items fail.
+    /// Uses [`Iterator::size_hint()`] to get the length if it is exact, falling back to a
+    /// spinner otherwise.
+    fn progress_results<T, E>(self) -> ProgressBarIter<CountFailures<Self>>
+    where
+        Self: Iterator<Item = Result<T, E>>,
+    {
+        let progress = match exact_len(&self) {
+            Some(len) => ProgressBar::new(len).with_style(
+                ProgressStyle::with_template("{wide_bar} {pos}/{len} ({failures} failed)").unwrap(),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {pos} ({failures} failed)").unwrap(),
+            ),
+        };
+        CountFailures {
+            it: self,
+            progress: progress.clone(),
+        }
+        .progress_with(progress)
+    }
+}
+
+/// Wraps an iterable with a progress bar showing the count, elapsed time, ETA and rate
+///
+/// This is the quickest way to add progress reporting to a script, without importing
+/// [`ProgressIterator`] or choosing a style. If the length of the iterator is known exactly from
+/// its [`Iterator::size_hint()`], a progress bar is shown; otherwise, a spinner counting the
+/// items.
+///
+/// ```rust
+/// for _ in indicatif::track(0..100) {
+///     // ...
+/// }
+/// ```
+pub fn track<I: IntoIterator>(iter: I) -> ProgressBarIter<I::IntoIter> {
+    let iter = iter.into_iter();
+    let style = match exact_len(&iter) {
+        Some(_) => ProgressStyle::with_template(
+            "{wide_bar} {pos}/{len} [{elapsed_precise}<{eta_precise}, {per_sec}]",
+        ),
+        None => ProgressStyle::with_template("{spinner} {pos} [{elapsed_precise}, {per_sec}]"),
+    };
+    track_with_style(iter, style.unwrap())
+}
+
+/// Wraps an iterable with a progress bar using the given style, see [`track()`]
+pub fn track_with_style<I: IntoIterator>(
+    iter: I,
+    style: ProgressStyle,
+) -> ProgressBarIter<I::IntoIter> {
+    let iter = iter.into_iter();
+    let bar = match exact_len(&iter) {
+        Some(len) => ProgressBar::new(len),
+        None => ProgressBar::new_spinner(),
+    };
+    iter.progress_with(bar.with_style(style))
+}
+
+/// The length of the iterator if its [`Iterator::size_hint()`] is exact
+fn exact_len(iter: &impl Iterator) -> Option<u64> {
+    match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(u64::try_from(upper).unwrap()),
+        _ => None,
+    }
 }
 
 /// Wraps an iterator to display its progress.
@@ -62,9 +176,18 @@ where
 pub struct ProgressBarIter<T> {
     pub(crate) it: T,
     pub progress: ProgressBar,
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
 }
 
 impl<T> ProgressBarIter<T> {
+    /// Decreases the position of the underlying progress bar by `delta`
+    ///
+    /// Use this when items yielded earlier are put back into the underlying work queue, so they
+    /// are counted again when they are yielded the next time. See [`ProgressBar::dec()`].
+    pub fn dec(&self, delta: u64) {
+        self.progress.dec(delta);
+    }
+
     /// Builder-like function for setting underlying progress bar's style.
     ///
     /// See [`ProgressBar::with_style()`].
@@ -112,12 +235,58 @@ impl<T> ProgressBarIter<T> {
         self.progress = self.progress.with_finish(finish);
         self
     }
+
+    /// Builder-like function for stopping early once `token` is set
+    ///
+    /// After the token is set to `true`, iterations and stream polls return `None`, and reads
+    /// and writes fail with an [`io::ErrorKind::Other`] error. The progress bar is abandoned the
+    /// first time the cancellation is observed. This makes Ctrl-C handlers and timeouts
+    /// composable with wrapped iterators and I/O objects.
+    ///
+    /// [`io::ErrorKind::Interrupted`] is deliberately not used, since [`io::copy()`] and
+    /// [`io::Read::read_exact()`] retry on it and would never return.
+    ///
+    /// ```rust
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use std::sync::Arc;
+    /// # use indicatif::ProgressIterator;
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let mut iter = (0..100).progress().with_cancellation(cancel.clone());
+    /// assert_eq!(iter.next(), Some(0));
+    /// cancel.store(true, Ordering::Relaxed);
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn with_cancellation(mut self, token: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Returns whether the cancellation token is set, abandoning the progress bar if it is
+    fn is_cancelled(&self) -> bool {
+        match &self.cancel {
+            Some(token) if token.load(Ordering::Relaxed) => {
+                if !self.progress.is_finished() {
+                    self.progress.abandon();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "operation cancelled")
 }
 
 impl<S, T: Iterator<Item = S>> Iterator for ProgressBarIter<T> {
     type Item = S;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.is_cancelled() {
+            return None;
+        }
+
         let item = self.it.next();
 
         if item.is_some() {
@@ -138,6 +307,10 @@ impl<T: ExactSizeIterator> ExactSizeIterator for ProgressBarIter<T> {
 
 impl<T: DoubleEndedIterator> DoubleEndedIterator for ProgressBarIter<T> {
     fn next_back(&mut self) -> Option<Self::Item> {
+        if self.is_cancelled() {
+            return None;
+        }
+
         let item = self.it.next_back();
 
         if item.is_some() {
@@ -154,24 +327,40 @@ impl<T: FusedIterator> FusedIterator for ProgressBarIter<T> {}
 
 impl<R: io::Read> io::Read for ProgressBarIter<R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         let inc = self.it.read(buf)?;
         self.progress.inc(inc as u64);
         Ok(inc)
     }
 
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         let inc = self.it.read_vectored(bufs)?;
         self.progress.inc(inc as u64);
         Ok(inc)
     }
 
     fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         let inc = self.it.read_to_string(buf)?;
         self.progress.inc(inc as u64);
         Ok(inc)
     }
 
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         self.it.read_exact(buf)?;
         self.progress.inc(buf.len() as u64);
         Ok(())
@@ -180,6 +369,10 @@ impl<R: io::Read> io::Read for ProgressBarIter<R> {
 
 impl<R: io::BufRead> io::BufRead for ProgressBarIter<R> {
     fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         self.it.fill_buf()
     }
 
@@ -211,6 +404,10 @@ impl<W: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for ProgressBarIter
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
+        if self.is_cancelled() {
+            return Poll::Ready(Err(cancelled()));
+        }
+
         Pin::new(&mut self.it).poll_write(cx, buf).map(|poll| {
             poll.map(|inc| {
                 self.progress.inc(inc as u64);
@@ -236,6 +433,10 @@ impl<W: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for ProgressBarIter<W
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
+        if self.is_cancelled() {
+            return Poll::Ready(Err(cancelled()));
+        }
+
         let prev_len = buf.filled().len() as u64;
         if let Poll::Ready(e) = Pin::new(&mut self.it).poll_read(cx, buf) {
             self.progress.inc(buf.filled().len() as u64 - prev_len);
@@ -265,6 +466,10 @@ impl<W: tokio::io::AsyncBufRead + Unpin + tokio::io::AsyncRead> tokio::io::Async
 {
     fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
         let this = self.get_mut();
+        if this.is_cancelled() {
+            return Poll::Ready(Err(cancelled()));
+        }
+
         let result = Pin::new(&mut this.it).poll_fill_buf(cx);
         if let Poll::Ready(Ok(buf)) = &result {
             this.progress.inc(buf.len() as u64);
@@ -287,6 +492,10 @@ impl<S: futures_core::Stream + Unpin> futures_core::Stream for ProgressBarIter<S
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
         let this = self.get_mut();
+        if this.is_cancelled() {
+            return std::task::Poll::Ready(None);
+        }
+
         let item = std::pin::Pin::new(&mut this.it).poll_next(cx);
         match &item {
             std::task::Poll::Ready(Some(_)) => this.progress.inc(1),
@@ -297,8 +506,108 @@ impl<S: futures_core::Stream + Unpin> futures_core::Stream for ProgressBarIter<S
     }
 }
 
+/// Wraps a stream of byte chunks to display the number of bytes received
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub trait ProgressStreamExt: futures_core::Stream + Sized {
+    /// Increments `progress` by the length of each chunk of the stream
+    ///
+    /// Unlike [`ProgressBar::wrap_stream()`], which counts items, this shows the number of bytes
+    /// received, so that templates with `{bytes}` and `{bytes_per_sec}` work for streams of
+    /// `Bytes`, `Vec<u8>` and similar. The progress bar is finished when the stream ends.
+    fn progress_bytes_with(self, progress: ProgressBar) -> ProgressBytes<Self>
+    where
+        Self::Item: AsRef<[u8]>,
+    {
+        ProgressBytes {
+            it: self,
+            progress,
+            len: |chunk| chunk.as_ref().len(),
+        }
+    }
+
+    /// Increments `progress` by the length of each successful chunk of a fallible stream
+    ///
+    /// This suits HTTP response bodies, e.g. from `reqwest::Response::bytes_stream()`. Errors
+    /// are passed on without changing the progress bar.
+    ///
+    /// ```rust
+    /// # futures::executor::block_on(async {
+    /// use futures::stream::{self, StreamExt};
+    /// use indicatif::{ProgressBar, ProgressStreamExt};
+    ///
+    /// let body = stream::iter([Ok::<_, ()>(vec![0; 512]), Ok(vec![0; 256])]);
+    /// let pb = ProgressBar::new(768);
+    /// let chunks = body.try_progress_bytes_with(pb.clone()).count().await;
+    /// assert_eq!((chunks, pb.position()), (2, 768));
+    /// # }); // block_on
+    /// ```
+    fn try_progress_bytes_with<T, E>(self, progress: ProgressBar) -> ProgressBytes<Self>
+    where
+        Self: futures_core::Stream<Item = Result<T, E>>,
+        T: AsRef<[u8]>,
+    {
+        ProgressBytes {
+            it: self,
+            progress,
+            len: |chunk| chunk.as_ref().map_or(0, |chunk| chunk.as_ref().len()),
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: futures_core::Stream> ProgressStreamExt for S {}
+
+/// A stream that increments a progress bar by the length of each chunk
+///
+/// See [`ProgressStreamExt::progress_bytes_with()`].
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub struct ProgressBytes<S: futures_core::Stream> {
+    it: S,
+    progress: ProgressBar,
+    len: fn(&S::Item) -> usize,
+}
+
+#[cfg(feature = "futures")]
+impl<S: futures_core::Stream + fmt::Debug> fmt::Debug for ProgressBytes<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressBytes")
+            .field("it", &self.it)
+            .field("progress", &self.progress)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S: futures_core::Stream + Unpin> futures_core::Stream for ProgressBytes<S> {
+    type Item = S::Item;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let item = std::pin::Pin::new(&mut this.it).poll_next(cx);
+        match &item {
+            std::task::Poll::Ready(Some(chunk)) => this.progress.inc((this.len)(chunk) as u64),
+            std::task::Poll::Ready(None) => this.progress.finish_using_style(),
+            std::task::Poll::Pending => {}
+        }
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.it.size_hint()
+    }
+}
+
 impl<W: io::Write> io::Write for ProgressBarIter<W> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         self.it.write(buf).map(|inc| {
             self.progress.inc(inc as u64);
             inc
@@ -306,6 +615,10 @@ impl<W: io::Write> io::Write for ProgressBarIter<W> {
     }
 
     fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        if self.is_cancelled() {
+            return Err(cancelled());
+        }
+
         self.it.write_vectored(bufs).map(|inc| {
             self.progress.inc(inc as u64);
             inc
@@ -321,17 +634,436 @@ impl<W: io::Write> io::Write for ProgressBarIter<W> {
     // fn write_fmt(&mut self, fmt: fmt::Arguments) -> io::Result<()>;
 }
 
+impl<R: io::Read> ProgressBarIter<R> {
+    /// Calls `f` with all bytes read, e.g. to compute a checksum while copying
+    ///
+    /// This avoids a second pass over large files for hashing. Any hasher can be used by
+    /// passing its update function:
+    ///
+    /// ```rust,no_run
+    /// # use std::collections::hash_map::DefaultHasher;
+    /// # use std::fs::File;
+    /// # use std::hash::Hasher;
+    /// # use std::io;
+    /// # use indicatif::ProgressBar;
+    /// # fn test() -> io::Result<()> {
+    /// let source = File::open("work.txt")?;
+    /// let mut target = File::create("done.txt")?;
+    /// let pb = ProgressBar::new(source.metadata()?.len());
+    ///
+    /// let mut hasher = DefaultHasher::new();
+    /// let mut reader = pb.wrap_read(source).inspect(|buf| hasher.write(buf));
+    /// io::copy(&mut reader, &mut target)?;
+    /// drop(reader);
+    /// println!("checksum: {:x}", hasher.finish());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn inspect<F: FnMut(&[u8])>(self, f: F) -> InspectReader<R, F> {
+        InspectReader { inner: self, f }
+    }
+}
+
+/// A reader that passes all bytes read to a callback
+///
+/// See [`ProgressBarIter::inspect()`].
+pub struct InspectReader<R, F> {
+    inner: ProgressBarIter<R>,
+    f: F,
+}
+
+impl<R, F> InspectReader<R, F> {
+    /// The progress bar updated by this reader
+    pub fn progress(&self) -> &ProgressBar {
+        &self.inner.progress
+    }
+}
+
+impl<R: fmt::Debug, F> fmt::Debug for InspectReader<R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InspectReader")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: io::Read, F: FnMut(&[u8])> io::Read for InspectReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        (self.f)(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: io::Write> ProgressBarIter<W> {
+    /// Only count written bytes once they are flushed
+    ///
+    /// By default, bytes are counted as soon as the wrapped writer accepts them. For buffered
+    /// writers this runs ahead of the data actually written, e.g. to disk. With this adapter,
+    /// accepted bytes are held back until a call to [`flush`](io::Write::flush) succeeds, so that
+    /// the progress matches durable progress if the writer is flushed at checkpoints.
+    ///
+    /// Note that a [`BufWriter`](io::BufWriter) also writes its buffer on its own when it is
+    /// full. To count those bytes as well, wrap the inner writer instead:
+    /// `BufWriter::new(pb.wrap_write(file))`.
+    ///
+    /// Bytes that are still pending when the adapter is dropped are not counted, even if the
+    /// wrapped writer flushes them on drop. Call [`flush`](io::Write::flush) before dropping the
+    /// adapter to count them.
+    pub fn count_on_flush(self) -> CountOnFlush<W> {
+        CountOnFlush {
+            inner: self,
+            pending: 0,
+        }
+    }
+}
+
+/// A writer that counts written bytes on the progress bar when they are flushed
+///
+/// See [`ProgressBarIter::count_on_flush()`].
+#[derive(Debug)]
+pub struct CountOnFlush<W> {
+    inner: ProgressBarIter<W>,
+    pending: u64,
+}
+
+impl<W> CountOnFlush<W> {
+    /// The progress bar updated by this writer
+    pub fn progress(&self) -> &ProgressBar {
+        &self.inner.progress
+    }
+
+    /// The number of bytes written but not flushed yet
+    pub fn pending(&self) -> u64 {
+        self.pending
+    }
+}
+
+impl<W: io::Write> io::Write for CountOnFlush<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.inner.is_cancelled() {
+            return Err(cancelled());
+        }
+
+        let n = self.inner.it.write(buf)?;
+        self.pending += n as u64;
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        if self.inner.is_cancelled() {
+            return Err(cancelled());
+        }
+
+        let n = self.inner.it.write_vectored(bufs)?;
+        self.pending += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.inner.is_cancelled() {
+            return Err(cancelled());
+        }
+
+        self.inner.it.flush()?;
+        self.inner.progress.inc(std::mem::take(&mut self.pending));
+        Ok(())
+    }
+}
+
+/// An iterator that sets the message of a progress bar from each item
+///
+/// See [`ProgressIterator::progress_with_message()`].
+pub struct WithMessage<I, F> {
+    it: I,
+    f: F,
+    progress: ProgressBar,
+}
+
+impl<I, F> WithMessage<I, F> {
+    fn set_message<M: Into<Cow<'static, str>>>(&mut self, item: &I::Item)
+    where
+        I: Iterator,
+        F: FnMut(&I::Item) -> M,
+    {
+        // The position is incremented after the item is yielded, which draws the new message
+        if self.progress.would_draw() {
+            let msg = (self.f)(item).into();
+            self.progress.state().state.set_message(msg);
+        }
+    }
+}
+
+impl<I: fmt::Debug, F> fmt::Debug for WithMessage<I, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithMessage")
+            .field("it", &self.it)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I, F, M> Iterator for WithMessage<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> M,
+    M: Into<Cow<'static, str>>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.it.next()?;
+        self.set_message(&item);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.it.size_hint()
+    }
+}
+
+impl<I, F, M> ExactSizeIterator for WithMessage<I, F>
+where
+    I: ExactSizeIterator,
+    F: FnMut(&I::Item) -> M,
+    M: Into<Cow<'static, str>>,
+{
+    fn len(&self) -> usize {
+        self.it.len()
+    }
+}
+
+impl<I, F, M> DoubleEndedIterator for WithMessage<I, F>
+where
+    I: DoubleEndedIterator,
+    F: FnMut(&I::Item) -> M,
+    M: Into<Cow<'static, str>>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.it.next_back()?;
+        self.set_message(&item);
+        Some(item)
+    }
+}
+
+impl<I, F, M> FusedIterator for WithMessage<I, F>
+where
+    I: FusedIterator,
+    F: FnMut(&I::Item) -> M,
+    M: Into<Cow<'static, str>>,
+{
+}
+
+/// An iterator that counts the errors it yields as failures of a progress bar
+///
+/// See [`ProgressIterator::progress_results()`].
+#[derive(Debug)]
+pub struct CountFailures<I> {
+    it: I,
+    progress: ProgressBar,
+}
+
+impl<I, T, E> Iterator for CountFailures<I>
+where
+    I: Iterator<Item = Result<T, E>>,
+{
+    type Item = Result<T, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.it.next()?;
+        if item.is_err() {
+            self.progress.inc_failures(1);
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.it.size_hint()
+    }
+}
+
+impl<I, T, E> ExactSizeIterator for CountFailures<I>
+where
+    I: ExactSizeIterator<Item = Result<T, E>>,
+{
+    fn len(&self) -> usize {
+        self.it.len()
+    }
+}
+
+impl<I, T, E> DoubleEndedIterator for CountFailures<I>
+where
+    I: DoubleEndedIterator<Item = Result<T, E>>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.it.next_back()?;
+        if item.is_err() {
+            self.progress.inc_failures(1);
+        }
+        Some(item)
+    }
+}
+
+impl<I, T, E> FusedIterator for CountFailures<I> where I: FusedIterator<Item = Result<T, E>> {}
+
 impl<S, T: Iterator<Item = S>> ProgressIterator for T {
     fn progress_with(self, progress: ProgressBar) -> ProgressBarIter<Self> {
-        ProgressBarIter { it: self, progress }
+        ProgressBarIter {
+            it: self,
+            progress,
+            cancel: None,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::io::{self, Read};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
     use crate::iter::{ProgressBarIter, ProgressIterator};
     use crate::progress_bar::ProgressBar;
-    use crate::ProgressStyle;
+    use crate::{ProgressDrawTarget, ProgressStyle};
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn progress_bytes() {
+        use futures::stream::{self, StreamExt};
+
+        use crate::ProgressStreamExt;
+
+        let pb = ProgressBar::hidden();
+        let chunks = stream::iter([&b"hello"[..], b" world"]).progress_bytes_with(pb.clone());
+        assert_eq!(futures::executor::block_on(chunks.count()), 2);
+        assert_eq!(pb.position(), 11);
+        assert!(pb.is_finished());
+
+        let pb = ProgressBar::hidden();
+        let body = stream::iter([Ok(vec![0; 3]), Err(()), Ok(vec![0; 4])]);
+        let chunks = body.try_progress_bytes_with(pb.clone()).collect::<Vec<_>>();
+        assert_eq!(futures::executor::block_on(chunks).len(), 3);
+        assert_eq!(pb.position(), 7);
+    }
+
+    #[test]
+    fn inspect() {
+        use std::io::Read;
+
+        let pb = ProgressBar::hidden();
+        let mut seen = Vec::<u8>::new();
+        let mut reader = pb
+            .wrap_read(&b"hello world"[..])
+            .inspect(|buf| seen.extend_from_slice(buf));
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+        drop(reader);
+
+        assert_eq!(out, "hello world");
+        assert_eq!(seen, b"hello world");
+        assert_eq!(pb.position(), 11);
+    }
+
+    #[test]
+    fn cancellation() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut reader = ProgressBar::hidden()
+            .wrap_read(&[1u8, 2, 3, 4][..])
+            .with_cancellation(cancel.clone());
+
+        let mut buf = [0; 2];
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        cancel.store(true, Ordering::Relaxed);
+        let err = reader.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+        assert_eq!(reader.progress.position(), 2);
+        assert_eq!(
+            reader.progress.finish_reason(),
+            Some(crate::FinishReason::Abandoned)
+        );
+    }
+
+    #[test]
+    fn progress_with_message_hidden() {
+        let mut calls = 0;
+        let iter = ["a", "b"].into_iter().progress_with_message(|item| {
+            calls += 1;
+            item.to_string()
+        });
+        iter.progress.set_draw_target(ProgressDrawTarget::hidden());
+        assert_eq!(iter.progress.length(), Some(2));
+        assert_eq!(iter.count(), 2);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn progress_results() {
+        let iter = [Ok(1), Err("bad"), Ok(3), Err("worse")]
+            .into_iter()
+            .progress_results();
+        let pb = iter.progress.clone();
+        assert_eq!(iter.filter_map(Result::ok).sum::<i32>(), 4);
+        assert_eq!(pb.position(), 4);
+        assert_eq!(pb.failures(), 2);
+        assert_eq!(pb.length(), Some(4));
+
+        // An upper bound alone is not the length
+        let iter = [Ok(1), Err("bad"), Ok(3)]
+            .into_iter()
+            .filter(|item| item != &Ok(3))
+            .progress_results();
+        assert_eq!(iter.progress.length(), None);
+        assert_eq!(iter.count(), 2);
+    }
+
+    #[test]
+    fn track() {
+        let iter = super::track(vec![1, 2, 3]);
+        assert_eq!(iter.progress.length(), Some(3));
+        assert_eq!(iter.sum::<i32>(), 6);
+
+        let iter = super::track((0..).take_while(|&i| i < 5));
+        assert_eq!(iter.progress.length(), None);
+        let pb = iter.progress.clone();
+        assert_eq!(iter.count(), 5);
+        assert_eq!(pb.position(), 5);
+
+        // An upper bound alone is not the length
+        let iter = super::track((0..10).filter(|i| i % 2 == 0));
+        assert_eq!(iter.progress.length(), None);
+        assert_eq!(iter.count(), 5);
+    }
+
+    #[test]
+    fn count_on_flush() {
+        use std::io::{BufWriter, Write};
+
+        let pb = ProgressBar::hidden();
+        let mut writer = pb.wrap_write(BufWriter::new(Vec::new())).count_on_flush();
+        writer.write_all(b"hello").unwrap();
+        assert_eq!((pb.position(), writer.pending()), (0, 5));
+
+        writer.flush().unwrap();
+        assert_eq!((pb.position(), writer.pending()), (5, 0));
+    }
+
+    #[test]
+    fn count_on_flush_cancellation() {
+        use std::io::{BufWriter, Write};
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let pb = ProgressBar::hidden();
+        let mut writer = pb
+            .wrap_write(BufWriter::new(Vec::new()))
+            .with_cancellation(cancel.clone())
+            .count_on_flush();
+        writer.write_all(b"hello").unwrap();
+        cancel.store(true, Ordering::Relaxed);
+
+        assert_eq!(writer.write(b"!").unwrap_err().kind(), io::ErrorKind::Other);
+        assert_eq!(writer.flush().unwrap_err().kind(), io::ErrorKind::Other);
+        assert_eq!((pb.position(), writer.pending()), (0, 5));
+        assert_eq!(pb.finish_reason(), Some(crate::FinishReason::Abandoned));
+    }
 
     #[test]
     fn it_can_wrap_an_iterator() {
@@ -141,15 +141,28 @@
 //!
 //! ```text
 //! <^>             for an optional alignment specification (left, center and right respectively)
+//! 0               an optional zero to pad numbers with zeros on the left, like `{:04}` in Rust
 //! WIDTH           an optional width as positive integer
+//! ,               an optional comma to group the digits of numbers with commas
 //! !               an optional exclamation mark to enable truncation
+//! +LINES          an optional plus sign to wrap onto at most LINES lines (only for `wide_msg`)
+//! ~PRECISION      an optional number of decimals (only for `percent_precise`, `per_sec` and
+//!                 `smoothed_per_sec`)
 //! .STYLE          an optional dot separated style string
 //! /STYLE          an optional dot separated alternative style string
 //! ```
 //!
+//! The numeric keys are `pos`, `len`, `session_pos`, `failures`, `successes`, `percent`,
+//! `percent_precise`, `per_sec` and `smoothed_per_sec`. For example, `{pos:>9,}` renders
+//! `1,234,567` right-aligned and `{per_sec:~2.green}` renders `1,234.50/s` in green.
+//!
 //! For the style component see [`Style::from_dotted_str`](https://docs.rs/console/0.7.5/console/struct.Style.html#method.from_dotted_str)
 //! for more information. Indicatif uses the `console` base crate for all
 //! colorization and formatting options.
+//! In addition, colors can be given as `#rrggbb` for the foreground and `on_#rrggbb` for the
+//! background, like `{bar:40.#ff8800/#333333}`. They are rendered as 24-bit colors if the
+//! `COLORTERM` environment variable says the terminal supports them, and as the closest color
+//! of the 256 or 16 color palettes otherwise.
 //!
 //! Some examples for templates:
 //!
@@ -171,6 +184,10 @@
 //!     .progress_chars("##-"));
 //! ```
 //!
+//! Unknown keys render as empty strings and unknown style names are ignored. Templates
+//! from configuration files can be validated with [`ProgressStyle::with_template_strict`],
+//! which rejects them instead.
+//!
 //! The following keys exist:
 //!
 //! * `bar`: renders a progress bar. By default 20 characters wide.  The
@@ -179,19 +196,33 @@
 //! * `wide_bar`: like `bar` but always fills the remaining space. It should not be used with `wide_msg`.
 //! * `spinner`: renders the spinner (current tick string). Note that spinners do not automatically tick by default. You either
 //!   need to call `enable_steady_tick` or manually call `tick`.
+//! * `spinner_frame_index`: renders the index of the current spinner frame (see
+//!   [`ProgressState::tick`] for driving custom animations).
 //! * `prefix`: renders the prefix set on the progress bar.
+//! * `gutter`: renders the prefix right-aligned in a fixed-width column (see
+//!   [`ProgressStyle::with_gutter`]).
+//! * `id`: renders the identifier set on the progress bar (see [`ProgressBar::set_id`]).
+//! * `finish_reason`: renders how the progress bar ended (`finished`, `abandoned`, `failed` or
+//!   `dropped`), or nothing while it is in progress (see [`ProgressBar::finish_reason`]).
 //! * `msg`: renders the currently set message on the progress bar.
-//! * `wide_msg`: like `msg` but always fills the remaining space and truncates. It should not be used
-//!   with `wide_bar`.
-//! * `pos`: renders the current position of the bar as integer
+//! * `wide_msg`: like `msg` but always fills the remaining space and truncates (or scrolls, see
+//!   [`ProgressStyle::message_overflow`]). It should not be used with `wide_bar`. With the `+`
+//!   flag, e.g. `{wide_msg:+3}`, long messages wrap onto continuation lines (at most 3 lines
+//!   in total here; without a number the number of lines is unlimited).
+//! * `pos`: renders the current position of the bar as integer (or scaled, see
+//!   [`ProgressStyle::with_unit_scale`])
+//! * `session_pos`: renders the progress made in this session (see
+//!   [`ProgressBar::reset_session`]).
 //! * `human_pos`: renders the current position of the bar as an integer, with commas as the
 //!   thousands separator.
-//! * `len`: renders the amount of work to be done as an integer
+//! * `len`: renders the amount of work to be done as an integer (or scaled, like `pos`)
 //! * `human_len`: renders the total length of the bar as an integer, with commas as the thousands
 //!   separator.
 //! * `percent`: renders the current position of the bar as a percentage of the total length (as an integer).
 //! * `percent_precise`: renders the current position of the bar as a percentage of the total length (with 3 fraction digits).
 //! * `bytes`: renders the current position of the bar as bytes (alias of `binary_bytes`).
+//! * `session_bytes`: renders the progress made in this session as bytes (see
+//!   [`ProgressBar::reset_session`]).
 //! * `total_bytes`: renders the total length of the bar as bytes (alias of `binary_total_bytes`).
 //! * `decimal_bytes`: renders the current position of the bar as bytes using
 //!   power-of-10 units, i.e. `MB`, `kB`, etc.
@@ -203,20 +234,54 @@
 //!   power-of-two units, i.e. `MiB`, `KiB`, etc.
 //! * `elapsed_precise`: renders the elapsed time as `HH:MM:SS`.
 //! * `elapsed`: renders the elapsed time as `42s`, `1m` etc.
+//! * `multi_elapsed_precise`, `multi_elapsed`: like `elapsed_precise` and `elapsed`, but the time
+//!   since the [`MultiProgress`] the bar belongs to was created (see [`MultiProgress::elapsed`]),
+//!   e.g. to show the overall time in a header. Without a [`MultiProgress`], this is the
+//!   elapsed time of the bar itself.
 //! * `per_sec`: renders the speed in steps per second.
+//! * `trend`: renders `↑` if the recent speed is notably higher than the average speed since
+//!   the start, `↓` if it is notably lower and `→` otherwise.
 //! * `bytes_per_sec`: renders the speed in bytes per second (alias of `binary_bytes_per_sec`).
 //! * `decimal_bytes_per_sec`: renders the speed in bytes per second using
 //!   power-of-10 units, i.e. `MB`, `kB`, etc.
 //! * `binary_bytes_per_sec`: renders the speed in bytes per second using
 //!   power-of-two units, i.e. `MiB`, `KiB`, etc.
 //! * `eta_precise`: the remaining time (like `elapsed_precise`).
-//! * `eta`: the remaining time (like `elapsed`).
+//! * `eta`: the remaining time (like `elapsed`). An externally computed ETA can be provided
+//!   with [`ProgressBar::set_eta`].
+//! * `time_left_or_total_precise`: the remaining time while in progress, and the elapsed time
+//!   once finished (like `elapsed_precise`).
+//! * `time_left_or_total`: the remaining time while in progress, and the elapsed time once
+//!   finished (like `elapsed`).
 //! * `duration_precise`: the extrapolated total duration (like `elapsed_precise`).
 //! * `duration`: the extrapolated total duration time (like `elapsed`).
+//! * `failures`, `successes`: the number of failed items and of the remaining items up to the
+//!   current position (see [`ProgressBar::inc_failures`]).
+//! * `smoothed_per_sec`, `smoothed_eta`: the speed in steps per second and the remaining time
+//!   (like `elapsed`), using double exponential smoothing of the speed between updates. The
+//!   smoothing factor can be tuned with [`ProgressStyle::smoothing_alpha`].
+//! * `min_item_time`, `max_item_time`, `p95_item_time`: the minimum, maximum and 95th percentile
+//!   time spent per item. These require [`ProgressStyle::item_time_stats`] to be enabled.
 //!
 //! If the list above does not contain the value you need, consider creating a custom
 //! [`ProgressTracker`][crate::style::ProgressTracker] implementation.
 //!
+//! Parts of a template can be shown depending on the state of the progress bar with
+//! `{if ...}`, `{else}` and `{endif}`, so that a single style works for progress bars with and
+//! without a length:
+//!
+//! ```rust
+//! # use indicatif::ProgressStyle;
+//! let style = ProgressStyle::with_template(
+//!     "{spinner} {if len}{pos}/{len}{else}{pos} items{endif}{if msg}: {msg}{endif}",
+//! )
+//! .unwrap();
+//! ```
+//!
+//! The conditions are `len` (the length is known), `msg` and `prefix` (the message or prefix
+//! is not empty) and `finished`. Prefix a condition with `!` to negate it, e.g. `{if !len}`.
+//! Sections can be nested.
+//!
 //! The design of the progress bar can be altered with the integrated
 //! template functionality.  The template can be set by changing a
 //! [`ProgressStyle`] and attaching it to the progress bar.
@@ -240,39 +305,104 @@
 //!
 //! * `rayon`: adds rayon support
 //! * `improved_unicode`: adds improved unicode support (graphemes, better width calculation)
+//! * `log_bridge`: adds [`log_bridge::LogWrapper`] to print `log` records above progress bars
+//! * `tracing`: adds [`tracing_layer::IndicatifLayer`] to show `tracing` spans as progress bars
+//!   and print events above them
+//! * `input`: adds [`listen_for_keys()`] and [`pause_on_keys()`] to pause or cancel on key presses
+//! * `ipc`: adds [`ProgressDrawTarget::ipc()`] to report progress to another process as binary
+//!   events, and [`IpcReader`] to decode them
+//! * `replay`: adds [`replay::Replay`] to drive progress bars from a file of timed operations
+//! * `diagnose`: adds [`diagnose()`] to print terminal detection results and a test pattern
+//! * `serde`: implements `Serialize` and `Deserialize` for [`ProgressSnapshot`]
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![warn(unreachable_pub)]
 
+mod cast;
+#[cfg(feature = "diagnose")]
+mod diagnose;
 mod draw_target;
+mod error;
+#[cfg(feature = "ffi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
 mod format;
 #[cfg(feature = "in_memory")]
 mod in_memory;
+#[cfg(feature = "input")]
+mod input;
+pub mod io;
+#[cfg(feature = "ipc")]
+mod ipc;
 mod iter;
+#[cfg(feature = "log_bridge")]
+#[cfg_attr(docsrs, doc(cfg(feature = "log_bridge")))]
+pub mod log_bridge;
 mod multi;
 mod progress_bar;
+pub mod rate;
 #[cfg(feature = "rayon")]
 mod rayon;
+#[cfg(feature = "replay")]
+#[cfg_attr(docsrs, doc(cfg(feature = "replay")))]
+pub mod replay;
 mod state;
 pub mod style;
+mod sync;
 mod term_like;
+#[cfg(feature = "tracing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+pub mod tracing_layer;
 
+#[cfg(feature = "diagnose")]
+pub use crate::diagnose::diagnose;
 pub use crate::draw_target::ProgressDrawTarget;
+pub use crate::error::Error;
 pub use crate::format::{
     BinaryBytes, DecimalBytes, FormattedDuration, HumanBytes, HumanCount, HumanDuration,
     HumanFloatCount,
 };
 #[cfg(feature = "in_memory")]
 pub use crate::in_memory::InMemoryTerm;
-pub use crate::iter::{ProgressBarIter, ProgressIterator};
-pub use crate::multi::{MultiProgress, MultiProgressAlignment};
+#[cfg(feature = "input")]
+pub use crate::input::{listen_for_keys, pause_on_keys, InputEvent, KeyListener};
+#[cfg(feature = "ipc")]
+pub use crate::ipc::{IpcEvent, IpcReader};
+pub use crate::iter::{
+    track, track_with_style, CountFailures, CountOnFlush, InspectReader, ProgressBarIter,
+    ProgressIterator, WithMessage,
+};
+#[cfg(feature = "futures")]
+pub use crate::iter::{ProgressBytes, ProgressStreamExt};
+pub use crate::multi::{
+    MultiProgress, MultiProgressAlignment, MultiProgressGuard, ProgressEvent, ProgressGroup,
+};
 pub use crate::progress_bar::{ProgressBar, WeakProgressBar};
 #[cfg(feature = "rayon")]
 pub use crate::rayon::ParallelProgressIterator;
-pub use crate::state::{ProgressFinish, ProgressState};
+pub use crate::state::{
+    FinishReason, IpcProgress, ProgressFinish, ProgressSnapshot, ProgressState,
+};
 pub use crate::style::ProgressStyle;
 pub use crate::term_like::TermLike;
 
+/// Prints a formatted line above a progress bar, formatting it only if it is actually printed
+///
+/// `progress_println!(pb, "{} items", n)` is a shorthand for
+/// `pb.println_fmt(format_args!("{} items", n))`, see [`ProgressBar::println_fmt()`].
+///
+/// ```rust
+/// # use indicatif::{progress_println, ProgressBar};
+/// let pb = ProgressBar::hidden();
+/// progress_println!(pb, "processed {} items", 42);
+/// ```
+#[macro_export]
+macro_rules! progress_println {
+    ($pb:expr, $($arg:tt)*) => {
+        $pb.println_fmt(::core::format_args!($($arg)*))
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -281,10 +411,13 @@ mod tests {
     trait MustBeThreadSafe: Send + Sync {}
 
     // Ensure that the following types are `Send + Sync`
+    #[cfg(feature = "ipc")]
+    impl MustBeThreadSafe for IpcEvent {}
     impl MustBeThreadSafe for MultiProgress {}
     impl MustBeThreadSafe for MultiProgressAlignment {}
     impl MustBeThreadSafe for ProgressBar {}
     impl MustBeThreadSafe for ProgressBarIter<()> {}
+    impl MustBeThreadSafe for ProgressDrawTarget {}
     impl MustBeThreadSafe for ProgressFinish {}
     impl MustBeThreadSafe for ProgressState {}
     impl MustBeThreadSafe for ProgressStyle {}
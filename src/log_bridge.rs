@@ -0,0 +1,190 @@
+//! Routes records of the [`log`] crate through a [`MultiProgress`]
+//!
+//! Log records written directly to the terminal interleave with the redraws of progress bars,
+//! leaving fragments of bars between log lines. [`LogWrapper`] wraps an existing logger (such as
+//! `env_logger`) and suspends the progress bars while it writes a record, so that the record
+//! ends up above them.
+//!
+//! ```rust,no_run
+//! # struct Logger;
+//! # impl log::Log for Logger {
+//! #     fn enabled(&self, _: &log::Metadata<'_>) -> bool { true }
+//! #     fn log(&self, _: &log::Record<'_>) {}
+//! #     fn flush(&self) {}
+//! # }
+//! # let logger = Logger;
+//! use indicatif::log_bridge::LogWrapper;
+//! use indicatif::{MultiProgress, ProgressBar};
+//!
+//! let multi = MultiProgress::new();
+//! LogWrapper::new(logger, multi.clone()).try_init().unwrap();
+//!
+//! let pb = multi.add(ProgressBar::new(10));
+//! log::info!("this is printed above the progress bar");
+//! ```
+
+use std::fmt;
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::MultiProgress;
+
+/// A [`Log`] implementation that suspends the progress bars of a [`MultiProgress`] while
+/// another logger writes a record
+///
+/// The wrapped logger decides which records are enabled and how they are formatted and
+/// written; the wrapper only clears the progress bars before and redraws them after.
+pub struct LogWrapper<L> {
+    log: L,
+    multi: MultiProgress,
+}
+
+impl<L: Log + 'static> LogWrapper<L> {
+    /// Wraps `log`, suspending `multi` while it writes a record
+    pub fn new(log: L, multi: MultiProgress) -> Self {
+        Self { log, multi }
+    }
+
+    /// Returns the [`MultiProgress`] records are printed through
+    pub fn multi(&self) -> MultiProgress {
+        self.multi.clone()
+    }
+
+    /// Installs this wrapper as the global logger
+    ///
+    /// The maximum log level is set to the most verbose level the wrapped logger enables by
+    /// default, like the wrapped logger would do when installed on its own. If it enables more
+    /// verbose levels for some targets only, call [`log::set_max_level()`] afterwards.
+    pub fn try_init(self) -> Result<(), SetLoggerError> {
+        let max_level = max_level(&self.log);
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl<L: Log> Log for LogWrapper<L> {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.log.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.log.enabled(record.metadata()) {
+            return;
+        }
+
+        self.multi.suspend(|| self.log.log(record));
+    }
+
+    fn flush(&self) {
+        self.log.flush();
+    }
+}
+
+/// The most verbose level `log` enables for records without a target
+fn max_level(log: &impl Log) -> LevelFilter {
+    [
+        LevelFilter::Trace,
+        LevelFilter::Debug,
+        LevelFilter::Info,
+        LevelFilter::Warn,
+        LevelFilter::Error,
+    ]
+    .into_iter()
+    .find(|filter| {
+        let level = filter.to_level().unwrap();
+        log.enabled(&Metadata::builder().level(level).build())
+    })
+    .unwrap_or(LevelFilter::Off)
+}
+
+impl<L> fmt::Debug for LogWrapper<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogWrapper")
+            .field("multi", &self.multi)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+
+    use super::{max_level, LogWrapper};
+    use crate::{MultiProgress, ProgressDrawTarget};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Log for Recorder {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn max_level_of_inner_logger() {
+        assert_eq!(max_level(&Recorder::default()), LevelFilter::Info);
+    }
+
+    #[test]
+    fn hidden_passes_enabled_records() {
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let wrapper = LogWrapper::new(Recorder::default(), multi);
+
+        for (level, msg) in [(Level::Info, "info"), (Level::Debug, "debug")] {
+            wrapper.log(
+                &Record::builder()
+                    .level(level)
+                    .args(format_args!("{msg}"))
+                    .build(),
+            );
+        }
+
+        assert_eq!(*wrapper.log.0.lock().unwrap(), ["info"]);
+    }
+
+    #[cfg(feature = "in_memory")]
+    #[test]
+    fn suspends_bars() {
+        use crate::{InMemoryTerm, ProgressBar, ProgressStyle};
+
+        // Records what the terminal shows while a record is logged
+        struct Screen(InMemoryTerm, Mutex<Vec<String>>);
+
+        impl Log for Screen {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn log(&self, _: &Record<'_>) {
+                self.1.lock().unwrap().push(self.0.contents());
+            }
+
+            fn flush(&self) {}
+        }
+
+        let in_mem = InMemoryTerm::new(10, 80);
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(
+            in_mem.clone(),
+        )));
+        let pb = multi.add(
+            ProgressBar::new(10).with_style(ProgressStyle::with_template("{pos}/{len}").unwrap()),
+        );
+        pb.tick();
+
+        let wrapper = LogWrapper::new(Screen(in_mem.clone(), Mutex::default()), multi);
+        wrapper.log(&Record::builder().args(format_args!("careful")).build());
+
+        assert_eq!(*wrapper.log.1.lock().unwrap(), [""]);
+        assert_eq!(in_mem.contents(), "0/10");
+    }
+}
@@ -1,15 +1,32 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Formatter};
+use std::fs::File;
 use std::io;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, RwLock};
 use std::thread::panicking;
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
+use console::measure_text_width;
+
+use crate::cast::CastRecorder;
 use crate::draw_target::{
-    visual_line_count, DrawState, DrawStateWrapper, LineAdjust, LineType, ProgressDrawTarget,
-    VisualLines,
+    visual_line_count, DeferredOutput, DrawState, DrawStateWrapper, LineAdjust, LineType,
+    ProgressDrawTarget, VisualLines,
 };
-use crate::progress_bar::ProgressBar;
+use crate::error::Error;
+use crate::format::{FormattedDuration, HumanFloatCount};
+use crate::progress_bar::{ProgressBar, WeakProgressBar};
+use crate::state::{IpcProgress, ProgressSnapshot};
+use crate::style::ProgressStyle;
+use crate::sync::IgnorePoison;
 #[cfg(target_arch = "wasm32")]
 use web_time::Instant;
 
@@ -45,13 +62,51 @@ impl MultiProgress {
         }
     }
 
+    /// Makes this the current [`MultiProgress`] of this thread until the guard is dropped
+    ///
+    /// Progress bars created with [`ProgressBar::new_in_current()`] while the guard is alive are
+    /// added to this [`MultiProgress`], so library code can attach its progress bars to the one
+    /// of the caller without passing a handle through every function. Guards can be nested; the
+    /// most recently entered [`MultiProgress`] is the current one.
+    pub fn enter(&self) -> MultiProgressGuard {
+        CURRENT.with(|current| current.borrow_mut().push(self.clone()));
+        MultiProgressGuard {
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Returns the current [`MultiProgress`] of this thread, see [`MultiProgress::enter()`]
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().last().cloned())
+    }
+
     /// Sets a different draw target for the multiprogress bar.
     ///
+    /// Progress bars that were already added keep rendering into the new target, and are
+    /// redrawn right away. This includes switching from a hidden target to a terminal at
+    /// runtime, for example when output is redirected to a log file first: progress bars whose
+    /// state was not rendered while hidden show up without waiting for their next update.
+    ///
     /// Use [`MultiProgress::with_draw_target`] to set the draw target during creation.
     pub fn set_draw_target(&self, target: ProgressDrawTarget) {
-        let mut state = self.state.write().unwrap();
+        let mut state = self.state.write().ignore_poison();
+        let _ = state.flush_output();
         state.draw_target.disconnect(Instant::now());
         state.draw_target = target;
+        // Zombie lines were drawn to the old target, the new one starts out empty
+        state.zombie_lines_count = VisualLines::default();
+        let bars = state
+            .members
+            .iter()
+            .filter(|member| !member.is_zombie)
+            .filter_map(|member| member.bar.clone())
+            .collect::<Vec<_>>();
+        drop(state);
+
+        // Progress bars must be locked without holding the lock on the `MultiState`
+        for bar in bars.iter().filter_map(WeakProgressBar::upgrade) {
+            let _ = bar.state().draw(true, Instant::now());
+        }
     }
 
     /// Set whether we should try to move the cursor when possible instead of clearing lines.
@@ -61,14 +116,64 @@ impl MultiProgress {
     pub fn set_move_cursor(&self, move_cursor: bool) {
         self.state
             .write()
-            .unwrap()
+            .ignore_poison()
             .draw_target
             .set_move_cursor(move_cursor);
     }
 
     /// Set alignment flag
     pub fn set_alignment(&self, alignment: MultiProgressAlignment) {
-        self.state.write().unwrap().alignment = alignment;
+        self.state.write().ignore_poison().alignment = alignment;
+    }
+
+    /// Sets the maximum rate at which steady ticks redraw the progress bars (default: 0)
+    ///
+    /// With [`ProgressBar::enable_steady_tick()`], each ticking progress bar redraws the whole
+    /// stack on every tick, so many spinners with short intervals cause a lot of drawing. With a
+    /// limit, ticks arriving within `1 / fps` seconds of the previous frame only update the
+    /// lines of their progress bar, which are then drawn with the next frame. Other updates are
+    /// not affected. A rate of 0 disables this limit, which is the default.
+    ///
+    /// [`ProgressBar::enable_steady_tick()`]: crate::ProgressBar::enable_steady_tick
+    pub fn set_max_tick_fps(&self, fps: u8) {
+        self.state.write().ignore_poison().min_tick_interval = match fps {
+            0 => Duration::ZERO,
+            fps => Duration::from_millis(1000 / fps as u64),
+        };
+    }
+
+    /// Sets a callback that can modify the lines of the progress bars before each frame is drawn
+    ///
+    /// The callback receives the lines of all progress bars in display order (not including lines
+    /// printed with [`MultiProgress::println`]) and can insert separators or group headers, or
+    /// remove lines. Lines must not contain newlines.
+    ///
+    /// Note that finished progress bars at the top are moved into the scrollback by line count,
+    /// so lines inserted above them may end up in the scrollback in their place.
+    ///
+    /// **Note:** The callback runs while the internal lock of the [`MultiProgress`] is held, and
+    /// possibly while the lock of the progress bar that caused the frame is held as well. It
+    /// must not call any method of this [`MultiProgress`] or of its progress bars (for example
+    /// to print a line or update a message), which deadlocks. It should also be quick, as all
+    /// progress bars are blocked while it runs.
+    pub fn set_frame_decorator(
+        &self,
+        decorator: impl Fn(&mut Vec<String>) + Send + Sync + 'static,
+    ) {
+        self.state.write().ignore_poison().frame_decorator =
+            Some(FrameDecorator(Box::new(decorator)));
+    }
+
+    /// Subscribes to structured events for the members of this [`MultiProgress`]
+    ///
+    /// The returned receiver gets a [`ProgressEvent`] whenever a progress bar is added, updated,
+    /// finished or removed, independent of the draw target. This can be used to mirror progress
+    /// to a GUI or a log without parsing the rendered output. A subscription ends when the
+    /// receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<ProgressEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.state.write().ignore_poison().subscribers.push(tx);
+        rx
     }
 
     /// Adds a progress bar.
@@ -86,6 +191,36 @@ impl MultiProgress {
         self.internalize(InsertLocation::End, pb)
     }
 
+    /// Adds a section with a header line below all other progress bars
+    ///
+    /// Progress bars added with [`ProgressGroup::add()`] are drawn below the header and the
+    /// progress bars added to the group before, even if progress bars are added to other groups
+    /// or to the [`MultiProgress`] concurrently. Unless the group is the first member of the
+    /// [`MultiProgress`], an empty line separates its header from the progress bars above.
+    pub fn add_group(&self, title: impl Into<Cow<'static, str>>) -> ProgressGroup {
+        let (id, separated) = {
+            let mut state = self.state.write().ignore_poison();
+            state.next_group += 1;
+            (state.next_group, !state.ordering.is_empty())
+        };
+
+        let template = match separated {
+            true => "\n{msg:.bold}",
+            false => "{msg:.bold}",
+        };
+        let header = ProgressBar::with_draw_target(None, ProgressDrawTarget::hidden())
+            .with_style(ProgressStyle::with_template(template).unwrap())
+            .with_message(title);
+        let header = self.internalize(InsertLocation::Group(id), header);
+        header.tick();
+
+        ProgressGroup {
+            multi: self.clone(),
+            id,
+            header,
+        }
+    }
+
     /// Inserts a progress bar.
     ///
     /// The progress bar inserted at position `index` will have the draw
@@ -101,6 +236,48 @@ impl MultiProgress {
         self.internalize(InsertLocation::Index(index), pb)
     }
 
+    /// Adds a progress bar that is removed as soon as it finishes
+    ///
+    /// The progress bar is positioned below all other bars, like with [`MultiProgress::add`].
+    /// Once it is finished, its last lines (if any) are printed above the remaining progress
+    /// bars and it is removed, like with [`ProgressBar::finish_into_scrollback()`]. Bars finished with
+    /// [`ProgressBar::finish_and_clear()`] simply disappear. This keeps long-running programs
+    /// with many short-lived progress bars from accumulating finished members.
+    ///
+    /// Inserting a progress bar that is already a member of the [`MultiProgress`]
+    /// will have no effect.
+    pub fn add_with_auto_remove(&self, pb: ProgressBar) -> ProgressBar {
+        if self.is_member(&pb) {
+            return pb;
+        }
+
+        let pb = self.internalize(InsertLocation::End, pb);
+        if let Some(idx) = pb.index() {
+            self.state.write().ignore_poison().members[idx].auto_remove = true;
+        }
+        pb
+    }
+
+    /// Adds a progress bar with a priority
+    ///
+    /// The progress bar is positioned below all other bars, like with [`MultiProgress::add`].
+    /// When the progress bars do not fit the terminal height, the bars with the lowest priority
+    /// (and among those, the ones further down) are hidden and summarized in a single
+    /// `... and N more` line, so that bars with a higher priority remain visible. Bars added
+    /// without a priority have priority 0.
+    ///
+    /// Inserting a progress bar that is already a member of the [`MultiProgress`]
+    /// will have no effect.
+    pub fn insert_with_priority(&self, priority: i32, pb: ProgressBar) -> ProgressBar {
+        let pb = self.internalize(InsertLocation::End, pb);
+        if let Some(idx) = pb.index() {
+            let mut state = self.state.write().ignore_poison();
+            state.members[idx].priority = priority;
+            state.prioritized = true;
+        }
+        pb
+    }
+
     /// Inserts a progress bar from the back.
     ///
     /// The progress bar inserted at position `MultiProgressState::objects.len() - index`
@@ -141,6 +318,47 @@ impl MultiProgress {
         self.internalize(InsertLocation::After(after.index().unwrap()), pb)
     }
 
+    /// Inserts a progress bar before an existing one, like [`MultiProgress::insert_before()`]
+    ///
+    /// Fails with [`Error::InvalidUsage`] instead of panicking if `before` is not a member of
+    /// this [`MultiProgress`].
+    pub fn try_insert_before(
+        &self,
+        before: &ProgressBar,
+        pb: ProgressBar,
+    ) -> Result<ProgressBar, Error> {
+        let idx = self.member_index(before)?;
+        Ok(self.internalize(InsertLocation::Before(idx), pb))
+    }
+
+    /// Inserts a progress bar after an existing one, like [`MultiProgress::insert_after()`]
+    ///
+    /// Fails with [`Error::InvalidUsage`] instead of panicking if `after` is not a member of
+    /// this [`MultiProgress`].
+    pub fn try_insert_after(
+        &self,
+        after: &ProgressBar,
+        pb: ProgressBar,
+    ) -> Result<ProgressBar, Error> {
+        let idx = self.member_index(after)?;
+        Ok(self.internalize(InsertLocation::After(idx), pb))
+    }
+
+    /// Returns the index of `pb` if it's a member of this `MultiProgress`
+    fn member_index(&self, pb: &ProgressBar) -> Result<usize, Error> {
+        match pb.try_state()?.draw_target.remote() {
+            Some((state, idx)) if Arc::ptr_eq(&self.state, state) => Ok(idx),
+            _ => Err(Error::InvalidUsage(
+                "the progress bar is not a member of the MultiProgress",
+            )),
+        }
+    }
+
+    /// Inserts a progress bar after the member at `idx`, see `ProgressBar::add_child()`
+    pub(crate) fn insert_after_index(&self, idx: usize, pb: ProgressBar) -> ProgressBar {
+        self.internalize(InsertLocation::After(idx), pb)
+    }
+
     /// Removes a progress bar.
     ///
     /// The progress bar is removed only if it was previously inserted or added
@@ -158,25 +376,318 @@ impl MultiProgress {
             _ => return,
         };
 
+        let snapshot = state.snapshot(Instant::now());
+        state.draw_target = ProgressDrawTarget::hidden();
+        let mut multi = self.state.write().ignore_poison();
+        multi.record_final(idx, snapshot);
+        multi.remove_idx(idx);
+    }
+
+    /// Removes a progress bar, like [`MultiProgress::remove()`]
+    ///
+    /// Fails with [`Error::InvalidUsage`] if `pb` is not a member of this [`MultiProgress`],
+    /// instead of doing nothing or panicking.
+    pub fn try_remove(&self, pb: &ProgressBar) -> Result<(), Error> {
+        let mut state = pb.try_state()?;
+        let idx = match state.draw_target.remote() {
+            Some((multi, idx)) if Arc::ptr_eq(&self.state, multi) => idx,
+            _ => {
+                return Err(Error::InvalidUsage(
+                    "the progress bar is not a member of the MultiProgress",
+                ))
+            }
+        };
+
+        let snapshot = state.snapshot(Instant::now());
         state.draw_target = ProgressDrawTarget::hidden();
-        self.state.write().unwrap().remove_idx(idx);
+        let mut multi = self.state.write()?;
+        multi.record_final(idx, snapshot);
+        multi.remove_idx(idx);
+        Ok(())
+    }
+
+    /// Shows or hides all progress bars of a group
+    ///
+    /// Hidden progress bars keep running and can be shown again at any time, e.g. to toggle
+    /// verbose output at runtime. See [`ProgressBar::group()`] for assigning progress bars to a
+    /// group.
+    pub fn set_tag_visible(&self, tag: &str, visible: bool) {
+        let mut state = self.state.write().ignore_poison();
+        let changed = match visible {
+            true => state.hidden_tags.remove(tag),
+            false => state.hidden_tags.insert(Cow::Owned(tag.to_owned())),
+        };
+        if changed {
+            let _ = state.draw(true, None, Instant::now());
+        }
+    }
+
+    /// Sets the style of progress bars added afterwards that still use the default style
+    ///
+    /// Progress bars whose style was set explicitly, including spinners and timers created
+    /// with [`ProgressBar::new_spinner()`] or [`ProgressBar::new_timer()`], keep their style.
+    /// Progress bars that were already added are not affected.
+    pub fn set_default_style(&self, style: ProgressStyle) {
+        self.state.write().ignore_poison().default_style = Some(DefaultStyle(style));
+    }
+
+    /// Sets the tab width of progress bars added afterwards that still use the default tab width
+    ///
+    /// Progress bars whose tab width was set explicitly keep it. Progress bars that were already
+    /// added are not affected.
+    pub fn set_default_tab_width(&self, tab_width: usize) {
+        self.state.write().ignore_poison().default_tab_width = Some(tab_width);
+    }
+
+    /// Limits the number of progress bars that are drawn (default: no limit)
+    ///
+    /// Only `n` progress bars are drawn, the others are summarized in a single `... and N more`
+    /// line. The window starts at the first progress bar that is not finished, so as progress
+    /// bars at the top finish or are removed, it scrolls down to the following ones. The header
+    /// and footer are always drawn and do not count towards the limit.
+    ///
+    /// Use this when adding more progress bars than fit the terminal height, which otherwise
+    /// garbles the output.
+    pub fn set_max_visible(&self, n: usize) {
+        let mut state = self.state.write().ignore_poison();
+        if state.max_visible != Some(n) {
+            state.max_visible = Some(n);
+            let _ = state.draw(true, None, Instant::now());
+        }
+    }
+
+    /// Sets the verbosity level, which determines which progress bars are drawn (default: 0)
+    ///
+    /// Progress bars are only drawn if their [minimum verbosity] is at most `level`. By mapping
+    /// `-q` and `-v` flags to levels below and above 0, classes of progress bars can be
+    /// suppressed or revealed without keeping track of every handle. The level can be changed
+    /// at any time.
+    ///
+    /// [minimum verbosity]: ProgressBar::with_min_verbosity
+    pub fn set_verbosity(&self, level: i8) {
+        let mut state = self.state.write().ignore_poison();
+        if state.verbosity != level {
+            state.verbosity = level;
+            let _ = state.draw(true, None, Instant::now());
+        }
+    }
+
+    /// Removes all progress bars of a group, see [`MultiProgress::remove()`]
+    pub fn remove_tag(&self, tag: &str) {
+        let state = self.state.read().ignore_poison();
+        let bars = state
+            .ordering
+            .iter()
+            .filter(|&&idx| state.members[idx].tag.as_deref() == Some(tag))
+            .filter_map(|&idx| state.members[idx].bar.clone())
+            .collect::<Vec<_>>();
+        drop(state);
+
+        // Progress bars must be locked without holding the lock on the `MultiState`
+        for bar in bars.iter().filter_map(WeakProgressBar::upgrade) {
+            self.remove(&bar);
+        }
+    }
+
+    /// Sets a progress bar as the header of the [`MultiProgress`]
+    ///
+    /// The header is drawn above all other progress bars and updated like any other progress
+    /// bar, but unlike lines printed with [`MultiProgress::println`], it is never scrolled away.
+    /// This is useful for totals or the current stage of a multi-step process. A previous header
+    /// is removed.
+    ///
+    /// While a header is set, finished progress bars are kept in place below the header instead of
+    /// being moved into the scrollback.
+    pub fn set_header(&self, pb: ProgressBar) -> ProgressBar {
+        self.pin(Pin::Header, pb)
+    }
+
+    /// Sets a progress bar as the footer of the [`MultiProgress`]
+    ///
+    /// The footer is drawn below all other progress bars, e.g. for a status line or keyboard
+    /// hints like "press q to cancel". A previous footer is removed. Unlike other progress bars,
+    /// the footer is cleared when it is dropped, even if it was finished with
+    /// [`ProgressFinish::AndLeave`](crate::ProgressFinish::AndLeave).
+    pub fn set_footer(&self, pb: ProgressBar) -> ProgressBar {
+        self.pin(Pin::Footer, pb)
+    }
+
+    fn pin(&self, pin: Pin, pb: ProgressBar) -> ProgressBar {
+        let old = self
+            .state
+            .read()
+            .ignore_poison()
+            .pinned(pin)
+            .map(|p| p.bar.clone());
+        if let Some(old) = old.and_then(|bar| bar.upgrade()) {
+            self.remove(&old);
+        }
+
+        let started = self.state.read().ignore_poison().started;
+        pb.state().state.multi_started = Some(started);
+
+        let mut state = self.state.write().ignore_poison();
+        let idx = state.alloc_member();
+        state.add_summary_row(idx, &pb);
+        *state.pinned_mut(pin) = Some(PinnedMember {
+            idx,
+            bar: pb.downgrade(),
+        });
+        drop(state);
+
+        pb.set_draw_target(ProgressDrawTarget::new_remote(self.state.clone(), idx));
+        self.notify_added(idx, &pb);
+        pb
     }
 
     fn internalize(&self, location: InsertLocation, pb: ProgressBar) -> ProgressBar {
-        let mut state = self.state.write().unwrap();
+        let (style, tab_width, started) = {
+            let state = self.state.read().ignore_poison();
+            (
+                state.default_style.as_ref().map(|style| style.0.clone()),
+                state.default_tab_width,
+                state.started,
+            )
+        };
+        let (tag, min_verbosity) = {
+            let mut state = pb.state();
+            state.inherit_defaults(style.as_ref(), tab_width);
+            state.state.multi_started = Some(started);
+            (state.tag.clone(), state.min_verbosity)
+        };
+        let mut state = self.state.write().ignore_poison();
         let idx = state.insert(location);
+        state.add_summary_row(idx, &pb);
+        state.set_tag(idx, tag);
+        state.set_min_verbosity(idx, min_verbosity);
         drop(state);
 
         pb.set_draw_target(ProgressDrawTarget::new_remote(self.state.clone(), idx));
+        self.notify_added(idx, &pb);
         pb
     }
 
+    /// Whether `pb` draws to this [`MultiProgress`]
+    fn is_member(&self, pb: &ProgressBar) -> bool {
+        pb.state()
+            .draw_target
+            .remote()
+            .is_some_and(|(state, _)| Arc::ptr_eq(state, &self.state))
+    }
+
+    fn notify_added(&self, index: usize, pb: &ProgressBar) {
+        let id = pb.id();
+        self.state
+            .write()
+            .ignore_poison()
+            .notify(|| ProgressEvent::Added { index, id });
+    }
+
+    /// Records every frame drawn from now on to an [asciicast v2] file at `path`
+    ///
+    /// The recording can be played back with `asciinema play` or converted to an animated GIF
+    /// for documentation, without an external screen capture tool. Frames are recorded as they
+    /// are drawn, so nothing is recorded while the draw target is hidden. Writes to the file are
+    /// buffered; recording stops when [`MultiProgress::stop_recording()`] is called, which
+    /// reports errors writing the file, or the [`MultiProgress`] is dropped. After an error, no
+    /// more frames are recorded. A previous recording is stopped, ignoring its errors.
+    ///
+    /// [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+    pub fn record_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = File::create(path)?;
+        let mut state = self.state.write().ignore_poison();
+        let width = state.width().unwrap_or(80);
+        let height = state.height().unwrap_or(24);
+        state.recorder = Some(CastRecorder::new(file, width, height)?);
+        Ok(())
+    }
+
+    /// Stops the recording started with [`MultiProgress::record_to()`]
+    ///
+    /// Returns the first error that occurred while writing the recording, if any.
+    pub fn stop_recording(&self) -> io::Result<()> {
+        let recorder = self.state.write().ignore_poison().recorder.take();
+        recorder.map_or(Ok(()), CastRecorder::finish)
+    }
+
+    /// Returns the time since the [`MultiProgress`] was created
+    ///
+    /// This is rendered by the `{multi_elapsed}` and `{multi_elapsed_precise}` template keys of
+    /// its progress bars, e.g. in a [header](MultiProgress::set_header).
+    pub fn elapsed(&self) -> Duration {
+        self.state.read().ignore_poison().started.elapsed()
+    }
+
+    /// Returns a plain-text table summarizing all progress bars added so far
+    ///
+    /// The table has one row per progress bar in the order they were added, with its name (the
+    /// identifier, prefix or message, whichever is set first), position and length, elapsed
+    /// time, average rate and status. Finished and removed progress bars are reported with their
+    /// final state, so a small amount of metadata is retained for each of them. To bound the
+    /// memory use of long-running programs, only the last 1000 finished or removed progress bars
+    /// are reported, and progress bars added with [`MultiProgress::add_with_auto_remove()`] are
+    /// no longer reported once they are removed.
+    pub fn summary(&self) -> String {
+        let rows = self
+            .state
+            .read()
+            .ignore_poison()
+            .summary
+            .values()
+            .map(|row| (row.bar.clone(), row.last.clone()))
+            .collect::<Vec<_>>();
+
+        // Live progress bars must be locked without holding the lock on the `MultiState`
+        let snapshots = rows
+            .into_iter()
+            .filter_map(|(bar, last)| last.or_else(|| Some(bar.upgrade()?.snapshot())))
+            .collect::<Vec<_>>();
+        format_summary(&snapshots)
+    }
+
+    /// Returns snapshots of all progress bars in the order they are drawn
+    ///
+    /// This includes the [header](MultiProgress::set_header) and
+    /// [footer](MultiProgress::set_footer). With the `serde` feature, the snapshots can be
+    /// serialized, e.g. to show the progress in another process.
+    pub fn snapshot(&self) -> Vec<ProgressSnapshot> {
+        let state = self.state.read().ignore_poison();
+        let header = state.header.as_ref().map(|h| h.idx);
+        let footer = state.footer.as_ref().map(|f| f.idx);
+        let rows = header
+            .iter()
+            .chain(&state.ordering)
+            .chain(footer.iter())
+            .filter_map(|&idx| {
+                let member = &state.members[idx];
+                let last = member
+                    .summary_row
+                    .and_then(|row| state.summary.get(&row)?.last.clone());
+                Some((member.bar.clone()?, last))
+            })
+            .collect::<Vec<_>>();
+        drop(state);
+
+        // Live progress bars must be locked without holding the lock on the `MultiState`
+        rows.into_iter()
+            .filter_map(|(bar, last)| last.or_else(|| Some(bar.upgrade()?.snapshot())))
+            .collect()
+    }
+
+    /// Prints the [`MultiProgress::summary`] table above all progress bars
+    ///
+    /// Like [`MultiProgress::println`], this writes to the draw target, so nothing is printed if
+    /// it is hidden. Use [`MultiProgress::summary`] to print the table elsewhere.
+    pub fn print_summary(&self) -> io::Result<()> {
+        self.println(self.summary())
+    }
+
     /// Print a log line above all progress bars in the [`MultiProgress`]
     ///
     /// If the draw target is hidden (e.g. when standard output is not a terminal), `println()`
     /// will not do anything.
     pub fn println<I: AsRef<str>>(&self, msg: I) -> io::Result<()> {
-        let mut state = self.state.write().unwrap();
+        let mut state = self.state.write().ignore_poison();
         state.println(msg, Instant::now())
     }
 
@@ -190,16 +701,94 @@ impl MultiProgress {
     /// anything on the progress bar will be blocked until `f` finishes.
     /// Therefore, it is recommended to avoid long-running operations in `f`.
     pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
-        let mut state = self.state.write().unwrap();
+        let mut state = self.state.write().ignore_poison();
         state.suspend(f, Instant::now())
     }
 
+    /// Executes `f` without drawing, then draws the [`MultiProgress`] once
+    ///
+    /// Useful for adding or removing many progress bars at once, which would otherwise redraw
+    /// the display for every change. Lines printed in `f` appear in the final frame. Batches can
+    /// be nested, only the outermost one draws.
+    pub fn batch<F: FnOnce(&Self) -> R, R>(&self, f: F) -> R {
+        struct Batch<'a>(&'a MultiProgress);
+
+        impl Drop for Batch<'_> {
+            fn drop(&mut self) {
+                let mut state = self.0.state.write().ignore_poison();
+                state.batches -= 1;
+                if state.batches == 0 {
+                    let _ = state.draw(true, None, Instant::now());
+                }
+            }
+        }
+
+        self.state.write().ignore_poison().batches += 1;
+        let batch = Batch(self);
+        f(batch.0)
+    }
+
+    /// Prints a log line above all progress bars, like [`MultiProgress::println()`]
+    ///
+    /// Unlike `println()`, which carries on after a panic in another thread that was updating
+    /// the progress bars, this reports it as [`Error::Poisoned`].
+    pub fn try_println<I: AsRef<str>>(&self, msg: I) -> Result<(), Error> {
+        let mut state = self.state.write()?;
+        Ok(state.println(msg, Instant::now())?)
+    }
+
     pub fn clear(&self) -> io::Result<()> {
-        self.state.write().unwrap().clear(Instant::now())
+        self.state.write().ignore_poison().clear(Instant::now())
+    }
+
+    /// Clears all progress bars, like [`MultiProgress::clear()`]
+    ///
+    /// Reports a panic in another thread that was updating the progress bars as
+    /// [`Error::Poisoned`].
+    pub fn try_clear(&self) -> Result<(), Error> {
+        Ok(self.state.write()?.clear(Instant::now())?)
     }
 
     pub fn is_hidden(&self) -> bool {
-        self.state.read().unwrap().is_hidden()
+        self.state.read().ignore_poison().is_hidden()
+    }
+
+    /// Writes `line` to the terminal if it is not user attended, see
+    /// `ProgressDrawTarget::write_unattended_line`
+    #[cfg(feature = "tracing")]
+    pub(crate) fn write_unattended_line(&self, line: &str) -> io::Result<()> {
+        self.state
+            .read()
+            .ignore_poison()
+            .draw_target
+            .write_unattended_line(line)
+    }
+
+    /// Returns true if the last draw did not fit the terminal height
+    ///
+    /// Progress bars that do not fit are not drawn. Callers can use this to switch to a more
+    /// compact style.
+    pub fn is_truncated(&self) -> bool {
+        self.state.read().ignore_poison().is_truncated()
+    }
+}
+
+thread_local! {
+    /// The stack of entered `MultiProgress` objects, see `MultiProgress::enter`
+    static CURRENT: RefCell<Vec<MultiProgress>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Keeps a [`MultiProgress`] current for this thread, see [`MultiProgress::enter()`]
+#[derive(Debug)]
+#[must_use = "the MultiProgress is only current until the guard is dropped"]
+pub struct MultiProgressGuard {
+    // The guard must be dropped on the thread that created it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for MultiProgressGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| current.borrow_mut().pop());
     }
 }
 
@@ -221,6 +810,47 @@ pub(crate) struct MultiState {
     orphan_lines: Vec<LineType>,
     /// The count of currently visible zombie lines.
     zombie_lines_count: VisualLines,
+    /// Progress bar drawn above all members, not part of `ordering`
+    header: Option<PinnedMember>,
+    /// Progress bar drawn below all members, not part of `ordering`
+    footer: Option<PinnedMember>,
+    /// Callback modifying the member lines of each frame
+    frame_decorator: Option<FrameDecorator>,
+    /// Channels receiving structured events, see `MultiProgress::subscribe`
+    subscribers: Vec<Sender<ProgressEvent>>,
+    /// The progress bars reported by `MultiProgress::summary`, by the order they were added in
+    summary: BTreeMap<usize, SummaryRow>,
+    /// The key of the next row in `summary`
+    next_summary_row: usize,
+    /// The number of rows in `summary` of finished or removed progress bars
+    finished_summary_rows: usize,
+    /// Whether a member was added with a priority, enabling eviction of low-priority members
+    prioritized: bool,
+    /// Minimum time between frames drawn because of a steady tick, see
+    /// `MultiProgress::set_max_tick_fps`
+    min_tick_interval: Duration,
+    /// When the last frame was drawn
+    last_frame: Option<Instant>,
+    /// Tags of members that are not drawn, see `MultiProgress::set_tag_visible`
+    hidden_tags: HashSet<Cow<'static, str>>,
+    /// Members with a higher minimum verbosity are not drawn
+    verbosity: i8,
+    /// Style of added bars that still use the default style
+    default_style: Option<DefaultStyle>,
+    /// Tab width of added bars that still use the default tab width
+    default_tab_width: Option<usize>,
+    /// Maximum number of members drawn, see `MultiProgress::set_max_visible`
+    max_visible: Option<usize>,
+    /// When the `MultiProgress` was created, see `MultiProgress::elapsed`
+    started: Instant,
+    /// Records drawn frames, see `MultiProgress::record_to`
+    recorder: Option<CastRecorder>,
+    /// The identifier of the last group, see `MultiProgress::add_group`
+    next_group: usize,
+    /// The number of running `MultiProgress::batch` calls, nothing is drawn while it's non-zero
+    batches: usize,
+    /// Frames drawn to the terminal, written after the lock on the state is released
+    output: Arc<DeferredOutput>,
 }
 
 impl MultiState {
@@ -233,17 +863,148 @@ impl MultiState {
             alignment: MultiProgressAlignment::default(),
             orphan_lines: Vec::new(),
             zombie_lines_count: VisualLines::default(),
+            header: None,
+            footer: None,
+            frame_decorator: None,
+            subscribers: Vec::new(),
+            summary: BTreeMap::new(),
+            next_summary_row: 0,
+            finished_summary_rows: 0,
+            prioritized: false,
+            min_tick_interval: Duration::ZERO,
+            last_frame: None,
+            hidden_tags: HashSet::new(),
+            verbosity: 0,
+            default_style: None,
+            default_tab_width: None,
+            max_visible: None,
+            started: Instant::now(),
+            recorder: None,
+            next_group: 0,
+            batches: 0,
+            output: Arc::default(),
+        }
+    }
+
+    pub(crate) fn set_tag(&mut self, idx: usize, tag: Option<Cow<'static, str>>) {
+        if let Some(member) = self.members.get_mut(idx) {
+            member.tag = tag;
+        }
+    }
+
+    pub(crate) fn set_min_verbosity(&mut self, idx: usize, level: i8) {
+        if let Some(member) = self.members.get_mut(idx) {
+            member.min_verbosity = level;
+        }
+    }
+
+    /// Whether the member is not drawn because of its group or verbosity
+    fn is_filtered(&self, idx: usize) -> bool {
+        let member = &self.members[idx];
+        member.min_verbosity > self.verbosity
+            || member
+                .tag
+                .as_ref()
+                .is_some_and(|tag| self.hidden_tags.contains(tag))
+    }
+
+    /// When the last frame was drawn, if any
+    pub(crate) fn last_frame(&self) -> Option<Instant> {
+        self.last_frame
+    }
+
+    /// Whether a frame would be drawn at `now`, see [`ProgressDrawTarget::would_draw()`]
+    pub(crate) fn would_draw(&self, now: Instant) -> bool {
+        self.draw_target.would_draw(now)
+    }
+
+    /// Whether a frame caused by a steady tick should be drawn
+    ///
+    /// The lines of the ticking member are updated either way, so skipped ticks are coalesced
+    /// into the next frame.
+    pub(crate) fn allow_steady_tick(&self, now: Instant) -> bool {
+        self.last_frame.map_or(true, |last| {
+            now.saturating_duration_since(last) >= self.min_tick_interval
+        })
+    }
+
+    fn add_summary_row(&mut self, idx: usize, pb: &ProgressBar) {
+        let row = self.next_summary_row;
+        self.next_summary_row += 1;
+        self.members[idx].bar = Some(pb.downgrade());
+        self.members[idx].summary_row = Some(row);
+        self.summary.insert(
+            row,
+            SummaryRow {
+                bar: pb.downgrade(),
+                last: None,
+            },
+        );
+    }
+
+    /// Retain the final state of a finished or removed member for the summary
+    pub(crate) fn record_final(&mut self, idx: usize, snapshot: ProgressSnapshot) {
+        let Some(row) = self.members.get(idx).and_then(|m| m.summary_row) else {
+            return;
+        };
+
+        let Some(row) = self.summary.get_mut(&row) else {
+            return;
+        };
+        if row.last.is_some() {
+            return;
+        }
+        row.last = Some(snapshot);
+        self.finished_summary_rows += 1;
+
+        // Forget the oldest finished progress bars
+        while self.finished_summary_rows > MAX_FINISHED_SUMMARY_ROWS {
+            let oldest = self
+                .summary
+                .iter()
+                .find(|(_, row)| row.last.is_some())
+                .map(|(&row, _)| row);
+            if let Some(oldest) = oldest {
+                self.summary.remove(&oldest);
+            }
+            self.finished_summary_rows -= 1;
+        }
+    }
+
+    pub(crate) fn write_control(&self, seq: &str) -> io::Result<()> {
+        self.flush_output()?;
+        self.draw_target.write_control(seq)
+    }
+
+    /// Sends an event to all subscribers, dropping those that hung up
+    pub(crate) fn notify(&mut self, event: impl FnOnce() -> ProgressEvent) {
+        if self.subscribers.is_empty() {
+            return;
         }
+
+        let event = event();
+        self.subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    /// Forgets the previously drawn frame, so that the next draw redraws all lines
+    pub(crate) fn invalidate(&mut self) {
+        self.draw_target.invalidate();
     }
 
     pub(crate) fn mark_zombie(&mut self, index: usize) {
+        // A pinned bar is simply removed; its lines are cleared on the next draw.
+        if self.is_pinned(index) {
+            self.remove_idx(index);
+            return;
+        }
+
         let width = self.width().map(usize::from);
 
         let member = &mut self.members[index];
 
         // If the zombie is the first visual bar then we can reap it right now instead of
-        // deferring it to the next draw.
-        if index != self.ordering.first().copied().unwrap() {
+        // deferring it to the next draw. Zombies below a header stay in place.
+        if self.header.is_some() || index != self.ordering.first().copied().unwrap() {
             member.is_zombie = true;
             return;
         }
@@ -265,7 +1026,31 @@ impl MultiState {
         self.remove_idx(index);
     }
 
+    /// Returns the queue of terminal output, see [`MultiState::queue_draw()`]
+    pub(crate) fn output(&self) -> Arc<DeferredOutput> {
+        self.output.clone()
+    }
+
+    /// Writes any terminal output queued by other threads
+    fn flush_output(&self) -> io::Result<()> {
+        self.output.flush()
+    }
+
     pub(crate) fn draw(
+        &mut self,
+        force_draw: bool,
+        extra_lines: Option<Vec<LineType>>,
+        now: Instant,
+    ) -> io::Result<()> {
+        self.queue_draw(force_draw, extra_lines, now)?;
+        self.flush_output()
+    }
+
+    /// Draws a frame, but only queues the terminal output
+    ///
+    /// The caller must flush the [`output()`][Self::output()], preferably after releasing the
+    /// lock on the state so that slow terminals don't block updates of other progress bars.
+    pub(crate) fn queue_draw(
         &mut self,
         mut force_draw: bool,
         extra_lines: Option<Vec<LineType>>,
@@ -275,6 +1060,14 @@ impl MultiState {
             return Ok(());
         }
 
+        if self.batches > 0 {
+            // Printed lines are kept for the frame drawn at the end of the batch
+            if let Some(lines) = extra_lines {
+                self.orphan_lines.extend(lines);
+            }
+            return Ok(());
+        }
+
         let width = match self.width() {
             Some(width) => width as usize,
             None => return Ok(()),
@@ -288,19 +1081,27 @@ impl MultiState {
 
         let mut reap_indices = vec![];
 
-        // Reap all consecutive 'zombie' progress bars from head of the list.
+        // Reap all consecutive 'zombie' progress bars from head of the list. This is not possible
+        // while a header is drawn above them.
         let mut adjust = VisualLines::default();
-        for &index in &self.ordering {
+        let head = match self.header {
+            Some(_) => &[][..],
+            None => &self.ordering[..],
+        };
+        for &index in head {
             let member = &self.members[index];
             if !member.is_zombie {
                 break;
             }
 
-            let line_count = member
-                .draw_state
-                .as_ref()
-                .map(|d| d.visual_line_count(.., width))
-                .unwrap_or_default();
+            let line_count = match self.is_filtered(index) {
+                true => VisualLines::default(),
+                false => member
+                    .draw_state
+                    .as_ref()
+                    .map(|d| d.visual_line_count(.., width))
+                    .unwrap_or_default(),
+            };
             // Track the total number of zombie lines on the screen.
             self.zombie_lines_count += line_count;
 
@@ -321,13 +1122,22 @@ impl MultiState {
 
         let orphan_visual_line_count = visual_line_count(&self.orphan_lines, width);
         force_draw |= orphan_visual_line_count > VisualLines::default();
+        let evicted = self.evicted_members(width);
+        let hidden = self
+            .ordering
+            .iter()
+            .copied()
+            .filter(|&idx| self.is_filtered(idx))
+            .collect::<Vec<_>>();
         let mut drawable = match self.draw_target.drawable(force_draw, now) {
             Some(drawable) => drawable,
             None => return Ok(()),
         };
+        self.last_frame = Some(now);
 
         let mut draw_state = drawable.state();
         draw_state.alignment = self.alignment;
+        draw_state.is_multi = true;
 
         if let Some(extra_lines) = &extra_lines {
             draw_state.lines.extend_from_slice(extra_lines.as_slice());
@@ -336,15 +1146,47 @@ impl MultiState {
         // Add lines from `ProgressBar::println` call.
         draw_state.lines.append(&mut self.orphan_lines);
 
-        for index in &self.ordering {
+        let header = self.header.as_ref().map(|h| h.idx);
+        let footer = self.footer.as_ref().map(|f| f.idx);
+        let mut member_lines = Vec::new();
+        for index in header.iter().chain(&self.ordering) {
             let member = &self.members[*index];
+            if evicted.contains(index) || hidden.contains(index) {
+                continue;
+            }
             if let Some(state) = &member.draw_state {
-                draw_state.lines.extend_from_slice(&state.lines[..]);
+                member_lines.extend_from_slice(&state.lines[..]);
+            }
+        }
+        if !evicted.is_empty() {
+            member_lines.push(LineType::Bar(format!("... and {} more", evicted.len())));
+        }
+        for index in footer.iter() {
+            if let Some(state) = &self.members[*index].draw_state {
+                member_lines.extend_from_slice(&state.lines[..]);
             }
         }
 
+        if let Some(FrameDecorator(decorator)) = &self.frame_decorator {
+            let mut lines = member_lines
+                .into_iter()
+                .map(|line| match line {
+                    LineType::Text(s) | LineType::Bar(s) => s,
+                    LineType::Empty => String::new(),
+                })
+                .collect();
+            decorator(&mut lines);
+            member_lines = lines.into_iter().map(LineType::Bar).collect();
+        }
+
+        draw_state.lines.append(&mut member_lines);
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(now, &draw_state.lines);
+        }
+
         drop(draw_state);
-        let drawable = drawable.draw();
+        let drawable = drawable.defer(&self.output);
 
         for index in reap_indices {
             self.remove_idx(index);
@@ -355,9 +1197,12 @@ impl MultiState {
         if extra_lines.is_none() {
             self.draw_target
                 .adjust_last_line_count(LineAdjust::Keep(adjust));
+            if let Some(recorder) = &mut self.recorder {
+                recorder.keep(adjust.as_usize());
+            }
         }
 
-        drawable
+        drawable.map(|_| ())
     }
 
     pub(crate) fn println<I: AsRef<str>>(&mut self, msg: I, now: Instant) -> io::Result<()> {
@@ -372,6 +1217,53 @@ impl MultiState {
         self.draw(true, Some(lines), now)
     }
 
+    /// Records whether the progress bar of a member is finished, see `set_max_visible`
+    pub(crate) fn set_finished(&mut self, idx: usize, finished: bool) {
+        if let Some(member) = self.members.get_mut(idx) {
+            member.finished = finished;
+        }
+    }
+
+    pub(crate) fn is_auto_remove(&self, idx: usize) -> bool {
+        self.members
+            .get(idx)
+            .is_some_and(|member| member.auto_remove)
+    }
+
+    /// Remove a finished member and print its last lines above the remaining members
+    pub(crate) fn move_to_scrollback(&mut self, idx: usize, now: Instant) -> io::Result<()> {
+        let lines = self.members[idx]
+            .draw_state
+            .take()
+            .map(|state| state.lines)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|line| match line {
+                LineType::Bar(s) => LineType::Text(s),
+                line => line,
+            })
+            .collect::<Vec<_>>();
+        // Auto-removed members would otherwise accumulate in the summary
+        if let Some(row) = self.members[idx]
+            .summary_row
+            .filter(|_| self.is_auto_remove(idx))
+        {
+            if self
+                .summary
+                .remove(&row)
+                .is_some_and(|row| row.last.is_some())
+            {
+                self.finished_summary_rows -= 1;
+            }
+        }
+        self.remove_idx(idx);
+
+        match lines.is_empty() {
+            true => self.draw(true, None, now),
+            false => self.draw(true, Some(lines), now),
+        }
+    }
+
     pub(crate) fn draw_state(&mut self, idx: usize) -> DrawStateWrapper<'_> {
         let member = self.members.get_mut(idx).unwrap();
         // alignment is handled by the `MultiProgress`'s underlying draw target, so there is no
@@ -396,14 +1288,129 @@ impl MultiState {
         self.draw_target.width()
     }
 
-    fn insert(&mut self, location: InsertLocation) -> usize {
-        let idx = if let Some(idx) = self.free_set.pop() {
+    pub(crate) fn height(&self) -> Option<u16> {
+        self.draw_target.height()
+    }
+
+    /// Members in `ordering` that are not drawn because they exceed the maximum number of
+    /// visible members or do not fit the terminal height
+    ///
+    /// Members beyond the maximum are evicted first. Then, members with the lowest priority are
+    /// evicted, starting from the bottom, until the remaining members and a line summarizing the
+    /// evicted ones fit.
+    fn evicted_members(&self, width: usize) -> Vec<usize> {
+        let mut evicted = Vec::new();
+        if let Some(max) = self.max_visible {
+            let members = self
+                .ordering
+                .iter()
+                .copied()
+                .filter(|&idx| !self.is_filtered(idx))
+                .collect::<Vec<_>>();
+            // Finished members at the top scroll out of the window, like removed ones
+            let finished = members
+                .iter()
+                .take_while(|&&idx| self.members[idx].finished)
+                .count();
+            let start = finished.min(members.len().saturating_sub(max));
+            evicted.extend(&members[..start]);
+            evicted.extend(members.iter().skip(start + max));
+        }
+
+        let height = match (self.prioritized, self.height()) {
+            (true, Some(height)) => VisualLines::from(height),
+            _ => return evicted,
+        };
+
+        let member_height = |idx: usize| match self.is_filtered(idx) || evicted.contains(&idx) {
+            true => VisualLines::default(),
+            false => self.members[idx]
+                .draw_state
+                .as_ref()
+                .map(|d| d.visual_line_count(.., width))
+                .unwrap_or_default(),
+        };
+        let pinned = [&self.header, &self.footer].into_iter().flatten();
+        let mut total = pinned
+            .map(|p| member_height(p.idx))
+            .chain(self.ordering.iter().map(|&idx| member_height(idx)))
+            .fold(VisualLines::default(), |acc, h| acc.saturating_add(h));
+        let mut candidates = self
+            .ordering
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, idx)| !self.is_filtered(idx) && !evicted.contains(&idx))
+            .collect::<Vec<_>>();
+        let summary = match evicted.is_empty() {
+            true => VisualLines::default(),
+            false => VisualLines::from(1usize),
+        };
+        if total.saturating_add(summary) <= height {
+            return evicted;
+        }
+        candidates.sort_by_key(|&(pos, idx)| (self.members[idx].priority, Reverse(pos)));
+
+        // Make room for the line summarizing the evicted members
+        let height = height.saturating_sub(VisualLines::from(1usize));
+        let mut newly_evicted = Vec::new();
+        for (_, idx) in candidates {
+            if total <= height {
+                break;
+            }
+            total = total.saturating_sub(member_height(idx));
+            newly_evicted.push(idx);
+        }
+        evicted.extend(newly_evicted);
+        evicted
+    }
+
+    pub(crate) fn member_lines(&self, idx: usize) -> Vec<LineType> {
+        self.members
+            .get(idx)
+            .and_then(|member| member.draw_state.as_ref())
+            .map(|draw_state| draw_state.lines.clone())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.draw_target.is_truncated()
+    }
+
+    fn pinned(&self, pin: Pin) -> Option<&PinnedMember> {
+        match pin {
+            Pin::Header => self.header.as_ref(),
+            Pin::Footer => self.footer.as_ref(),
+        }
+    }
+
+    fn pinned_mut(&mut self, pin: Pin) -> &mut Option<PinnedMember> {
+        match pin {
+            Pin::Header => &mut self.header,
+            Pin::Footer => &mut self.footer,
+        }
+    }
+
+    fn is_pinned(&self, idx: usize) -> bool {
+        [&self.header, &self.footer]
+            .into_iter()
+            .flatten()
+            .any(|pinned| pinned.idx == idx)
+    }
+
+    /// Allocates a member slot without placing it in `ordering`
+    fn alloc_member(&mut self) -> usize {
+        if let Some(idx) = self.free_set.pop() {
             self.members[idx] = MultiStateMember::default();
             idx
         } else {
             self.members.push(MultiStateMember::default());
             self.members.len() - 1
-        };
+        }
+    }
+
+    fn insert(&mut self, location: InsertLocation) -> usize {
+        let idx = self.alloc_member();
 
         match location {
             InsertLocation::End => self.ordering.push(idx),
@@ -423,6 +1430,15 @@ impl MultiState {
                 let pos = self.ordering.iter().position(|i| *i == before_idx).unwrap();
                 self.ordering.insert(pos, idx);
             }
+            InsertLocation::Group(group) => {
+                let pos = self
+                    .ordering
+                    .iter()
+                    .rposition(|&i| self.members[i].group == Some(group))
+                    .map_or(self.ordering.len(), |pos| pos + 1);
+                self.ordering.insert(pos, idx);
+                self.members[idx].group = Some(group);
+            }
         }
 
         assert_eq!(
@@ -435,6 +1451,7 @@ impl MultiState {
     }
 
     fn clear(&mut self, now: Instant) -> io::Result<()> {
+        self.flush_output()?;
         match self.draw_target.drawable(true, now) {
             Some(mut drawable) => {
                 // Make the clear operation also wipe out zombie lines
@@ -454,6 +1471,12 @@ impl MultiState {
         self.members[idx] = MultiStateMember::default();
         self.free_set.push(idx);
         self.ordering.retain(|&x| x != idx);
+        for pinned in [&mut self.header, &mut self.footer] {
+            if pinned.as_ref().is_some_and(|p| p.idx == idx) {
+                *pinned = None;
+            }
+        }
+        self.notify(|| ProgressEvent::Removed { index: idx });
 
         assert_eq!(
             self.len(),
@@ -462,8 +1485,10 @@ impl MultiState {
         );
     }
 
+    /// The number of members in `ordering`, i.e. excluding pinned ones
     fn len(&self) -> usize {
-        self.members.len() - self.free_set.len()
+        let pinned = usize::from(self.header.is_some()) + usize::from(self.footer.is_some());
+        self.members.len() - self.free_set.len() - pinned
     }
 }
 
@@ -474,6 +1499,22 @@ struct MultiStateMember {
     draw_state: Option<DrawState>,
     /// Whether the corresponding progress bar (more precisely, `BarState`) has been dropped.
     is_zombie: bool,
+    /// The progress bar of the member
+    bar: Option<WeakProgressBar>,
+    /// Key of the corresponding row in `MultiState::summary`
+    summary_row: Option<usize>,
+    /// Members with a lower priority are hidden first if the terminal is too small
+    priority: i32,
+    /// The group the progress bar belongs to, see `ProgressBar::group`
+    tag: Option<Cow<'static, str>>,
+    /// The member is only drawn if the verbosity is at least this level
+    min_verbosity: i8,
+    /// Whether the member is removed as soon as its progress bar finishes
+    auto_remove: bool,
+    /// Whether the progress bar of the member is finished
+    finished: bool,
+    /// The group the member was added to, see `MultiProgress::add_group`
+    group: Option<usize>,
 }
 
 impl Debug for MultiStateMember {
@@ -485,6 +1526,179 @@ impl Debug for MultiStateMember {
     }
 }
 
+/// A section of a [`MultiProgress`] with a header line, see [`MultiProgress::add_group()`]
+#[derive(Debug, Clone)]
+pub struct ProgressGroup {
+    multi: MultiProgress,
+    id: usize,
+    header: ProgressBar,
+}
+
+impl ProgressGroup {
+    /// Adds a progress bar below the header and the progress bars of this group
+    ///
+    /// Like with [`MultiProgress::add()`], the draw target of the progress bar is changed to
+    /// the [`MultiProgress`].
+    pub fn add(&self, pb: ProgressBar) -> ProgressBar {
+        self.multi.internalize(InsertLocation::Group(self.id), pb)
+    }
+
+    /// Returns the progress bar drawing the header line
+    ///
+    /// The title is its message, so it can be changed with [`ProgressBar::set_message()`].
+    pub fn header(&self) -> &ProgressBar {
+        &self.header
+    }
+}
+
+/// A progress bar reported by `MultiProgress::summary`
+struct SummaryRow {
+    bar: WeakProgressBar,
+    /// The state when the progress bar finished or was removed
+    last: Option<ProgressSnapshot>,
+}
+
+impl Debug for SummaryRow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SummaryRow")
+            .field("last", &self.last)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The number of finished or removed progress bars reported by `MultiProgress::summary`
+const MAX_FINISHED_SUMMARY_ROWS: usize = 1000;
+
+const SUMMARY_HEADER: [&str; 5] = ["name", "total", "duration", "rate", "status"];
+
+fn format_summary(snapshots: &[ProgressSnapshot]) -> String {
+    let rows = snapshots
+        .iter()
+        .map(|s| {
+            let name = [&s.id, &Some(s.prefix.clone()), &Some(s.message.clone())]
+                .into_iter()
+                .flatten()
+                .find(|name| !name.is_empty())
+                .cloned()
+                .unwrap_or_default();
+            let total = match s.len {
+                Some(len) => format!("{}/{}", s.pos, len),
+                None => s.pos.to_string(),
+            };
+            let status = s.finish_reason.map_or("running", |r| r.as_str());
+            [
+                name,
+                total,
+                FormattedDuration(s.elapsed).to_string(),
+                format!("{}/s", HumanFloatCount(s.per_sec)),
+                status.to_owned(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = SUMMARY_HEADER.map(measure_text_width);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = Ord::max(*width, measure_text_width(cell));
+        }
+    }
+
+    let mut out = String::new();
+    let header = SUMMARY_HEADER.map(String::from);
+    for row in std::iter::once(&header).chain(&rows) {
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            line.push_str(&" ".repeat(width - measure_text_width(cell)));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+type DecoratorFn = dyn Fn(&mut Vec<String>) + Send + Sync;
+
+struct FrameDecorator(Box<DecoratorFn>);
+
+struct DefaultStyle(ProgressStyle);
+
+impl Debug for DefaultStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DefaultStyle").finish_non_exhaustive()
+    }
+}
+
+impl Debug for FrameDecorator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameDecorator").finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Pin {
+    Header,
+    Footer,
+}
+
+/// A member of a [`MultiState`] drawn at a fixed place rather than through `ordering`
+struct PinnedMember {
+    idx: usize,
+    bar: WeakProgressBar,
+}
+
+impl Debug for PinnedMember {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PinnedMember")
+            .field("idx", &self.idx)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A structured event emitted to subscribers of a [`MultiProgress`]
+///
+/// See [`MultiProgress::subscribe`]. Progress bars are identified by their index in the
+/// [`MultiProgress`], which is stable while the bar is a member but may be reused for another bar
+/// after a [`Removed`](ProgressEvent::Removed) event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// A progress bar was added
+    Added {
+        /// The index of the progress bar
+        index: usize,
+        /// The identifier of the progress bar, if one was set
+        id: Option<String>,
+    },
+    /// A progress bar was updated
+    ///
+    /// Like frames, these events are rate limited: they are sent at most 20 times per second
+    /// per progress bar, so not every update is reported.
+    Updated {
+        /// The index of the progress bar
+        index: usize,
+        /// A snapshot of the progress bar's state
+        progress: IpcProgress,
+    },
+    /// A progress bar finished
+    ///
+    /// This is sent once, when the progress bar is finished for the first time.
+    Finished {
+        /// The index of the progress bar
+        index: usize,
+        /// A snapshot of the progress bar's final state
+        progress: IpcProgress,
+    },
+    /// A progress bar was removed, either explicitly or after it was dropped
+    Removed {
+        /// The index of the progress bar
+        index: usize,
+    },
+}
+
 /// Vertical alignment of a multi progress.
 ///
 /// The alignment controls how the multi progress is aligned if some of its progress bars get removed.
@@ -501,29 +1715,137 @@ impl Debug for MultiStateMember {
 /// [0/100] progress bar 2   =>   [0/100] progress bar 1
 /// [0/100] progress bar 3        [0/100] progress bar 3
 /// ```
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum MultiProgressAlignment {
+    #[default]
     Top,
     Bottom,
 }
 
-impl Default for MultiProgressAlignment {
-    fn default() -> Self {
-        Self::Top
-    }
-}
-
 enum InsertLocation {
     End,
     Index(usize),
     IndexFromBack(usize),
     After(usize),
     Before(usize),
+    /// After the last member of a group, see `MultiProgress::add_group`
+    Group(usize),
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{MultiProgress, ProgressBar, ProgressDrawTarget};
+    use std::io;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::{MAX_FINISHED_SUMMARY_ROWS, SUMMARY_HEADER};
+    use crate::{
+        Error, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressEvent, ProgressStyle,
+        TermLike,
+    };
+
+    #[test]
+    fn multi_elapsed() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        mp.state.write().unwrap().started = Instant::now() - Duration::from_secs(65);
+        assert!(mp.elapsed() >= Duration::from_secs(65));
+
+        let style = ProgressStyle::with_template("{multi_elapsed_precise} {elapsed_precise}");
+        let pb = mp.add(ProgressBar::new(10).with_style(style.unwrap()));
+        let footer = mp.set_footer(ProgressBar::new(10));
+        assert!(footer.state().state.multi_elapsed() >= Duration::from_secs(65));
+
+        let mut lines = Vec::new();
+        let state = pb.state();
+        state.style.format_state(&state.state, &mut lines, 80);
+        assert_eq!(lines[0].as_ref(), "00:01:05 00:00:00");
+    }
+
+    #[test]
+    fn add_with_auto_remove_existing_member() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let pb = mp.add(ProgressBar::new(10));
+        let idx = pb.index().unwrap();
+
+        let pb = mp.add_with_auto_remove(pb);
+        assert_eq!(pb.index(), Some(idx));
+        assert!(!mp.state.read().unwrap().is_auto_remove(idx));
+        assert_eq!(mp.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn snapshot_order() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let footer = mp.set_footer(ProgressBar::new(1).with_prefix("footer"));
+        let last = mp.add(ProgressBar::new(10).with_prefix("last"));
+        let first = mp.insert(0, ProgressBar::new(20).with_prefix("first"));
+        let header = mp.set_header(ProgressBar::new(30).with_prefix("header"));
+        first.inc(5);
+        last.finish();
+
+        let snapshots = mp.snapshot();
+        let prefixes = snapshots
+            .iter()
+            .map(|s| s.prefix.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(prefixes, ["header", "first", "last", "footer"]);
+        assert_eq!(snapshots[1].pos, 5);
+        assert!(snapshots[2].is_finished());
+        drop((header, footer));
+    }
+
+    #[test]
+    fn current() {
+        assert!(MultiProgress::current().is_none());
+        let outer = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let inner = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+
+        let outer_guard = outer.enter();
+        let pb = ProgressBar::new_in_current(10);
+        assert_eq!(pb.index(), Some(0));
+        assert_eq!(outer.summary().lines().count(), 2);
+
+        {
+            let _inner_guard = inner.enter();
+            ProgressBar::new_in_current(10);
+            assert_eq!(inner.summary().lines().count(), 2);
+            assert_eq!(outer.summary().lines().count(), 2);
+
+            // Other threads have their own current `MultiProgress`
+            std::thread::spawn(|| assert!(MultiProgress::current().is_none()))
+                .join()
+                .unwrap();
+        }
+
+        ProgressBar::new_in_current(10);
+        assert_eq!(outer.summary().lines().count(), 3);
+        drop(outer_guard);
+
+        assert!(MultiProgress::current().is_none());
+        assert_eq!(ProgressBar::new_in_current(10).index(), None);
+    }
+
+    #[test]
+    fn max_tick_fps() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let now = Instant::now();
+        assert!(mp.state.read().unwrap().allow_steady_tick(now));
+
+        // There is no limit by default
+        mp.state.write().unwrap().last_frame = Some(now);
+        assert!(mp.state.read().unwrap().allow_steady_tick(now));
+
+        mp.set_max_tick_fps(20);
+        let state = mp.state.read().unwrap();
+        assert!(!state.allow_steady_tick(now + Duration::from_millis(10)));
+        assert!(state.allow_steady_tick(now + Duration::from_millis(50)));
+        drop(state);
+
+        mp.set_max_tick_fps(0);
+        assert!(mp.state.read().unwrap().allow_steady_tick(now));
+    }
 
     #[test]
     fn late_pb_drop() {
@@ -692,4 +2014,248 @@ mod tests {
         let pb = mp.add(ProgressBar::new(10));
         mp.add(pb);
     }
+
+    #[test]
+    fn summary() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let pb1 = mp.add(ProgressBar::new(10).with_id("download"));
+        let pb2 = mp.add(ProgressBar::new(20).with_prefix("extract"));
+        let pb3 = mp.add(ProgressBar::no_length().with_message("index"));
+
+        pb1.finish();
+        pb2.inc(5);
+        mp.remove(&pb2);
+        pb2.inc(5);
+        drop(pb2);
+        pb3.inc(3);
+
+        let summary = mp.summary();
+        let lines = summary
+            .lines()
+            .map(str::split_whitespace)
+            .collect::<Vec<_>>();
+        let cells = |i: usize, col: usize| lines[i].clone().nth(col).unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].clone().collect::<Vec<_>>(), SUMMARY_HEADER);
+        assert_eq!(
+            (cells(1, 0), cells(1, 1), cells(1, 4)),
+            ("download", "10/10", "finished")
+        );
+        assert_eq!(
+            (cells(2, 0), cells(2, 1), cells(2, 4)),
+            ("extract", "5/20", "running")
+        );
+        assert_eq!(
+            (cells(3, 0), cells(3, 1), cells(3, 4)),
+            ("index", "3", "running")
+        );
+
+        drop(pb3);
+        assert!(mp.summary().lines().nth(3).unwrap().ends_with("dropped"));
+    }
+
+    #[test]
+    fn summary_is_bounded() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let total = mp.add(ProgressBar::new(10).with_message("total"));
+        for i in 0..MAX_FINISHED_SUMMARY_ROWS + 5 {
+            mp.add(ProgressBar::new(10).with_message(i.to_string()))
+                .finish();
+        }
+
+        let summary = mp.summary();
+        let names = summary
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names.len(), MAX_FINISHED_SUMMARY_ROWS + 1);
+        // The oldest finished progress bars are dropped, running ones are kept
+        assert_eq!(names[..2], ["total", "5"]);
+        drop(total);
+    }
+
+    #[test]
+    fn subscribe_events() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let rx = mp.subscribe();
+
+        let pb = mp.add(ProgressBar::new(10).with_id("download"));
+        pb.set_position(4);
+        // Updates are sent at the draw rate
+        for pos in 5..10 {
+            pb.set_position(pos);
+        }
+        pb.finish();
+        pb.tick();
+        mp.remove(&pb);
+
+        let events = rx.try_iter().collect::<Vec<_>>();
+        let updates = events
+            .iter()
+            .filter(|event| matches!(event, ProgressEvent::Updated { .. }))
+            .count();
+        assert!(updates < 6);
+        assert_eq!(events.len(), updates + 3);
+        assert_eq!(
+            events[0],
+            ProgressEvent::Added {
+                index: 0,
+                id: Some("download".into())
+            }
+        );
+        assert!(matches!(
+            &events[1],
+            ProgressEvent::Updated { index: 0, progress }
+                if progress.pos == 4 && progress.id.as_deref() == Some("download")
+        ));
+        // Finishing is reported once, even though the finished bar is drawn again
+        assert!(matches!(
+            &events[events.len() - 2],
+            ProgressEvent::Finished { index: 0, progress } if progress.pos == 10
+        ));
+        assert_eq!(events.last(), Some(&ProgressEvent::Removed { index: 0 }));
+
+        // Dropped receivers are unsubscribed
+        drop(rx);
+        mp.add(ProgressBar::new(1));
+        assert!(mp.state.read().unwrap().subscribers.is_empty());
+    }
+
+    /// A terminal whose writes block until `gate` is unlocked
+    #[derive(Debug, Clone)]
+    struct SlowTerm {
+        gate: Arc<Mutex<()>>,
+        writing: Arc<AtomicBool>,
+    }
+
+    impl SlowTerm {
+        fn write(&self) -> io::Result<()> {
+            self.writing.store(true, Ordering::SeqCst);
+            drop(self.gate.lock().unwrap());
+            Ok(())
+        }
+    }
+
+    impl TermLike for SlowTerm {
+        fn width(&self) -> u16 {
+            80
+        }
+
+        fn move_cursor_up(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn move_cursor_down(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn move_cursor_right(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn move_cursor_left(&self, _: usize) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn write_line(&self, _: &str) -> io::Result<()> {
+            self.write()
+        }
+
+        fn write_str(&self, _: &str) -> io::Result<()> {
+            self.write()
+        }
+
+        fn clear_line(&self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn typed_errors() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let other = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let member = mp.add(ProgressBar::new(10));
+        let stranger = other.add(ProgressBar::new(10));
+
+        assert!(matches!(
+            mp.try_insert_before(&stranger, ProgressBar::new(1)),
+            Err(Error::InvalidUsage(_))
+        ));
+        assert!(matches!(
+            mp.try_remove(&ProgressBar::new(1)),
+            Err(Error::InvalidUsage(_))
+        ));
+        let pb = mp.try_insert_after(&member, ProgressBar::new(1)).unwrap();
+        mp.try_remove(&pb).unwrap();
+        mp.try_println("fine").unwrap();
+
+        let poisoned = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let state = poisoned.state.clone();
+        thread::spawn(move || {
+            let _state = state.write().unwrap();
+            panic!("poisoning the state");
+        })
+        .join()
+        .unwrap_err();
+        assert!(matches!(
+            poisoned.try_println("poisoned"),
+            Err(Error::Poisoned)
+        ));
+        assert!(matches!(poisoned.try_clear(), Err(Error::Poisoned)));
+    }
+
+    #[test]
+    fn survives_poisoning() {
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let pb = mp.add(ProgressBar::new(10));
+
+        let (bar, state) = (pb.clone(), mp.state.clone());
+        thread::spawn(move || {
+            let _bar = bar.state();
+            let _state = state.write().unwrap();
+            panic!("worker failed");
+        })
+        .join()
+        .unwrap_err();
+        assert!(mp.state.is_poisoned());
+
+        pb.inc(1);
+        mp.println("still running").unwrap();
+        mp.add(ProgressBar::new(5)).finish();
+        assert_eq!(pb.position(), 1);
+        mp.remove(&pb);
+    }
+
+    #[test]
+    fn unlocked_while_writing() {
+        let term = SlowTerm {
+            gate: Arc::default(),
+            writing: Arc::default(),
+        };
+        let gate = term.gate.clone();
+        let closed = gate.lock().unwrap();
+        let mp =
+            MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(term.clone())));
+        let pb = mp.add(ProgressBar::new(10));
+        let drawing = thread::spawn(move || pb.tick());
+
+        let start = Instant::now();
+        while !term.writing.load(Ordering::SeqCst) {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "frame not written"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+        // The frame is being written, but other progress bars can be updated
+        assert!(mp.state.try_write().is_ok());
+
+        drop(closed);
+        drawing.join().unwrap();
+    }
 }
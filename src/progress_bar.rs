@@ -1,21 +1,27 @@
 #[cfg(test)]
 use portable_atomic::{AtomicBool, Ordering};
 use std::borrow::Cow;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{Arc, Condvar, Mutex, MutexGuard, Weak};
 use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
-use std::{fmt, io, thread};
+use std::{fmt, io, mem, thread};
 
 #[cfg(test)]
 use once_cell::sync::Lazy;
 #[cfg(target_arch = "wasm32")]
 use web_time::Instant;
 
-use crate::draw_target::ProgressDrawTarget;
-use crate::state::{AtomicPosition, BarState, ProgressFinish, Reset, TabExpandedString};
+use crate::draw_target::{visual_line_count, ProgressDrawTarget};
+use crate::error::Error;
+use crate::state::{
+    AtomicPosition, BarState, EtaOverride, FinishReason, ParentLink, ProgressFinish,
+    ProgressSnapshot, Reset, TabExpandedString, TitlePercent,
+};
 use crate::style::ProgressStyle;
-use crate::{ProgressBarIter, ProgressIterator, ProgressState};
+use crate::sync::IgnorePoison;
+use crate::{MultiProgress, ProgressBarIter, ProgressIterator, ProgressState};
 
 /// A progress bar or spinner
 ///
@@ -47,6 +53,20 @@ impl ProgressBar {
         Self::with_draw_target(Some(len), ProgressDrawTarget::stderr())
     }
 
+    /// Creates a new progress bar with a given length, added to the current [`MultiProgress`]
+    ///
+    /// If a [`MultiProgress`] was [entered] on this thread, the progress bar is added to it like
+    /// with [`MultiProgress::add()`]. Otherwise, this is the same as [`ProgressBar::new()`].
+    ///
+    /// [entered]: MultiProgress::enter
+    pub fn new_in_current(len: u64) -> Self {
+        let pb = Self::new(len);
+        match MultiProgress::current() {
+            Some(multi) => multi.add(pb),
+            None => pb,
+        }
+    }
+
     /// Creates a new progress bar without a specified length
     ///
     /// This progress bar by default draws directly to stderr, and refreshes a maximum of 20 times
@@ -116,6 +136,45 @@ impl ProgressBar {
         self
     }
 
+    /// A convenience builder-like function for a progress bar with a given identifier
+    ///
+    /// See [`ProgressBar::set_id()`].
+    pub fn with_id(self, id: impl Into<Cow<'static, str>>) -> Self {
+        self.state().state.id = Some(id.into());
+        self
+    }
+
+    /// A convenience builder-like function for a progress bar in the given group
+    ///
+    /// The progress bars of a group can be shown, hidden or removed together with
+    /// [`MultiProgress::set_tag_visible()`] and [`MultiProgress::remove_tag()`].
+    pub fn group(self, tag: impl Into<Cow<'static, str>>) -> Self {
+        let tag = tag.into();
+        let mut state = self.state();
+        state.tag = Some(tag.clone());
+        if let Some((multi, idx)) = state.draw_target.remote() {
+            multi.write().ignore_poison().set_tag(idx, Some(tag));
+        }
+        drop(state);
+        self
+    }
+
+    /// A convenience builder-like function for a progress bar with a minimum verbosity
+    ///
+    /// Inside a [`MultiProgress`], the progress bar is only drawn while the verbosity set with
+    /// [`MultiProgress::set_verbosity()`] is at least `level`. The default is 0, so e.g. bars with
+    /// a minimum verbosity of 1 are only shown in verbose mode, while bars with a minimum
+    /// verbosity of -1 are even shown in quiet mode.
+    pub fn with_min_verbosity(self, level: i8) -> Self {
+        let mut state = self.state();
+        state.min_verbosity = level;
+        if let Some((multi, idx)) = state.draw_target.remote() {
+            multi.write().ignore_poison().set_min_verbosity(idx, level);
+        }
+        drop(state);
+        self
+    }
+
     /// A convenience builder-like function for a progress bar with a given position
     pub fn with_position(self, pos: u64) -> Self {
         self.state().state.set_pos(pos);
@@ -144,6 +203,20 @@ impl ProgressBar {
         self
     }
 
+    /// Creates a new progress bar over the range `start..end`
+    ///
+    /// The position starts at `start` and is absolute: `{pos}` renders it as-is and
+    /// [`ProgressBar::set_position()`] takes absolute values, while [`ProgressBar::inc()`] is
+    /// relative as usual. The fraction (and thus the bar and `{percent}`) is computed over the
+    /// range, which is useful e.g. for resuming a transfer at a byte offset. The length is `end`.
+    ///
+    /// This progress bar by default draws directly to stderr.
+    pub fn new_range(start: u64, end: u64) -> Self {
+        let rv = Self::with_draw_target(Some(end), ProgressDrawTarget::stderr());
+        rv.state().state.set_range_start(Ord::min(start, end));
+        rv
+    }
+
     /// Creates a new spinner
     ///
     /// This spinner by default draws directly to stderr. This adds the default spinner style to it.
@@ -153,11 +226,110 @@ impl ProgressBar {
         rv
     }
 
+    /// Creates a new timer for an operation with a known total duration
+    ///
+    /// The position of a timer advances by itself from the elapsed time (in milliseconds) via a
+    /// steady tick, so the ETA is exact. The timer can be finished early like any other progress
+    /// bar, and its duration can be changed with [`ProgressBar::set_total_duration()`]. Once the
+    /// total duration has elapsed, the timer is finished with the style's
+    /// [`ProgressFinish`](crate::ProgressFinish) by the next tick.
+    ///
+    /// This timer by default draws directly to stderr. This adds the default timer style to it.
+    pub fn new_timer(total: Duration) -> Self {
+        let rv = Self::with_draw_target(None, ProgressDrawTarget::stderr());
+        rv.set_style(ProgressStyle::default_timer());
+        {
+            let mut state = rv.state();
+            state.state.timer = true;
+            state.set_total_duration(Instant::now(), total);
+        }
+        rv.enable_steady_tick(TIMER_TICK_INTERVAL);
+        rv
+    }
+
+    /// Creates a spinner for the current sub-step of this progress bar
+    ///
+    /// If this progress bar is part of a [`MultiProgress`], the spinner is inserted immediately
+    /// below it. The spinner is automatically cleared when this progress bar finishes. If this
+    /// progress bar is not part of a [`MultiProgress`], the spinner is hidden.
+    ///
+    /// Like any spinner, it needs to be ticked to animate, e.g. by calling
+    /// [`ProgressBar::enable_steady_tick()`] on it.
+    pub fn attach_spinner(&self, msg: impl Into<Cow<'static, str>>) -> ProgressBar {
+        let spinner = Self::with_draw_target(None, ProgressDrawTarget::hidden())
+            .with_style(ProgressStyle::default_spinner())
+            .with_message(msg);
+
+        // Don't hold our own lock while inserting, which needs to look up our index
+        let multi = self
+            .state()
+            .draw_target
+            .remote()
+            .map(|(state, _)| state.clone());
+        if let Some(state) = multi {
+            MultiProgress { state }.insert_after(self, spinner.clone());
+        }
+
+        self.state().children.push(Arc::downgrade(&spinner.state));
+        spinner
+    }
+
+    /// Creates a child progress bar that advances this one by one step when it finishes
+    ///
+    /// See [`ProgressBar::add_child_with_weight()`].
+    pub fn add_child(&self, len: u64) -> ProgressBar {
+        self.add_child_with_weight(len, 1)
+    }
+
+    /// Creates a child progress bar that advances this one by `weight` steps when it finishes
+    ///
+    /// The child has the given length and the style of this progress bar. If this progress bar
+    /// is part of a [`MultiProgress`], the child is inserted below it and its existing children,
+    /// indented by two spaces per level of nesting; otherwise the child is hidden. Set the length
+    /// of this progress bar to the total weight of its children to track them.
+    ///
+    /// This progress bar only advances when the child finishes with [`ProgressBar::finish()`],
+    /// [`ProgressBar::finish_with_message()`], [`ProgressBar::finish_and_clear()`] or
+    /// [`ProgressBar::finish_using_style()`] (e.g. when a progress bar iterator is exhausted),
+    /// not when it is abandoned, fails or is dropped. Like spinners created with
+    /// [`ProgressBar::attach_spinner()`], unfinished children are cleared when this progress bar
+    /// finishes.
+    pub fn add_child_with_weight(&self, len: u64, weight: u64) -> ProgressBar {
+        let (style, depth, multi) = {
+            let state = self.state();
+            let multi = state
+                .draw_target
+                .remote()
+                .map(|(multi, idx)| (multi.clone(), state.last_descendant_index(idx)));
+            (state.style.clone(), state.depth, multi)
+        };
+
+        let child = Self::with_draw_target(Some(len), ProgressDrawTarget::hidden());
+        {
+            let mut state = child.state();
+            state.set_style(Instant::now(), style);
+            state.depth = depth + 1;
+            state.parent = Some(ParentLink {
+                state: Arc::downgrade(&self.state),
+                weight,
+            });
+        }
+
+        // Don't hold our own lock while inserting, which locks the child
+        if let Some((state, idx)) = multi {
+            MultiProgress { state }.insert_after_index(idx, child.clone());
+        }
+
+        self.state().children.push(Arc::downgrade(&child.state));
+        child
+    }
+
     /// Overrides the stored style
     ///
-    /// This does not redraw the bar. Call [`ProgressBar::tick()`] to force it.
+    /// If the bar was drawn before, it is cleared and redrawn with the new style right away, so
+    /// that switching between templates with a different layout doesn't leave stale output.
     pub fn set_style(&self, style: ProgressStyle) {
-        self.state().set_style(style);
+        self.state().set_style(Instant::now(), style);
     }
 
     /// Sets the tab width (default: 8). All tabs will be expanded to this many spaces.
@@ -203,8 +375,39 @@ impl ProgressBar {
         self.stop_and_replace_ticker(None);
     }
 
+    /// Changes the interval of the steady tick, enabling it if needed
+    ///
+    /// Unlike calling [`ProgressBar::enable_steady_tick()`] again, this keeps the background
+    /// thread and wakes it up right away, so the progress bar ticks immediately and then in the
+    /// new interval. If the thread exited because the progress bar was finished, a new one is
+    /// started. A zero `interval` disables the steady tick.
+    pub fn set_steady_tick(&self, interval: Duration) {
+        if interval.is_zero() {
+            return self.disable_steady_tick();
+        }
+
+        if let Some(ticker) = &*self.ticker.lock().ignore_poison() {
+            if ticker.set_interval(interval) {
+                return;
+            }
+        }
+        self.enable_steady_tick(interval);
+    }
+
+    /// Returns whether the progress bar is ticked by a background thread
+    ///
+    /// The thread exits when the progress bar is finished. See
+    /// [`ProgressBar::enable_steady_tick()`].
+    pub fn is_steady_tick_enabled(&self) -> bool {
+        self.ticker
+            .lock()
+            .ignore_poison()
+            .as_ref()
+            .is_some_and(Ticker::is_running)
+    }
+
     fn stop_and_replace_ticker(&self, interval: Option<Duration>) {
-        let mut ticker_state = self.ticker.lock().unwrap();
+        let mut ticker_state = self.ticker.lock().ignore_poison();
         if let Some(ticker) = ticker_state.take() {
             ticker.stop();
         }
@@ -220,8 +423,8 @@ impl ProgressBar {
     }
 
     fn tick_inner(&self, now: Instant) {
-        // Only tick if a `Ticker` isn't installed
-        if self.ticker.lock().unwrap().is_none() {
+        // Only tick if a `Ticker` isn't running
+        if !self.is_steady_tick_enabled() {
             self.state().tick(now);
         }
     }
@@ -235,9 +438,28 @@ impl ProgressBar {
         }
     }
 
+    /// Increases the number of failed items by `delta`
+    ///
+    /// Failed items are still counted in the position, so this is called in addition to
+    /// [`ProgressBar::inc()`], preferably before it so the next draw reflects both. The counts
+    /// can be rendered with the `{failures}` and `{successes}` placeholders.
+    pub fn inc_failures(&self, delta: u64) {
+        self.state()
+            .update(Instant::now(), |state| state.failures += delta, false);
+    }
+
+    /// Returns the number of failed items, see [`ProgressBar::inc_failures()`]
+    pub fn failures(&self) -> u64 {
+        self.state().state.failures()
+    }
+
     /// Decrease the position of the progress bar by `delta`
+    ///
+    /// The position does not go below 0. This is meant for work that is undone or re-queued:
+    /// unlike moving the position backwards with [`ProgressBar::set_position()`], it does not
+    /// reset the rate estimate, so the ETA stays stable.
     pub fn dec(&self, delta: u64) {
-        self.pos.dec(delta);
+        self.state().dec(delta);
         let now = Instant::now();
         if self.pos.allow(now) {
             self.tick_inner(now);
@@ -249,6 +471,15 @@ impl ProgressBar {
         self.state().draw_target.is_hidden()
     }
 
+    /// Returns true if the last draw did not fit the terminal height
+    ///
+    /// When a progress bar (e.g. with a multi-line template) is taller than the terminal, only
+    /// the lines that fit are drawn, followed by a `...` indicator. Callers can use this to switch
+    /// to a more compact style.
+    pub fn is_truncated(&self) -> bool {
+        self.state().draw_target.is_truncated()
+    }
+
     /// Indicates that the progress bar finished
     pub fn is_finished(&self) -> bool {
         self.state().state.is_finished()
@@ -269,10 +500,56 @@ impl ProgressBar {
         self.state().println(Instant::now(), msg.as_ref());
     }
 
+    /// Print a log line given as [`fmt::Arguments`] above the progress bar
+    ///
+    /// Like [`ProgressBar::println()`], but the line is only formatted if it is actually printed,
+    /// so chatty logging costs little while the progress bar is hidden. The
+    /// [`progress_println!`](crate::progress_println) macro is a shorthand for this method.
+    ///
+    /// ```rust
+    /// # use indicatif::ProgressBar;
+    /// let pb = ProgressBar::hidden();
+    /// pb.println_fmt(format_args!("processed {} items", 42));
+    /// ```
+    pub fn println_fmt(&self, args: fmt::Arguments<'_>) {
+        self.state().println(Instant::now(), args);
+    }
+
+    /// Print a log line above the progress bar, with `label` right-aligned in the gutter
+    ///
+    /// The label is formatted like the `{gutter}` key of the progress bar's style (see
+    /// [`ProgressStyle::with_gutter()`]), so log lines line up with the progress bars.
+    ///
+    /// ```rust,no_run
+    /// # use indicatif::{ProgressBar, ProgressStyle};
+    /// let pb = ProgressBar::new(10).with_style(
+    ///     ProgressStyle::with_template("{gutter:.green.bold} {wide_bar}").unwrap(),
+    /// );
+    /// pb.println_with_gutter("Compiling", "indicatif v0.17.11");
+    /// ```
+    pub fn println_with_gutter(&self, label: &str, msg: impl AsRef<str>) {
+        let mut state = self.state();
+        let line = state.style.format_gutter_line(label, msg.as_ref());
+        state.println(Instant::now(), &line);
+    }
+
     /// Update the `ProgressBar`'s inner [`ProgressState`]
     pub fn update(&self, f: impl FnOnce(&mut ProgressState)) {
-        self.state()
-            .update(Instant::now(), f, self.ticker.lock().unwrap().is_none());
+        self.state().update(
+            Instant::now(),
+            f,
+            self.ticker.lock().ignore_poison().is_none(),
+        );
+    }
+
+    /// Sets the tick counter, which selects the spinner frame, and redraws
+    ///
+    /// Unlike [`ProgressBar::tick()`], this does not advance the counter, so the rendered frame
+    /// only depends on `tick`. This is mainly useful to render exact frames in snapshot tests.
+    pub fn set_tick(&self, tick: u64) {
+        let mut state = self.state();
+        state.state.tick = tick;
+        state.update_estimate_and_draw(Instant::now());
     }
 
     /// Sets the position of the progress bar
@@ -284,6 +561,17 @@ impl ProgressBar {
         }
     }
 
+    /// Sets the position of the progress bar from a completion fraction between 0 and 1
+    ///
+    /// This is useful for work that reports its progress as a fraction. The fraction is clamped
+    /// and mapped onto the length (or the range, see [`ProgressBar::new_range()`]). If the length
+    /// is unknown, it is set to 10 000, so that `{pos}` and `{len}` render the fraction in units
+    /// of 0.01%.
+    pub fn set_position_from_fraction(&self, fraction: f32) {
+        let pos = self.state().state.pos_for_fraction(fraction);
+        self.set_position(pos);
+    }
+
     /// Sets the length of the progress bar to `None`
     pub fn unset_length(&self) {
         self.state().unset_length(Instant::now());
@@ -294,6 +582,15 @@ impl ProgressBar {
         self.state().set_length(Instant::now(), len);
     }
 
+    /// Sets the total duration of a timer created with [`ProgressBar::new_timer()`]
+    ///
+    /// This can be used to extend or shorten the timer while it is running. If the new duration
+    /// has already elapsed, the timer is finished right away. Has no effect on progress bars that
+    /// are not timers.
+    pub fn set_total_duration(&self, total: Duration) {
+        self.state().set_total_duration(Instant::now(), total);
+    }
+
     /// Increase the length of the progress bar
     pub fn inc_length(&self, delta: u64) {
         self.state().inc_length(Instant::now(), delta);
@@ -318,12 +615,63 @@ impl ProgressBar {
     ///
     /// For the message to be visible, the `{msg}` placeholder must be present in the template (see
     /// [`ProgressStyle`]).
+    ///
+    /// Any messages queued with [`ProgressBar::set_message_enqueue()`] are discarded.
     pub fn set_message(&self, msg: impl Into<Cow<'static, str>>) {
+        self.state().set_message(Instant::now(), msg.into());
+    }
+
+    /// Queues a message to be shown for at least `min_display`
+    ///
+    /// If the current queued message has been shown for its minimum display time, `msg` is shown
+    /// right away. Otherwise it is shown once all messages queued before it had their turn. This
+    /// keeps very fast message updates (e.g. the file currently being processed) readable.
+    ///
+    /// The queue advances whenever the progress bar ticks, so this is best combined with
+    /// [`ProgressBar::enable_steady_tick()`].
+    ///
+    /// At most 16 messages are queued. If messages arrive faster than they can be shown, the
+    /// oldest queued messages are dropped, so that the display doesn't fall further and further
+    /// behind.
+    pub fn set_message_enqueue(&self, msg: impl Into<Cow<'static, str>>, min_display: Duration) {
+        self.state()
+            .enqueue_message(Instant::now(), msg.into(), min_display);
+    }
+
+    /// Sets a stable identifier for the progress bar
+    ///
+    /// The identifier is carried in every structured event emitted for this progress bar (see
+    /// [`ProgressDrawTarget::ipc()`]) and can be rendered with the `{id}` placeholder.
+    pub fn set_id(&self, id: impl Into<Cow<'static, str>>) {
         let mut state = self.state();
-        state.state.message = TabExpandedString::new(msg.into(), state.tab_width);
+        state.state.id = Some(id.into());
         state.update_estimate_and_draw(Instant::now());
     }
 
+    /// Creates an independent progress bar with the same configuration
+    ///
+    /// Unlike [`ProgressBar::clone()`], which returns another handle to the same progress bar,
+    /// this creates a new progress bar that copies the style, length, range start, tab width,
+    /// prefix, message and finish behavior of this one, but starts at the beginning with fresh
+    /// timing. This is useful for fanning out workers with identical configuration.
+    ///
+    /// The draw target is not copied: like [`ProgressBar::new()`], the new progress bar draws to
+    /// stderr, so add it to a [`MultiProgress`] or [set] a draw target as needed. Steady ticks,
+    /// the identifier and child bars are not copied either.
+    ///
+    /// [set]: ProgressBar::set_draw_target
+    pub fn duplicate(&self) -> ProgressBar {
+        let pos = Arc::new(AtomicPosition::new());
+        let state =
+            self.state()
+                .duplicate(Instant::now(), ProgressDrawTarget::stderr(), pos.clone());
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            pos,
+            ticker: Arc::new(Mutex::new(None)),
+        }
+    }
+
     /// Creates a new weak reference to this [`ProgressBar`]
     pub fn downgrade(&self) -> WeakProgressBar {
         WeakProgressBar {
@@ -333,6 +681,27 @@ impl ProgressBar {
         }
     }
 
+    /// Pauses the clock of the progress bar
+    ///
+    /// While the progress bar is paused, e.g. while waiting for user input or a remote lock, the
+    /// elapsed time stands still and the time does not count as a stall in the rate estimate, so
+    /// the `{elapsed}`, `{eta}` and `{per_sec}` template keys only reflect the time the progress
+    /// bar was active. Updates are still drawn. Pausing a paused or finished progress bar has no
+    /// effect.
+    pub fn pause(&self) {
+        self.state().pause(Instant::now());
+    }
+
+    /// Resumes the progress bar after [`ProgressBar::pause()`]
+    pub fn resume(&self) {
+        self.state().resume(Instant::now());
+    }
+
+    /// Returns whether the progress bar is paused, see [`ProgressBar::pause()`]
+    pub fn is_paused(&self) -> bool {
+        self.state().state.is_paused()
+    }
+
     /// Resets the ETA calculation
     ///
     /// This can be useful if the progress bars made a large jump or was paused for a prolonged
@@ -341,6 +710,58 @@ impl ProgressBar {
         self.state().reset(Instant::now(), Reset::Eta);
     }
 
+    /// Overrides the estimated remaining time with an externally computed one
+    ///
+    /// The `{eta}`, `{eta_precise}`, `{duration}` and `{duration_precise}` template keys (and
+    /// [`ProgressState::eta()`]) use `eta` instead of the internal estimate, counting down from
+    /// the time it was set, until it is set again or cleared with
+    /// [`ProgressBar::clear_eta()`]. This is useful if the application can compute a more
+    /// accurate ETA, for example from server-side data. The override also applies if the length
+    /// is unknown.
+    pub fn set_eta(&self, eta: Duration) {
+        self.set_eta_override(eta, None);
+    }
+
+    /// Overrides the estimated remaining time like [`ProgressBar::set_eta()`], until it's stale
+    ///
+    /// If the ETA isn't set again within `stale_after`, the internal estimate takes over again.
+    /// This suits ETAs reported periodically by another party, like a server, which might stop
+    /// reporting them.
+    pub fn set_external_eta(&self, eta: Duration, stale_after: Duration) {
+        self.set_eta_override(eta, Some(stale_after));
+    }
+
+    fn set_eta_override(&self, eta: Duration, stale_after: Option<Duration>) {
+        let now = Instant::now();
+        let mut state = self.state();
+        state.state.eta_override = Some(EtaOverride {
+            eta,
+            set_at: now,
+            stale_after,
+        });
+        state.update_estimate_and_draw(now);
+    }
+
+    /// Removes the ETA set with [`ProgressBar::set_eta()`] or
+    /// [`ProgressBar::set_external_eta()`], returning to the internal estimate
+    pub fn clear_eta(&self) {
+        let now = Instant::now();
+        let mut state = self.state();
+        state.state.eta_override = None;
+        state.update_estimate_and_draw(now);
+    }
+
+    /// Starts a new session at the current position
+    ///
+    /// The progress made in the session is rendered by the `{session_pos}` and `{session_bytes}`
+    /// template keys, while `{pos}` and `{bytes}` keep rendering the absolute position. This is
+    /// useful for resumed downloads: set the position to the resume offset, then reset the
+    /// session. Bars created with [`ProgressBar::new_range()`] start their session at the start
+    /// of the range.
+    pub fn reset_session(&self) {
+        self.state().state.reset_session();
+    }
+
     /// Resets elapsed time and the ETA calculation
     pub fn reset_elapsed(&self) {
         self.state().reset(Instant::now(), Reset::Elapsed);
@@ -353,8 +774,9 @@ impl ProgressBar {
 
     /// Finishes the progress bar and leaves the current message
     pub fn finish(&self) {
-        self.state()
-            .finish_using_style(Instant::now(), ProgressFinish::AndLeave);
+        self.finish_inner(|state| {
+            state.finish_using_style(Instant::now(), ProgressFinish::AndLeave)
+        });
     }
 
     /// Finishes the progress bar and sets a message
@@ -362,14 +784,27 @@ impl ProgressBar {
     /// For the message to be visible, the `{msg}` placeholder must be present in the template (see
     /// [`ProgressStyle`]).
     pub fn finish_with_message(&self, msg: impl Into<Cow<'static, str>>) {
-        self.state()
-            .finish_using_style(Instant::now(), ProgressFinish::WithMessage(msg.into()));
+        let finish = ProgressFinish::WithMessage(msg.into());
+        self.finish_inner(|state| state.finish_using_style(Instant::now(), finish));
     }
 
     /// Finishes the progress bar and completely clears it
     pub fn finish_and_clear(&self) {
-        self.state()
-            .finish_using_style(Instant::now(), ProgressFinish::AndClear);
+        self.finish_inner(|state| {
+            state.finish_using_style(Instant::now(), ProgressFinish::AndClear)
+        });
+    }
+
+    /// Finishes the progress bar and moves it into the scrollback
+    ///
+    /// Inside a [`MultiProgress`](crate::MultiProgress), the final state of the progress bar is
+    /// printed above the other bars like a line from [`MultiProgress::println`], freeing its
+    /// space for the remaining bars, and the progress bar is removed from the `MultiProgress`.
+    /// Otherwise, this behaves like [`ProgressBar::finish()`].
+    ///
+    /// [`MultiProgress::println`]: crate::MultiProgress::println
+    pub fn finish_into_scrollback(&self) {
+        self.finish_inner(|state| state.finish_into_scrollback(Instant::now()));
     }
 
     /// Finishes the progress bar and leaves the current message and progress
@@ -389,13 +824,186 @@ impl ProgressBar {
         );
     }
 
+    /// Finishes the progress bar as failed, leaving the current message and progress
+    ///
+    /// Renders like [`ProgressBar::abandon()`], but [`ProgressBar::finish_reason()`] reports
+    /// [`FinishReason::Failed`].
+    pub fn fail(&self) {
+        self.state().finish_with_reason(
+            Instant::now(),
+            ProgressFinish::Abandon,
+            FinishReason::Failed,
+        );
+    }
+
+    /// Finishes the progress bar as failed and sets a message, leaving the current progress
+    ///
+    /// For the message to be visible, the `{msg}` placeholder must be present in the template (see
+    /// [`ProgressStyle`]).
+    pub fn fail_with_message(&self, msg: impl Into<Cow<'static, str>>) {
+        self.state().finish_with_reason(
+            Instant::now(),
+            ProgressFinish::AbandonWithMessage(msg.into()),
+            FinishReason::Failed,
+        );
+    }
+
+    /// Runs `f`, failing the progress bar with the panic message if it panics
+    ///
+    /// If `f` panics, the progress bar is finished with [`ProgressBar::fail_with_message()`]
+    /// using the panic message, and the panic is then resumed. This way a crashing worker leaves
+    /// a failed progress bar behind instead of a frozen one.
+    ///
+    /// ```rust,no_run
+    /// # use indicatif::ProgressBar;
+    /// let pb = ProgressBar::new(10);
+    /// std::thread::spawn(move || {
+    ///     pb.catch_panic(|| {
+    ///         for i in 0..10 {
+    ///             assert!(i < 5, "item {i} is broken");
+    ///             pb.inc(1);
+    ///         }
+    ///     });
+    ///     pb.finish();
+    /// });
+    /// ```
+    pub fn catch_panic<R>(&self, f: impl FnOnce() -> R) -> R {
+        // The panic is resumed right away, so nothing can observe broken invariants
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(value) => value,
+            Err(payload) => {
+                let msg = match payload.downcast_ref::<&str>() {
+                    Some(msg) => Cow::Owned(msg.to_string()),
+                    None => match payload.downcast_ref::<String>() {
+                        Some(msg) => Cow::Owned(msg.clone()),
+                        None => Cow::Borrowed("panicked"),
+                    },
+                };
+                self.fail_with_message(msg);
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    /// Runs `op` up to `attempts` times until it succeeds, showing the retries in the message
+    ///
+    /// While retrying, the current attempt is appended to the message. After a failed attempt,
+    /// the time until the next attempt is set as the ETA (see [`ProgressBar::set_eta()`]), so
+    /// the `{eta}` template key counts it down; the delay starts at `backoff` and doubles after
+    /// every failure. Before each retry the position is restored to where it was before the
+    /// first attempt and the ETA calculation is reset, so a restarted download doesn't count
+    /// twice.
+    ///
+    /// Afterwards, the original message is restored. On success, the progress bar is finished
+    /// using the behavior stored in the [`ProgressStyle`]. If the last attempt fails too, the
+    /// progress bar is finished with [`ProgressBar::fail()`] and the last error is returned.
+    /// At least one attempt is always made.
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use indicatif::ProgressBar;
+    /// # fn download(pb: &ProgressBar) -> Result<(), std::io::Error> { Ok(()) }
+    /// let pb = ProgressBar::new(1024);
+    /// let result = pb.retry(3, Duration::from_secs(1), || download(&pb));
+    /// ```
+    pub fn retry<T, E>(
+        &self,
+        attempts: usize,
+        backoff: Duration,
+        mut op: impl FnMut() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let attempts = attempts.max(1);
+        let message = self.message();
+        let status = |status: String| match message.is_empty() {
+            true => status,
+            false => format!("{message} ({status})"),
+        };
+        let start = self.position();
+        let mut delay = backoff;
+        let mut attempt = 1;
+        loop {
+            let result = op();
+            if result.is_ok() || attempt == attempts {
+                self.set_message(message);
+                self.clear_eta();
+                match result.is_ok() {
+                    true => self.finish_using_style(),
+                    false => self.fail(),
+                }
+                return result;
+            }
+
+            self.set_message(status(format!("attempt {attempt}/{attempts} failed")));
+            self.set_eta(delay);
+            thread::sleep(delay);
+
+            attempt += 1;
+            self.set_message(status(format!("attempt {attempt}/{attempts}")));
+            self.clear_eta();
+            self.set_position(start);
+            self.reset_eta();
+            delay = delay.saturating_mul(2);
+        }
+    }
+
+    /// Returns how the progress bar ended, or `None` if it is still in progress
+    ///
+    /// The reason can also be rendered with the `{finish_reason}` template key.
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.state().state.finish_reason()
+    }
+
     /// Finishes the progress bar using the behavior stored in the [`ProgressStyle`]
     ///
     /// See [`ProgressBar::with_finish()`].
     pub fn finish_using_style(&self) {
-        let mut state = self.state();
-        let finish = state.on_finish.clone();
-        state.finish_using_style(Instant::now(), finish);
+        self.finish_inner(|state| {
+            let finish = state.on_finish.clone();
+            state.finish_using_style(Instant::now(), finish);
+        });
+    }
+
+    /// Finishes the progress bar with `finish`, then advances the parent of a child bar
+    fn finish_inner(&self, finish: impl FnOnce(&mut BarState)) {
+        let parent = {
+            let mut state = self.state();
+            let was_finished = state.state.is_finished();
+            finish(&mut state);
+            if let Some(deadline) = state.pending_clear {
+                self.schedule_clear(deadline);
+            }
+            match (&state.parent, state.state.finish_reason()) {
+                (Some(parent), Some(FinishReason::Finished)) if !was_finished => {
+                    parent.state.upgrade().map(|state| (state, parent.weight))
+                }
+                _ => None,
+            }
+        };
+
+        // The parent is locked without holding our own lock, as it locks its children when
+        // finishing
+        if let Some((parent, weight)) = parent {
+            parent
+                .lock()
+                .ignore_poison()
+                .advance_from_child(Instant::now(), weight);
+        }
+    }
+
+    /// Clears the progress bar at `deadline` from the background thread of [`PENDING_CLEARS`]
+    ///
+    /// The queue keeps the state alive, so the progress bar stays visible even if all handles
+    /// are dropped right after finishing it.
+    fn schedule_clear(&self, deadline: Instant) {
+        let mut pending = PENDING_CLEARS.lock().ignore_poison();
+        pending.bars.push((deadline, self.state.clone()));
+        match pending.running {
+            true => PENDING_CLEARS_CHANGED.notify_one(),
+            false => {
+                pending.running = true;
+                thread::spawn(run_pending_clears);
+            }
+        }
     }
 
     /// Sets a different draw target for the progress bar
@@ -423,6 +1031,75 @@ impl ProgressBar {
         state.draw_target = target;
     }
 
+    /// Sets whether to ring the terminal bell when the progress bar is finished
+    ///
+    /// The bell (BEL) is written once when the progress bar is finished with e.g.
+    /// [`ProgressBar::finish()`], but not when it is abandoned or dropped. Depending on its
+    /// configuration, the terminal plays a sound or flashes. Nothing is written if the draw target
+    /// is hidden.
+    pub fn bell_on_finish(&self, enabled: bool) {
+        self.state().bell_on_finish = enabled;
+    }
+
+    /// Sets whether to ring the terminal bell when the progress bar fails
+    ///
+    /// See [`ProgressBar::fail()`] and [`ProgressBar::bell_on_finish()`].
+    pub fn bell_on_failure(&self, enabled: bool) {
+        self.state().bell_on_failure = enabled;
+    }
+
+    /// Shows the percentage of the progress bar in the terminal window title
+    ///
+    /// This lets users keep an eye on a long-running task while working in another window,
+    /// without the noise of [`ProgressBar::bell_on_finish()`]. The title is updated whenever the
+    /// displayed percentage changes.
+    ///
+    /// indicatif doesn't read terminal input, so it can't detect focus itself. If the application
+    /// tracks focus (e.g. from the focus events of its terminal library), it can pass a flag that
+    /// it keeps set while the terminal is focused: the title is then only updated while the flag
+    /// is unset. Without a flag, the title is always updated.
+    ///
+    /// The previous title is saved on the title stack of the terminal before it is replaced, and
+    /// restored when the flag gets set or the progress bar is finished. Nothing is written for
+    /// progress bars without a length or if the draw target is hidden.
+    pub fn enable_title_percent(&self, focused: Option<Arc<std::sync::atomic::AtomicBool>>) {
+        self.state().title_percent = Some(TitlePercent::new(focused));
+    }
+
+    /// Sets the minimum time between two redraws of this progress bar
+    ///
+    /// Updates within `interval` of the previous redraw are recorded but not drawn, before the
+    /// rate limit of the draw target is consulted. This is useful to tame progress bars that are
+    /// updated very frequently, especially inside a [`MultiProgress`] shared with other bars.
+    /// Finishing the progress bar always redraws it.
+    ///
+    /// [`MultiProgress`]: crate::MultiProgress
+    pub fn set_min_redraw_interval(&self, interval: Duration) {
+        self.state().min_redraw_interval = Some(interval);
+    }
+
+    /// Sets how long the progress bar has to be running before it is drawn
+    ///
+    /// Nothing is drawn until `delay` has passed since the progress bar was created or reset, so
+    /// operations that turn out to be fast don't show a progress bar at all. Once the delay has
+    /// passed, the progress bar is drawn with the next update or tick; enable a steady tick (see
+    /// [`ProgressBar::enable_steady_tick()`]) to have it drawn even if it is not updated.
+    pub fn set_render_delay(&self, delay: Duration) {
+        self.state().render_delay = Some(delay);
+    }
+
+    /// Sets how long the progress bar is visible at least before it is cleared
+    ///
+    /// Progress bars of very fast operations otherwise appear and vanish within a single frame,
+    /// which looks like flicker. With a minimum duration, [`ProgressBar::finish_and_clear()`]
+    /// (and finishing with [`ProgressFinish::AndClear`]) shows the finished progress bar until it
+    /// was visible for `duration`, then clears it from a background thread. A progress bar that
+    /// was never drawn is cleared right away, and so is a progress bar that is dropped without
+    /// being finished, as there is nothing left to keep it visible.
+    pub fn set_min_visible_duration(&self, duration: Duration) {
+        self.state().min_visible = Some(duration);
+    }
+
     /// Hide the progress bar temporarily, execute `f`, then redraw the progress bar
     ///
     /// Useful for external code that writes to the standard output.
@@ -446,6 +1123,30 @@ impl ProgressBar {
         self.state().suspend(Instant::now(), f)
     }
 
+    /// Hide the progress bar while asking the user for input in `f`, then redraw it
+    ///
+    /// Like [`ProgressBar::suspend`], but the bar is [paused](ProgressBar::pause) while `f` runs,
+    /// so that waiting for an answer neither counts towards the elapsed time nor makes the
+    /// remaining work look slower. This is meant for interactive prompts, e.g. from the
+    /// `dialoguer` crate. As with `suspend`, other threads updating the progress bar are blocked
+    /// until `f` returns.
+    ///
+    /// ```rust,no_run
+    /// # use indicatif::ProgressBar;
+    /// # use std::io::{self, Write};
+    /// let pb = ProgressBar::new(3);
+    /// let overwrite = pb.prompt(|| {
+    ///     print!("Overwrite existing files? [y/N] ");
+    ///     io::stdout().flush().unwrap();
+    ///     let mut answer = String::new();
+    ///     io::stdin().read_line(&mut answer).unwrap();
+    ///     answer.trim() == "y"
+    /// });
+    /// ```
+    pub fn prompt<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.state().prompt(Instant::now(), f)
+    }
+
     /// Wraps an [`Iterator`] with the progress bar
     ///
     /// ```rust,no_run
@@ -478,6 +1179,7 @@ impl ProgressBar {
         ProgressBarIter {
             progress: self.clone(),
             it: read,
+            cancel: None,
         }
     }
 
@@ -499,6 +1201,7 @@ impl ProgressBar {
         ProgressBarIter {
             progress: self.clone(),
             it: write,
+            cancel: None,
         }
     }
 
@@ -525,6 +1228,7 @@ impl ProgressBar {
         ProgressBarIter {
             progress: self.clone(),
             it: write,
+            cancel: None,
         }
     }
 
@@ -548,6 +1252,7 @@ impl ProgressBar {
         ProgressBarIter {
             progress: self.clone(),
             it: read,
+            cancel: None,
         }
     }
 
@@ -570,6 +1275,7 @@ impl ProgressBar {
         ProgressBarIter {
             progress: self.clone(),
             it: stream,
+            cancel: None,
         }
     }
 
@@ -578,6 +1284,13 @@ impl ProgressBar {
         self.state().state.pos()
     }
 
+    /// Returns the progress made in the current session
+    ///
+    /// See [`ProgressBar::reset_session()`].
+    pub fn session_position(&self) -> u64 {
+        self.state().state.session_pos()
+    }
+
     /// Returns the current length
     pub fn length(&self) -> Option<u64> {
         self.state().state.len()
@@ -603,6 +1316,79 @@ impl ProgressBar {
         self.state().state.elapsed()
     }
 
+    /// Returns a consistent snapshot of the progress bar's statistics
+    ///
+    /// Statistics are recorded regardless of the draw target, so this can be used to print a
+    /// summary when the progress bar is hidden, e.g. because the output is not a terminal.
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        self.state().snapshot(Instant::now())
+    }
+
+    /// Returns the current elapsed time as rendered by the `{elapsed_precise}` template key
+    ///
+    /// See also [`ProgressState::elapsed_precise()`] and the related `*_precise()` and `*_human()`
+    /// methods on [`ProgressState`], reachable through [`ProgressBar::update()`].
+    pub fn elapsed_precise(&self) -> String {
+        self.state().state.elapsed_precise()
+    }
+
+    /// Returns the current ETA as rendered by the `{eta_precise}` template key
+    pub fn eta_precise(&self) -> String {
+        self.state().state.eta_precise()
+    }
+
+    /// Returns the current expected duration as rendered by the `{duration_precise}` template key
+    pub fn duration_precise(&self) -> String {
+        self.state().state.duration_precise()
+    }
+
+    /// Returns when a frame including the progress bar was last written to the terminal
+    ///
+    /// Updates are not drawn if the draw target is hidden or rate limited, so this can be used
+    /// to do extra work only when the output actually changed:
+    ///
+    /// ```rust
+    /// # use std::time::Instant;
+    /// # use indicatif::ProgressBar;
+    /// let pb = ProgressBar::new(100);
+    /// let before = Instant::now();
+    /// pb.inc(1);
+    /// if pb.last_draw_time() >= Some(before) {
+    ///     // the progress bar was redrawn by `inc()`
+    /// }
+    /// ```
+    ///
+    /// Returns `None` if the progress bar has not been drawn yet.
+    pub fn last_draw_time(&self) -> Option<Instant> {
+        self.state().last_draw
+    }
+
+    /// Returns the lines of the progress bar as they were last drawn
+    ///
+    /// The lines may contain ANSI escape codes. This is empty if the progress bar has not been
+    /// drawn yet, was cleared or draws to a hidden target.
+    pub fn last_rendered_lines(&self) -> Vec<String> {
+        self.state()
+            .draw_target
+            .last_drawn_lines()
+            .into_iter()
+            .map(|line| line.as_ref().to_string())
+            .collect()
+    }
+
+    /// Returns the number of terminal lines the progress bar took up when it was last drawn
+    ///
+    /// This accounts for lines that wrap because they are wider than the terminal.
+    pub fn visual_height(&self) -> usize {
+        let state = self.state();
+        match state.draw_target.width() {
+            Some(width) => {
+                visual_line_count(&state.draw_target.last_drawn_lines(), width as usize).as_usize()
+            }
+            None => 0,
+        }
+    }
+
     /// Index in the `MultiState`
     pub(crate) fn index(&self) -> Option<usize> {
         self.state().draw_target.remote().map(|(_, idx)| idx)
@@ -618,9 +1404,24 @@ impl ProgressBar {
         self.state().state.prefix.expanded().to_string()
     }
 
+    /// Current identifier, if one was set
+    pub fn id(&self) -> Option<String> {
+        self.state().state.id().map(Into::into)
+    }
+
+    /// Whether updating the progress bar now would draw a frame
+    pub(crate) fn would_draw(&self) -> bool {
+        let now = Instant::now();
+        self.pos.would_allow(now) && self.state().would_draw(now)
+    }
+
     #[inline]
     pub(crate) fn state(&self) -> MutexGuard<'_, BarState> {
-        self.state.lock().unwrap()
+        self.state.lock().ignore_poison()
+    }
+
+    pub(crate) fn try_state(&self) -> Result<MutexGuard<'_, BarState>, Error> {
+        Ok(self.state.lock()?)
     }
 }
 
@@ -654,11 +1455,72 @@ impl WeakProgressBar {
     }
 }
 
+const TIMER_TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress bars waiting to be cleared, see [`ProgressBar::set_min_visible_duration()`]
+///
+/// A single thread clears the progress bars of all pending clears. It is started when the
+/// first clear is scheduled and exits when no clears are left.
+static PENDING_CLEARS: Mutex<PendingClears> = Mutex::new(PendingClears {
+    bars: Vec::new(),
+    running: false,
+});
+static PENDING_CLEARS_CHANGED: Condvar = Condvar::new();
+
+struct PendingClears {
+    bars: Vec<(Instant, Arc<Mutex<BarState>>)>,
+    /// Whether the thread clearing the progress bars is running
+    running: bool,
+}
+
+fn run_pending_clears() {
+    let mut pending = PENDING_CLEARS.lock().ignore_poison();
+    loop {
+        let now = Instant::now();
+        let (due, waiting) = mem::take(&mut pending.bars)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(deadline, _)| *deadline <= now);
+        pending.bars = waiting;
+
+        if !due.is_empty() {
+            // Don't block scheduling new clears while clearing
+            drop(pending);
+            for (deadline, state) in due {
+                let mut state = state.lock().ignore_poison();
+                // The progress bar may have been reset and finished again in the meantime
+                if state.pending_clear == Some(deadline) {
+                    state.clear_pending(Instant::now());
+                }
+            }
+            pending = PENDING_CLEARS.lock().ignore_poison();
+            continue;
+        }
+
+        let Some(next) = pending.bars.iter().map(|(deadline, _)| *deadline).min() else {
+            pending.running = false;
+            return;
+        };
+        pending = PENDING_CLEARS_CHANGED
+            .wait_timeout(pending, next.saturating_duration_since(now))
+            .ignore_poison()
+            .0;
+    }
+}
+
 pub(crate) struct Ticker {
-    stopping: Arc<(Mutex<bool>, Condvar)>,
+    control: Arc<(Mutex<TickerSettings>, Condvar)>,
     join_handle: Option<thread::JoinHandle<()>>,
 }
 
+/// Settings shared with the ticker thread
+struct TickerSettings {
+    /// Whether the ticker was requested to stop, or stopped because the bar was finished
+    stopping: bool,
+    /// Whether the ticker should tick right away
+    woken: bool,
+    interval: Duration,
+}
+
 impl Drop for Ticker {
     fn drop(&mut self) {
         self.stop();
@@ -673,63 +1535,95 @@ impl Ticker {
     pub(crate) fn new(interval: Duration, bar_state: &Arc<Mutex<BarState>>) -> Self {
         debug_assert!(!interval.is_zero());
 
-        // A `Mutex<bool>` is used as a flag to indicate whether the ticker was requested to stop.
-        // The `Condvar` is used a notification mechanism: when the ticker is dropped, we notify
-        // the thread and interrupt the ticker wait.
-        #[allow(clippy::mutex_atomic)]
-        let stopping = Arc::new((Mutex::new(false), Condvar::new()));
-        let control = TickerControl {
-            stopping: stopping.clone(),
+        // The `Mutex` holds the settings, including a flag to indicate whether the ticker was
+        // requested to stop. The `Condvar` is used a notification mechanism: when the ticker is
+        // dropped or the interval changes, we notify the thread and interrupt the ticker wait.
+        let control = Arc::new((
+            Mutex::new(TickerSettings {
+                stopping: false,
+                woken: false,
+                interval,
+            }),
+            Condvar::new(),
+        ));
+        let ticker = TickerControl {
+            control: control.clone(),
             state: Arc::downgrade(bar_state),
         };
 
-        let join_handle = thread::spawn(move || control.run(interval));
+        let join_handle = thread::spawn(move || ticker.run());
         Self {
-            stopping,
+            control,
             join_handle: Some(join_handle),
         }
     }
 
     pub(crate) fn stop(&self) {
-        *self.stopping.0.lock().unwrap() = true;
-        self.stopping.1.notify_one();
+        self.control.0.lock().ignore_poison().stopping = true;
+        self.control.1.notify_one();
+    }
+
+    /// Changes the interval, ticking right away
+    ///
+    /// Returns `false` if the ticker thread has stopped.
+    pub(crate) fn set_interval(&self, interval: Duration) -> bool {
+        debug_assert!(!interval.is_zero());
+        let mut settings = self.control.0.lock().ignore_poison();
+        if settings.stopping {
+            return false;
+        }
+        settings.interval = interval;
+        settings.woken = true;
+        self.control.1.notify_one();
+        true
+    }
+
+    fn is_running(&self) -> bool {
+        !self.control.0.lock().ignore_poison().stopping
     }
 }
 
 struct TickerControl {
-    stopping: Arc<(Mutex<bool>, Condvar)>,
+    control: Arc<(Mutex<TickerSettings>, Condvar)>,
     state: Weak<Mutex<BarState>>,
 }
 
 impl TickerControl {
-    fn run(&self, interval: Duration) {
+    fn run(&self) {
         #[cfg(test)]
         TICKER_RUNNING.store(true, Ordering::SeqCst);
 
+        let mut interval = self.control.0.lock().ignore_poison().interval;
         while let Some(arc) = self.state.upgrade() {
-            let mut state = arc.lock().unwrap();
+            let mut state = arc.lock().ignore_poison();
             if state.state.is_finished() {
+                // Mark the ticker as stopped while holding the lock on the state, so a reset
+                // and a following `set_steady_tick()` see it and start a new ticker
+                self.control.0.lock().ignore_poison().stopping = true;
                 break;
             }
 
-            state.tick(Instant::now());
+            state.steady_tick(Instant::now());
 
             drop(state); // Don't forget to drop the lock before sleeping
             drop(arc); // Also need to drop Arc otherwise BarState won't be dropped
 
-            // Wait for `interval` but return early if we are notified to stop
-            let result = self
-                .stopping
+            // Wait for `interval` but return early if we are notified to stop or woken up
+            let (mut settings, _) = self
+                .control
                 .1
-                .wait_timeout_while(self.stopping.0.lock().unwrap(), interval, |stopped| {
-                    !*stopped
-                })
-                .unwrap();
-
-            // If the wait didn't time out, it means we were notified to stop
-            if !result.1.timed_out() {
+                .wait_timeout_while(
+                    self.control.0.lock().ignore_poison(),
+                    interval,
+                    |settings| !settings.stopping && !settings.woken,
+                )
+                .ignore_poison();
+
+            if settings.stopping {
                 break;
             }
+            settings.woken = false;
+            interval = settings.interval;
         }
 
         #[cfg(test)]
@@ -744,6 +1638,304 @@ pub(crate) static TICKER_TEST: Lazy<Mutex<()>> = Lazy::new(Mutex::default);
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::MAX_QUEUED_MESSAGES;
+
+    #[test]
+    fn println_fmt_hidden_does_not_format() {
+        struct Panics;
+        impl fmt::Display for Panics {
+            fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+                panic!("formatted a line that is not printed");
+            }
+        }
+
+        let pb = ProgressBar::hidden();
+        pb.println_fmt(format_args!("{}", Panics));
+        crate::progress_println!(pb, "{}", Panics);
+    }
+
+    #[test]
+    fn dec_saturates() {
+        let pb = ProgressBar::hidden();
+        pb.inc(5);
+        pb.dec(2);
+        assert_eq!(pb.position(), 3);
+        pb.dec(10);
+        assert_eq!(pb.position(), 0);
+    }
+
+    #[test]
+    fn duplicate() {
+        let pb = ProgressBar::new_range(5, 20)
+            .with_style(ProgressStyle::with_template("{prefix} {pos}/{len}").unwrap())
+            .with_prefix("worker");
+        pb.inc(10);
+        pb.inc_failures(1);
+
+        let dup = pb.duplicate();
+        assert_eq!(dup.position(), 5);
+        assert_eq!(dup.length(), Some(20));
+        assert_eq!(dup.prefix(), "worker");
+        assert_eq!(dup.failures(), 0);
+        assert_eq!(dup.state().state.session_pos(), 0);
+
+        dup.inc(3);
+        assert_eq!(dup.position(), 8);
+        assert_eq!(pb.position(), 15);
+
+        let mut lines = Vec::new();
+        let state = dup.state();
+        state.style.format_state(&state.state, &mut lines, 80);
+        assert_eq!(lines[0].as_ref(), "worker 8/20");
+    }
+
+    #[test]
+    fn eta_override() {
+        let pb = ProgressBar::new_spinner();
+        assert_eq!(pb.eta(), Duration::ZERO);
+
+        pb.set_eta(Duration::from_secs(3600));
+        let eta = pb.eta();
+        assert!(eta <= Duration::from_secs(3600) && eta > Duration::from_secs(3590));
+        assert!(pb.duration() >= eta);
+
+        pb.clear_eta();
+        assert_eq!(pb.eta(), Duration::ZERO);
+
+        pb.set_eta(Duration::from_secs(60));
+        pb.finish();
+        assert_eq!(pb.eta(), Duration::ZERO);
+    }
+
+    #[test]
+    fn external_eta_goes_stale() {
+        let pb = ProgressBar::hidden();
+        pb.set_length(100);
+        pb.set_external_eta(Duration::from_secs(3600), Duration::from_secs(600));
+        assert!(pb.eta() > Duration::from_secs(3590));
+
+        // Pretend the ETA was reported long ago
+        pb.state().state.eta_override.as_mut().unwrap().set_at -= Duration::from_secs(601);
+        // No progress has been made, so the internal estimate is empty
+        assert_eq!(pb.eta(), Duration::ZERO);
+    }
+
+    #[test]
+    fn position_from_fraction() {
+        let pb = ProgressBar::hidden();
+        pb.set_position_from_fraction(0.25);
+        assert_eq!(pb.length(), Some(10_000));
+        assert_eq!(pb.position(), 2_500);
+
+        pb.set_length(u64::MAX / 2);
+        pb.set_position_from_fraction(1.5);
+        assert_eq!(pb.position(), u64::MAX / 2);
+        pb.set_position_from_fraction(-1.0);
+        assert_eq!(pb.position(), 0);
+        pb.set_position_from_fraction(f32::NAN);
+        assert_eq!(pb.position(), 0);
+
+        let pb = ProgressBar::new(3);
+        pb.set_position_from_fraction(0.5);
+        assert_eq!(pb.position(), 2);
+
+        let pb = ProgressBar::new_range(100, 200);
+        pb.set_position_from_fraction(0.3);
+        assert_eq!(pb.position(), 130);
+        assert_eq!(pb.state().state.fraction(), 0.3);
+    }
+
+    #[test]
+    fn hidden_snapshot() {
+        let pb = ProgressBar::hidden().with_message("copying");
+        pb.set_length(100);
+        thread::sleep(Duration::from_millis(50));
+        // Most of these updates are not drawn due to rate limiting
+        for _ in 0..50 {
+            pb.inc(1);
+        }
+
+        let snapshot = pb.snapshot();
+        assert_eq!(snapshot.pos, 50);
+        assert_eq!(snapshot.len, Some(100));
+        assert_eq!(snapshot.fraction, 0.5);
+        assert_eq!(snapshot.message, "copying");
+        assert!(snapshot.per_sec > 0.0);
+        assert!(snapshot.eta > Duration::ZERO);
+        assert!(snapshot.elapsed >= Duration::from_millis(50));
+        assert_eq!(snapshot.finish_reason, None);
+
+        pb.finish();
+        let snapshot = pb.snapshot();
+        assert_eq!(snapshot.pos, 100);
+        assert_eq!(snapshot.eta, Duration::ZERO);
+        assert_eq!(snapshot.finish_reason, Some(FinishReason::Finished));
+        assert!(snapshot.is_finished());
+    }
+
+    #[test]
+    fn catch_panic() {
+        let pb = ProgressBar::hidden();
+        assert_eq!(pb.catch_panic(|| 42), 42);
+        assert_eq!(pb.finish_reason(), None);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            pb.catch_panic(|| panic!("worker {} crashed", 3));
+        }));
+        assert!(result.is_err());
+        assert_eq!(pb.finish_reason(), Some(FinishReason::Failed));
+        assert_eq!(pb.message(), "worker 3 crashed");
+    }
+
+    #[allow(clippy::float_cmp)]
+    #[test]
+    fn range() {
+        let pb = ProgressBar::new_range(300, 400);
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+        assert_eq!(pb.position(), 300);
+        assert_eq!(pb.state().state.fraction(), 0.0);
+
+        pb.inc(25);
+        assert_eq!(pb.position(), 325);
+        assert_eq!(pb.state().state.fraction(), 0.25);
+
+        pb.set_position(400);
+        assert_eq!(pb.state().state.fraction(), 1.0);
+
+        pb.reset();
+        assert_eq!(pb.position(), 300);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot_serde() {
+        let pb = ProgressBar::hidden().with_message("copying");
+        pb.set_length(10);
+        pb.inc(3);
+        pb.abandon();
+        // No time elapsed, so there is no rate
+        pb.state().state.started = Instant::now() + Duration::from_secs(3600);
+
+        let snapshot = pb.snapshot();
+        assert_eq!(snapshot.per_sec, 0.0);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains(r#""message":"copying""#));
+        assert!(json.contains(r#""finish_reason":"Abandoned""#));
+        let parsed: crate::ProgressSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn session() {
+        let pb = ProgressBar::new_range(300, 400);
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+        pb.inc(25);
+        assert_eq!(pb.session_position(), 25);
+
+        pb.reset_session();
+        pb.inc(5);
+        assert_eq!(pb.position(), 330);
+        assert_eq!(pb.session_position(), 5);
+
+        pb.reset();
+        assert_eq!(pb.session_position(), 0);
+    }
+
+    #[test]
+    fn finish_reason() {
+        let pb = ProgressBar::hidden();
+        assert_eq!(pb.finish_reason(), None);
+        pb.finish();
+        assert_eq!(pb.finish_reason(), Some(FinishReason::Finished));
+
+        pb.reset();
+        assert_eq!(pb.finish_reason(), None);
+        pb.abandon();
+        assert_eq!(pb.finish_reason(), Some(FinishReason::Abandoned));
+
+        pb.reset();
+        pb.fail_with_message("boom");
+        assert_eq!(pb.finish_reason(), Some(FinishReason::Failed));
+    }
+
+    #[test]
+    fn retry() {
+        let pb = ProgressBar::hidden();
+        pb.set_message("download");
+        let mut calls = 0;
+        let result = pb.retry(3, Duration::ZERO, || {
+            calls += 1;
+            pb.inc(10);
+            match calls {
+                3 => Ok(calls),
+                _ => Err("timeout"),
+            }
+        });
+        assert_eq!(result, Ok(3));
+        assert_eq!(pb.position(), 10);
+        assert_eq!(pb.message(), "download");
+        assert_eq!(pb.finish_reason(), Some(FinishReason::Finished));
+
+        let pb = ProgressBar::hidden().with_message("upload");
+        let mut messages = Vec::new();
+        let result = pb.retry(2, Duration::ZERO, || {
+            messages.push(pb.message());
+            Err::<(), _>("timeout")
+        });
+        assert_eq!(result, Err("timeout"));
+        assert_eq!(messages, ["upload", "upload (attempt 2/2)"]);
+        assert_eq!(pb.message(), "upload");
+        assert_eq!(pb.finish_reason(), Some(FinishReason::Failed));
+    }
+
+    #[test]
+    fn message_queue() {
+        let pb = ProgressBar::hidden();
+        let start = Instant::now();
+        let min_display = Duration::from_millis(100);
+        let mut state = pb.state();
+
+        state.enqueue_message(start, "a".into(), min_display);
+        state.enqueue_message(start, "b".into(), min_display);
+        state.enqueue_message(start, "c".into(), min_display);
+        assert_eq!(state.state.message.expanded(), "a");
+
+        state.tick(start + Duration::from_millis(50));
+        assert_eq!(state.state.message.expanded(), "a");
+
+        state.tick(start + Duration::from_millis(100));
+        assert_eq!(state.state.message.expanded(), "b");
+
+        state.tick(start + Duration::from_millis(250));
+        assert_eq!(state.state.message.expanded(), "c");
+
+        // The queue is empty and "c" was shown long enough, so new messages show immediately
+        state.tick(start + Duration::from_millis(400));
+        state.enqueue_message(start + Duration::from_millis(400), "d".into(), min_display);
+        assert_eq!(state.state.message.expanded(), "d");
+
+        // Setting the message directly discards the queue
+        state.enqueue_message(start + Duration::from_millis(410), "e".into(), min_display);
+        state.set_message(start + Duration::from_millis(420), "f".into());
+        state.tick(start + Duration::from_millis(600));
+        assert_eq!(state.state.message.expanded(), "f");
+
+        // Messages arriving too fast drop the oldest queued ones
+        let now = start + Duration::from_millis(700);
+        for i in 0..=MAX_QUEUED_MESSAGES {
+            state.enqueue_message(now, i.to_string().into(), min_display);
+        }
+        assert_eq!(state.state.message.expanded(), "0");
+        assert_eq!(state.message_queue.len(), MAX_QUEUED_MESSAGES);
+        state.tick(now + min_display);
+        assert_eq!(state.state.message.expanded(), "1");
+
+        for i in 0..MAX_QUEUED_MESSAGES {
+            state.enqueue_message(now, format!("x{i}").into(), min_display);
+        }
+        state.tick(now + 2 * min_display);
+        assert_eq!(state.state.message.expanded(), "x0");
+    }
 
     #[allow(clippy::float_cmp)]
     #[test]
@@ -806,6 +1998,64 @@ mod tests {
         assert_eq!(writer.it, bytes);
     }
 
+    #[test]
+    fn prompt_pauses_the_bar() {
+        let pb = ProgressBar::hidden();
+        let answer = pb.prompt(|| {
+            thread::sleep(Duration::from_millis(300));
+            42
+        });
+        assert_eq!(answer, 42);
+        assert!(pb.elapsed() < Duration::from_millis(250));
+        assert!(!pb.is_paused());
+
+        // A bar paused by the caller stays paused
+        pb.pause();
+        pb.prompt(|| {});
+        assert!(pb.is_paused());
+    }
+
+    #[test]
+    fn timer_tracks_elapsed_time() {
+        let _guard = TICKER_TEST.lock().unwrap();
+
+        let pb = ProgressBar::new_timer(Duration::from_secs(10));
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+        assert_eq!(pb.length(), Some(10_000));
+
+        pb.state().state.started = Instant::now() - Duration::from_secs(4);
+        pb.state().tick(Instant::now());
+        assert!((4_000..10_000).contains(&pb.position()));
+        assert!(pb.eta() <= Duration::from_secs(6));
+        assert!(!pb.is_finished());
+
+        pb.set_total_duration(Duration::from_secs(2));
+        assert_eq!(pb.length(), Some(2_000));
+        assert_eq!(pb.position(), 2_000);
+        assert_eq!(pb.eta(), Duration::ZERO);
+        assert!(pb.is_finished());
+    }
+
+    #[test]
+    fn timer_finishes_at_deadline() {
+        let _guard = TICKER_TEST.lock().unwrap();
+
+        let pb = ProgressBar::new_timer(Duration::from_secs(10));
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+        pb.state().state.started = Instant::now() - Duration::from_secs(11);
+        pb.state().tick(Instant::now());
+        assert!(pb.is_finished());
+        assert_eq!(pb.position(), 10_000);
+    }
+
+    #[test]
+    fn set_total_duration_ignores_non_timers() {
+        let pb = ProgressBar::hidden();
+        pb.set_total_duration(Duration::from_secs(1));
+        assert_eq!(pb.length(), None);
+        assert_eq!(pb.position(), 0);
+    }
+
     #[test]
     fn ticker_thread_terminates_on_drop() {
         let _guard = TICKER_TEST.lock().unwrap();
@@ -823,6 +2073,54 @@ mod tests {
         assert!(!TICKER_RUNNING.load(Ordering::SeqCst));
     }
 
+    fn wait_for_ticks(pb: &ProgressBar, ticks: u64) {
+        let start = Instant::now();
+        while pb.state().state.tick() < ticks {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn set_steady_tick() {
+        let _guard = TICKER_TEST.lock().unwrap();
+
+        let pb = ProgressBar::new_spinner();
+        assert!(!pb.is_steady_tick_enabled());
+        pb.set_steady_tick(Duration::from_secs(3600));
+        assert!(pb.is_steady_tick_enabled());
+
+        // The first tick happens right away, the next one only after the interval
+        thread::sleep(Duration::from_millis(100));
+        let ticks = pb.state().state.tick();
+        assert_eq!(ticks, 1);
+
+        // Setting the same interval again also wakes the ticker thread
+        pb.set_steady_tick(Duration::from_secs(3600));
+        wait_for_ticks(&pb, 2);
+
+        // Changing the interval wakes the ticker thread
+        pb.set_steady_tick(Duration::from_millis(10));
+        wait_for_ticks(&pb, 7);
+
+        // The ticker thread exits when the bar is finished, and is restarted after a reset
+        pb.finish();
+        let start = Instant::now();
+        while pb.is_steady_tick_enabled() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(5));
+        }
+        pb.reset();
+        let ticks = pb.state().state.tick();
+        pb.set_steady_tick(Duration::from_millis(10));
+        assert!(pb.is_steady_tick_enabled());
+        wait_for_ticks(&pb, ticks + 5);
+
+        pb.set_steady_tick(Duration::ZERO);
+        assert!(!pb.is_steady_tick_enabled());
+        assert!(!TICKER_RUNNING.load(Ordering::SeqCst));
+    }
+
     #[test]
     fn ticker_thread_terminates_on_drop_2() {
         let _guard = TICKER_TEST.lock().unwrap();
@@ -0,0 +1,133 @@
+//! Rate limiting
+
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// A token bucket limiting how often something happens, allowing occasional bursts
+///
+/// This is the limiter used for the refresh rate of draw targets. It is useful for anything
+/// else that should not happen too often, like logging status lines or polling an API.
+///
+/// ```rust
+/// # use std::time::Instant;
+/// # use indicatif::rate::RateLimiter;
+/// let mut limiter = RateLimiter::with_burst(10, 1);
+/// let now = Instant::now();
+/// assert!(limiter.allow(now));
+/// assert!(!limiter.allow(now));
+/// ```
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: u16, // in milliseconds
+    capacity: u8,
+    burst: u8,
+    prev: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing `rate` events per second, with bursts of up to 20 events
+    ///
+    /// Will panic if `rate` is `0`.
+    pub fn new(rate: u8) -> Self {
+        Self::with_burst(rate, MAX_BURST)
+    }
+
+    /// Creates a limiter allowing `rate` events per second, with bursts of up to `burst` events
+    ///
+    /// Will panic if `rate` or `burst` is `0`.
+    pub fn with_burst(rate: u8, burst: u8) -> Self {
+        assert!(rate > 0, "rate must be at least 1");
+        assert!(burst > 0, "burst must be at least 1");
+        Self {
+            interval: 1000 / (rate as u16), // between 3 and 1000 milliseconds
+            capacity: burst,
+            burst,
+            prev: Instant::now(),
+        }
+    }
+
+    /// Returns whether an event at `now` is allowed, consuming capacity if it is
+    pub fn allow(&mut self, now: Instant) -> bool {
+        if now < self.prev {
+            return false;
+        }
+
+        let elapsed = now - self.prev;
+        // If `capacity` is 0 and not enough time (`self.interval` ms) has passed since
+        // `self.prev` to add new capacity, return `false`. The goal of this method is to
+        // make this decision as efficient as possible.
+        if self.capacity == 0 && elapsed < Duration::from_millis(self.interval as u64) {
+            return false;
+        }
+
+        // We now calculate `new`, the number of ms, since we last returned `true`,
+        // and `remainder`, which represents a number of ns less than 1ms which we cannot
+        // convert into capacity now, so we're saving it for later.
+        let (new, remainder) = (
+            elapsed.as_millis() / self.interval as u128,
+            elapsed.as_nanos() % (self.interval as u128 * 1_000_000),
+        );
+
+        // We add `new` to `capacity`, subtract one for returning `true` from here,
+        // then make sure it does not exceed a maximum of `burst`.
+        self.capacity = Ord::min(self.burst as u128, (self.capacity as u128) + new - 1) as u8;
+        // Store `prev` for the next iteration after subtracting the `remainder`.
+        // Just use `unwrap` here because it shouldn't be possible for this to underflow.
+        self.prev = now
+            .checked_sub(Duration::from_nanos(remainder as u64))
+            .unwrap();
+        true
+    }
+
+    /// Returns whether an event at `now` would be allowed, without consuming capacity
+    pub(crate) fn would_allow(&self, now: Instant) -> bool {
+        now >= self.prev
+            && (self.capacity > 0 || now - self.prev >= Duration::from_millis(self.interval as u64))
+    }
+}
+
+const MAX_BURST: u8 = 20;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_rate() {
+        let mut limiter = RateLimiter::with_burst(10, 3);
+        let start = limiter.prev;
+        for _ in 0..3 {
+            assert!(limiter.allow(start));
+        }
+        assert!(!limiter.allow(start));
+        assert!(!limiter.allow(start + Duration::from_millis(50)));
+        assert!(limiter.allow(start + Duration::from_millis(100)));
+        assert!(!limiter.allow(start + Duration::from_millis(150)));
+
+        // Capacity refills up to the burst size only, which is left after the event that
+        // refilled it
+        let later = start + Duration::from_secs(10);
+        for _ in 0..4 {
+            assert!(limiter.allow(later));
+        }
+        assert!(!limiter.allow(later));
+    }
+
+    #[test]
+    #[should_panic(expected = "rate must be at least 1")]
+    fn zero_rate() {
+        RateLimiter::new(0);
+    }
+
+    #[test]
+    fn earlier_than_previous() {
+        let mut limiter = RateLimiter::new(10);
+        let start = limiter.prev;
+        assert!(limiter.allow(start + Duration::from_secs(1)));
+        assert!(!limiter.allow(start));
+    }
+}
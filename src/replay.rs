@@ -0,0 +1,409 @@
+//! Replaying progress bar operations from an event file
+//!
+//! A [`Replay`] drives real progress bars in a [`MultiProgress`] from a list of timed
+//! operations. This is useful for demos, for testing how an application's output looks without
+//! running the actual work, and for reproducing rendering issues deterministically.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{MultiProgress, ProgressBar, ProgressDrawTarget};
+
+/// A sequence of timed progress bar operations
+///
+/// Replays are usually parsed from a plain-text file with one event per line. Each line has
+/// the time in seconds since the start of the replay, the name of a progress bar and an
+/// operation, separated by spaces. Empty lines and lines starting with `#` are ignored.
+///
+/// | Operation        | Effect                                             |
+/// |------------------|----------------------------------------------------|
+/// | `add [len]`      | adds a progress bar, with a length if one is given |
+/// | `inc <n>`        | [`ProgressBar::inc()`]                             |
+/// | `pos <n>`        | [`ProgressBar::set_position()`]                    |
+/// | `len <n>`        | [`ProgressBar::set_length()`]                      |
+/// | `msg <text>`     | [`ProgressBar::set_message()`]                     |
+/// | `prefix <text>`  | [`ProgressBar::set_prefix()`]                      |
+/// | `println <text>` | [`ProgressBar::println()`]                         |
+/// | `tick`           | [`ProgressBar::tick()`]                            |
+/// | `finish`         | [`ProgressBar::finish()`]                          |
+/// | `abandon`        | [`ProgressBar::abandon()`]                         |
+/// | `remove`         | [`MultiProgress::remove()`]                        |
+///
+/// Text arguments extend to the end of the line. Times must not decrease from one event to the
+/// next, and progress bars must be added before they are used.
+///
+/// ```rust
+/// # use indicatif::{MultiProgress, ProgressDrawTarget};
+/// # use indicatif::replay::Replay;
+/// let replay: Replay = "
+///     0.0 download add 100
+///     0.0 download msg fetching index
+///     0.5 download inc 60
+///     1.0 download pos 100
+///     1.0 download finish
+/// "
+/// .parse()
+/// .unwrap();
+///
+/// let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+/// let bars = replay.play_with_speed(&mp, f64::INFINITY);
+/// assert!(bars["download"].is_finished());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Replay {
+    events: Vec<ReplayEvent>,
+}
+
+impl Replay {
+    /// Creates an empty replay
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a replay in the text format described on [`Replay`]
+    ///
+    /// Parse errors are returned as [`io::ErrorKind::InvalidData`] errors wrapping a
+    /// [`ReplayError`].
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut parser = Parser::default();
+        for (idx, line) in reader.lines().enumerate() {
+            parser
+                .line(idx + 1, &line?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+        Ok(parser.replay)
+    }
+
+    /// Appends an operation on the progress bar `bar` at `at` after the start of the replay
+    ///
+    /// Text arguments of `op` must not contain newlines to be written with the [`Display`]
+    /// implementation.
+    ///
+    /// [`Display`]: fmt::Display
+    pub fn push(&mut self, at: Duration, bar: impl Into<String>, op: ReplayOp) {
+        self.events.push(ReplayEvent {
+            at,
+            bar: bar.into(),
+            op,
+        });
+    }
+
+    /// Returns the events of the replay
+    pub fn events(&self) -> &[ReplayEvent] {
+        &self.events
+    }
+
+    /// Plays the replay in real time, see [`Replay::play_with_speed()`]
+    pub fn play(&self, mp: &MultiProgress) -> HashMap<String, ProgressBar> {
+        self.play_with_speed(mp, 1.0)
+    }
+
+    /// Plays the replay against new progress bars in `mp`, blocking until it is done
+    ///
+    /// A `speed` of 2.0 plays the replay twice as fast as recorded; with an infinite `speed`,
+    /// all events are applied without waiting. Events that would happen too far in the future
+    /// to be represented (with a tiny `speed`) end the replay. Returns the progress bars that
+    /// were not removed, by name. Will panic if `speed` is not positive.
+    pub fn play_with_speed(&self, mp: &MultiProgress, speed: f64) -> HashMap<String, ProgressBar> {
+        assert!(speed > 0.0, "speed must be positive");
+        let start = Instant::now();
+        let mut bars = HashMap::new();
+        for event in &self.events {
+            let target = Duration::try_from_secs_f64(event.at.as_secs_f64() / speed)
+                .ok()
+                .and_then(|delay| start.checked_add(delay));
+            let Some(target) = target else {
+                break;
+            };
+            let now = Instant::now();
+            if target > now {
+                thread::sleep(target - now);
+            }
+
+            if let ReplayOp::Add(len) = event.op {
+                let pb = mp.add(ProgressBar::with_draw_target(
+                    len,
+                    ProgressDrawTarget::hidden(),
+                ));
+                bars.insert(event.bar.clone(), pb);
+                continue;
+            }
+
+            let Some(pb) = bars.get(&event.bar) else {
+                continue;
+            };
+            match &event.op {
+                ReplayOp::Add(_) => unreachable!(),
+                ReplayOp::Inc(delta) => pb.inc(*delta),
+                ReplayOp::Position(pos) => pb.set_position(*pos),
+                ReplayOp::Length(len) => pb.set_length(*len),
+                ReplayOp::Message(msg) => pb.set_message(msg.clone()),
+                ReplayOp::Prefix(prefix) => pb.set_prefix(prefix.clone()),
+                ReplayOp::Println(line) => pb.println(line),
+                ReplayOp::Tick => pb.tick(),
+                ReplayOp::Finish => pb.finish(),
+                ReplayOp::Abandon => pb.abandon(),
+                ReplayOp::Remove => {
+                    mp.remove(pb);
+                    bars.remove(&event.bar);
+                }
+            }
+        }
+        bars
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::default();
+        for (idx, line) in s.lines().enumerate() {
+            parser.line(idx + 1, line)?;
+        }
+        Ok(parser.replay)
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            write!(f, "{:.3} {} ", event.at.as_secs_f64(), event.bar)?;
+            match &event.op {
+                ReplayOp::Add(Some(len)) => writeln!(f, "add {len}")?,
+                ReplayOp::Add(None) => writeln!(f, "add")?,
+                ReplayOp::Inc(delta) => writeln!(f, "inc {delta}")?,
+                ReplayOp::Position(pos) => writeln!(f, "pos {pos}")?,
+                ReplayOp::Length(len) => writeln!(f, "len {len}")?,
+                ReplayOp::Message(msg) => writeln!(f, "msg {msg}")?,
+                ReplayOp::Prefix(prefix) => writeln!(f, "prefix {prefix}")?,
+                ReplayOp::Println(line) => writeln!(f, "println {line}")?,
+                ReplayOp::Tick => writeln!(f, "tick")?,
+                ReplayOp::Finish => writeln!(f, "finish")?,
+                ReplayOp::Abandon => writeln!(f, "abandon")?,
+                ReplayOp::Remove => writeln!(f, "remove")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single operation of a [`Replay`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReplayEvent {
+    /// The time since the start of the replay
+    pub at: Duration,
+    /// The name of the progress bar
+    pub bar: String,
+    /// The operation on the progress bar
+    pub op: ReplayOp,
+}
+
+/// An operation on a progress bar in a [`Replay`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReplayOp {
+    /// Adds the progress bar, with an optional length
+    Add(Option<u64>),
+    /// Advances the position
+    Inc(u64),
+    /// Sets the position
+    Position(u64),
+    /// Sets the length
+    Length(u64),
+    /// Sets the message
+    Message(String),
+    /// Sets the prefix
+    Prefix(String),
+    /// Prints a line above the progress bars
+    Println(String),
+    /// Redraws the progress bar
+    Tick,
+    /// Finishes the progress bar
+    Finish,
+    /// Abandons the progress bar
+    Abandon,
+    /// Removes the progress bar from the [`MultiProgress`]
+    Remove,
+}
+
+/// An error parsing a [`Replay`]
+#[derive(Debug)]
+pub struct ReplayError {
+    line: usize,
+    message: String,
+}
+
+impl ReplayError {
+    /// Returns the line number of the invalid event, starting at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ReplayError: {} on line {}", self.message, self.line)
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[derive(Default)]
+struct Parser {
+    replay: Replay,
+    bars: Vec<String>,
+}
+
+impl Parser {
+    fn line(&mut self, number: usize, line: &str) -> Result<(), ReplayError> {
+        let err = |message: String| ReplayError {
+            line: number,
+            message,
+        };
+
+        let line = line.trim_start().trim_end_matches(['\r', '\n']);
+        if line.trim_end().is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+
+        let mut parts = line.splitn(3, ' ');
+        let (time, bar, rest) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(time), Some(bar), Some(rest)) => (time, bar, rest),
+            _ => {
+                return Err(err(
+                    "expected a time, a progress bar and an operation".into()
+                ))
+            }
+        };
+        let (op, arg) = match rest.split_once(' ') {
+            Some((op, arg)) => (op, Some(arg)),
+            None => (rest.trim_end(), None),
+        };
+
+        let at = time
+            .parse::<f64>()
+            .ok()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .ok_or_else(|| err(format!("invalid time {time:?}")))?;
+        if matches!(self.replay.events.last(), Some(prev) if prev.at > at) {
+            return Err(err("time is earlier than the previous event".into()));
+        }
+
+        let number = |arg: Option<&str>| -> Result<u64, ReplayError> {
+            let arg = arg.map(str::trim).unwrap_or_default();
+            arg.parse()
+                .map_err(|_| err(format!("invalid number {arg:?} for {op:?}")))
+        };
+        let text = |arg: Option<&str>| arg.unwrap_or_default().to_owned();
+        let op = match op {
+            "add" => match arg.map(str::trim) {
+                None | Some("") => ReplayOp::Add(None),
+                arg => ReplayOp::Add(Some(number(arg)?)),
+            },
+            "inc" => ReplayOp::Inc(number(arg)?),
+            "pos" => ReplayOp::Position(number(arg)?),
+            "len" => ReplayOp::Length(number(arg)?),
+            "msg" => ReplayOp::Message(text(arg)),
+            "prefix" => ReplayOp::Prefix(text(arg)),
+            "println" => ReplayOp::Println(text(arg)),
+            "tick" => ReplayOp::Tick,
+            "finish" => ReplayOp::Finish,
+            "abandon" => ReplayOp::Abandon,
+            "remove" => ReplayOp::Remove,
+            op => return Err(err(format!("unknown operation {op:?}"))),
+        };
+
+        let known = self.bars.iter().any(|b| b == bar);
+        match (&op, known) {
+            (ReplayOp::Add(_), true) => return Err(err(format!("{bar:?} was already added"))),
+            (ReplayOp::Add(_), false) => self.bars.push(bar.to_owned()),
+            (_, false) => return Err(err(format!("{bar:?} was not added"))),
+            (ReplayOp::Remove, true) => self.bars.retain(|b| b != bar),
+            (_, true) => {}
+        }
+
+        self.replay.push(at, bar, op);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLAY: &str = "# two downloads
+0.000 a add 100
+0.000 a msg fetching index
+0.100 b add
+0.250 a inc 40
+0.250 b println done with b
+0.300 b remove
+0.500 a pos 100
+0.500 a finish
+";
+
+    #[test]
+    fn parse_and_display() {
+        let replay = REPLAY.parse::<Replay>().unwrap();
+        assert_eq!(replay.events().len(), 8);
+        assert_eq!(
+            replay.events()[1].op,
+            ReplayOp::Message("fetching index".into())
+        );
+        assert_eq!(replay.events()[2].at, Duration::from_millis(100));
+        assert_eq!(
+            replay.to_string(),
+            REPLAY.trim_start_matches("# two downloads\n")
+        );
+        assert_eq!(Replay::read(REPLAY.as_bytes()).unwrap(), replay);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let line = |s: &str| s.parse::<Replay>().unwrap_err().line();
+        assert_eq!(line("0 a add\n1 a jump"), 2);
+        assert_eq!(line("0 a add\n\n1 a inc x"), 3);
+        assert_eq!(line("0 a inc 1"), 1);
+        assert_eq!(line("0 a add\n0 a add"), 2);
+        assert_eq!(line("1 a add\n0.5 a tick"), 2);
+        assert_eq!(line("-1 a add"), 1);
+        assert_eq!(line("1e300 a add"), 1);
+        assert_eq!(line("0 a"), 1);
+        assert_eq!(line("0 a add\n0 a remove\n0 a tick"), 3);
+
+        let err = Replay::read("0 a nop".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn play() {
+        let replay = REPLAY.parse::<Replay>().unwrap();
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let bars = replay.play_with_speed(&mp, f64::INFINITY);
+
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars["a"].position(), 100);
+        assert_eq!(bars["a"].message(), "fetching index");
+        assert!(bars["a"].is_finished());
+        assert_eq!(mp.snapshot().len(), 1);
+    }
+
+    #[test]
+    fn play_far_future() {
+        let replay = "0 a add\n1 a finish".parse::<Replay>().unwrap();
+        let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let bars = replay.play_with_speed(&mp, 1e-300);
+        assert!(!bars["a"].is_finished());
+    }
+}
@@ -1,6 +1,9 @@
 use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
 use std::io;
-use std::sync::{Arc, OnceLock};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
 use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
@@ -10,7 +13,17 @@ use portable_atomic::{AtomicU64, AtomicU8, Ordering};
 use web_time::Instant;
 
 use crate::draw_target::{LineType, ProgressDrawTarget};
-use crate::style::ProgressStyle;
+use crate::format::{FormattedDuration, HumanDuration};
+#[cfg(feature = "ipc")]
+use crate::ipc::IpcEvent;
+use crate::multi::ProgressEvent;
+use crate::style::{EtaSmoothing, ProgressStyle, RateWindow};
+use crate::sync::IgnorePoison;
+
+/// The length used by [`ProgressBar::set_position_from_fraction`] if the length is unknown
+///
+/// [`ProgressBar::set_position_from_fraction`]: crate::ProgressBar::set_position_from_fraction
+pub(crate) const FRACTION_LENGTH: u64 = 10_000;
 
 pub(crate) struct BarState {
     pub(crate) draw_target: ProgressDrawTarget,
@@ -18,6 +31,54 @@ pub(crate) struct BarState {
     pub(crate) style: ProgressStyle,
     pub(crate) state: ProgressState,
     pub(crate) tab_width: usize,
+    /// Whether the style was set explicitly, rather than left at the library default
+    custom_style: bool,
+    /// Whether the tab width was set explicitly, rather than left at the library default
+    custom_tab_width: bool,
+    /// Child bars that are cleared when this bar finishes
+    pub(crate) children: Vec<Weak<Mutex<BarState>>>,
+    /// Messages waiting to be shown, with their minimum display time
+    pub(crate) message_queue: VecDeque<(Cow<'static, str>, Duration)>,
+    /// The earliest time the current message may be replaced by a queued one
+    pub(crate) message_until: Option<Instant>,
+    /// Minimum time between two redraws of this bar, unless forced
+    pub(crate) min_redraw_interval: Option<Duration>,
+    /// The last time this bar was drawn
+    last_redraw: Option<Instant>,
+    /// When `ProgressEvent::Updated` was last sent
+    last_update_event: Option<Instant>,
+    /// The last time a frame including this bar was written, see `ProgressBar::last_draw_time`
+    pub(crate) last_draw: Option<Instant>,
+    /// When the bar was first drawn with visible lines
+    first_draw: Option<Instant>,
+    /// How long the bar stays visible before `ProgressFinish::AndClear` clears it
+    pub(crate) min_visible: Option<Duration>,
+    /// When a deferred clear is due, see `ProgressBar::set_min_visible_duration`
+    pub(crate) pending_clear: Option<Instant>,
+    /// Nothing is drawn until the bar has been running this long, see
+    /// `ProgressBar::set_render_delay`
+    pub(crate) render_delay: Option<Duration>,
+    /// Whether to ring the terminal bell when the bar is finished
+    pub(crate) bell_on_finish: bool,
+    /// Whether to ring the terminal bell when the bar fails
+    pub(crate) bell_on_failure: bool,
+    /// Shows the percentage in the terminal window title, if enabled
+    pub(crate) title_percent: Option<TitlePercent>,
+    /// The group of the bar, used by `MultiProgress` to show, hide or remove groups of bars
+    pub(crate) tag: Option<Cow<'static, str>>,
+    /// The verbosity a `MultiProgress` needs to draw the bar
+    pub(crate) min_verbosity: i8,
+    /// The bar advanced when this bar finishes, see `ProgressBar::add_child()`
+    pub(crate) parent: Option<ParentLink>,
+    /// The number of ancestors created with `ProgressBar::add_child()`, indenting each line
+    pub(crate) depth: usize,
+}
+
+/// Links a child bar to the parent bar it advances when it finishes
+pub(crate) struct ParentLink {
+    pub(crate) state: Weak<Mutex<BarState>>,
+    /// The number of steps the parent advances by
+    pub(crate) weight: u64,
 }
 
 impl BarState {
@@ -32,13 +93,55 @@ impl BarState {
             style: ProgressStyle::default_bar(),
             state: ProgressState::new(len, pos),
             tab_width: DEFAULT_TAB_WIDTH,
+            custom_style: false,
+            custom_tab_width: false,
+            children: Vec::new(),
+            message_queue: VecDeque::new(),
+            message_until: None,
+            min_redraw_interval: None,
+            last_redraw: None,
+            last_update_event: None,
+            last_draw: None,
+            first_draw: None,
+            min_visible: None,
+            pending_clear: None,
+            render_delay: None,
+            bell_on_finish: false,
+            bell_on_failure: false,
+            title_percent: None,
+            tag: None,
+            min_verbosity: 0,
+            parent: None,
+            depth: 0,
         }
     }
 
     /// Finishes the progress bar using the [`ProgressFinish`] behavior stored
     /// in the [`ProgressStyle`].
     pub(crate) fn finish_using_style(&mut self, now: Instant, finish: ProgressFinish) {
+        let reason = match finish {
+            ProgressFinish::AndLeave
+            | ProgressFinish::WithMessage(_)
+            | ProgressFinish::AndClear => FinishReason::Finished,
+            ProgressFinish::Abandon | ProgressFinish::AbandonWithMessage(_) => {
+                FinishReason::Abandoned
+            }
+        };
+        self.finish_with_reason(now, finish, reason);
+    }
+
+    /// Finishes the progress bar like [`BarState::finish_using_style`], recording `reason`
+    pub(crate) fn finish_with_reason(
+        &mut self,
+        now: Instant,
+        finish: ProgressFinish,
+        reason: FinishReason,
+    ) {
+        let was_finished = self.state.is_finished();
         self.state.status = Status::DoneVisible;
+        self.state.finish_reason = Some(reason);
+        self.message_queue.clear();
+        self.message_until = None;
         match finish {
             ProgressFinish::AndLeave => {
                 if let Some(len) = self.state.len {
@@ -55,7 +158,16 @@ impl BarState {
                 if let Some(len) = self.state.len {
                     self.state.pos.set(len);
                 }
-                self.state.status = Status::DoneHidden;
+                // Keep the bar until it was visible for long enough, unless it was never drawn
+                let deadline = self
+                    .first_draw
+                    .zip(self.min_visible)
+                    .map(|(first, min)| first + min)
+                    .filter(|&deadline| deadline > now);
+                match deadline {
+                    Some(deadline) => self.pending_clear = Some(deadline),
+                    None => self.state.status = Status::DoneHidden,
+                }
             }
             ProgressFinish::Abandon => {}
             ProgressFinish::AbandonWithMessage(msg) => {
@@ -63,32 +175,176 @@ impl BarState {
             }
         }
 
+        if let Some((multi, idx)) = self.draw_target.remote() {
+            multi.write().ignore_poison().set_finished(idx, true);
+        }
+
         // There's no need to update the estimate here; once the `status` is no longer
         // `InProgress`, we will use the length and elapsed time to estimate.
         let _ = self.draw(true, now);
+
+        let bell = match reason {
+            FinishReason::Finished => self.bell_on_finish,
+            FinishReason::Failed => self.bell_on_failure,
+            FinishReason::Abandoned | FinishReason::Dropped => false,
+        };
+        if bell && !was_finished {
+            let _ = self.draw_target.bell();
+        }
+
+        if let Some((multi, idx)) = self.draw_target.remote() {
+            let snapshot = ProgressSnapshot::new(&self.state);
+            let mut multi = multi.write().ignore_poison();
+            multi.record_final(idx, snapshot);
+            if !was_finished {
+                multi.notify(|| ProgressEvent::Finished {
+                    index: idx,
+                    progress: IpcProgress::from_state(&self.state),
+                });
+            }
+            let auto_remove = multi.is_auto_remove(idx);
+            drop(multi);
+            if auto_remove {
+                self.move_to_scrollback(now);
+            }
+        }
+
+        for child in self.children.drain(..) {
+            if let Some(child) = child.upgrade() {
+                let mut child = child.lock().ignore_poison();
+                if !child.state.is_finished() {
+                    child.finish_using_style(now, ProgressFinish::AndClear);
+                    child.clear_pending(now);
+                }
+            }
+        }
     }
 
-    pub(crate) fn reset(&mut self, now: Instant, mode: Reset) {
-        // Always reset the estimator; this is the only reset that will occur if mode is
-        // `Reset::Eta`.
-        self.state.est.reset(now);
+    /// Finishes the progress bar like [`ProgressFinish::AndLeave`] and moves it out of the
+    /// `MultiProgress` it is drawn in, see `ProgressBar::finish_into_scrollback()`
+    pub(crate) fn finish_into_scrollback(&mut self, now: Instant) {
+        self.finish_with_reason(now, ProgressFinish::AndLeave, FinishReason::Finished);
+        self.move_to_scrollback(now);
+    }
+
+    /// Prints the last lines of the bar above the other members of its `MultiProgress` and
+    /// removes it from there
+    fn move_to_scrollback(&mut self, now: Instant) {
+        let Some((multi, idx)) = self.draw_target.remote() else {
+            return;
+        };
+        let _ = multi.write().ignore_poison().move_to_scrollback(idx, now);
+        self.draw_target = ProgressDrawTarget::hidden();
+    }
+
+    /// Whether the bar is not drawn yet because of `ProgressBar::set_render_delay`
+    pub(crate) fn is_render_delayed(&self, now: Instant) -> bool {
+        self.first_draw.is_none()
+            && self
+                .render_delay
+                .is_some_and(|delay| now.saturating_duration_since(self.state.started) < delay)
+    }
 
+    /// Whether updating the bar at `now` would draw a frame
+    ///
+    /// This doesn't consume capacity of the rate limiter, so it can be used to skip preparing
+    /// state that is only needed for drawing.
+    pub(crate) fn would_draw(&self, now: Instant) -> bool {
+        if self.state.is_finished() {
+            return true;
+        }
+        let recent = self
+            .last_redraw
+            .zip(self.min_redraw_interval)
+            .is_some_and(|(last, interval)| now.saturating_duration_since(last) < interval);
+        !recent && !self.is_render_delayed(now) && self.draw_target.would_draw(now)
+    }
+
+    /// Clears the bar if it was kept visible by `ProgressBar::set_min_visible_duration`
+    pub(crate) fn clear_pending(&mut self, now: Instant) {
+        if self.pending_clear.take().is_some() && self.state.is_finished() {
+            self.state.status = Status::DoneHidden;
+            let _ = self.draw(true, now);
+        }
+    }
+
+    pub(crate) fn reset(&mut self, now: Instant, mode: Reset) {
         if let Reset::Elapsed | Reset::All = mode {
             self.state.started = now;
+            self.state.paused = Duration::ZERO;
+            if self.state.paused_at.is_some() {
+                self.state.paused_at = Some(now);
+            }
         }
 
+        // Always reset the estimator; this is the only reset that will occur if mode is
+        // `Reset::Eta`.
+        self.state.est.reset(self.state.active_now(now));
+
         if let Reset::All = mode {
             self.state.pos.reset(now);
+            self.state.pos.set(self.state.start);
+            self.state.session_start = self.state.start;
+            self.state.failures = 0;
+            self.state.eta_override = None;
             self.state.status = Status::InProgress;
+            self.state.finish_reason = None;
+            self.first_draw = None;
+            self.pending_clear = None;
 
             for tracker in self.style.format_map.values_mut() {
                 tracker.reset(&self.state, now);
             }
 
+            if let Some(item_times) = &mut self.style.item_times {
+                item_times.reset(now);
+            }
+            self.style.smoothed_rate.reset(now);
+
+            if let Some((multi, idx)) = self.draw_target.remote() {
+                multi.write().ignore_poison().set_finished(idx, false);
+            }
             let _ = self.draw(false, now);
         }
     }
 
+    /// Stops the clock of the bar, see `ProgressBar::pause`
+    pub(crate) fn pause(&mut self, now: Instant) {
+        if self.stop_clock(now) {
+            let _ = self.draw(true, now);
+        }
+    }
+
+    /// Restarts the clock of the bar, excluding the paused time from the estimates
+    pub(crate) fn resume(&mut self, now: Instant) {
+        if self.restart_clock(now) {
+            let _ = self.draw(true, now);
+        }
+    }
+
+    /// Pauses the bar without drawing it, returns `false` if it is paused or finished already
+    fn stop_clock(&mut self, now: Instant) -> bool {
+        if self.state.paused_at.is_some() || self.state.is_finished() {
+            return false;
+        }
+
+        self.update_estimate(now);
+        self.state.paused_at = Some(now);
+        true
+    }
+
+    /// Resumes the bar without drawing it, returns `false` if it was not paused
+    fn restart_clock(&mut self, now: Instant) -> bool {
+        let Some(paused_at) = self.state.paused_at.take() else {
+            return false;
+        };
+
+        let paused = now.saturating_duration_since(paused_at);
+        self.state.paused += paused;
+        self.state.est.exclude(paused);
+        true
+    }
+
     pub(crate) fn update(&mut self, now: Instant, f: impl FnOnce(&mut ProgressState), tick: bool) {
         f(&mut self.state);
         if tick {
@@ -120,41 +376,304 @@ impl BarState {
         self.update_estimate_and_draw(now);
     }
 
+    /// Decreases the position, keeping the rate estimate (see `Estimator::rewind`)
+    pub(crate) fn dec(&mut self, delta: u64) {
+        let delta = self.state.pos.dec(delta);
+        self.state.est.rewind(delta);
+    }
+
     pub(crate) fn set_tab_width(&mut self, tab_width: usize) {
+        self.custom_tab_width = true;
+        self.apply_tab_width(tab_width);
+    }
+
+    fn apply_tab_width(&mut self, tab_width: usize) {
         self.tab_width = tab_width;
+        self.state.tab_width = tab_width;
         self.state.message.set_tab_width(tab_width);
         self.state.prefix.set_tab_width(tab_width);
         self.style.set_tab_width(tab_width);
     }
 
-    pub(crate) fn set_style(&mut self, style: ProgressStyle) {
+    pub(crate) fn set_style(&mut self, now: Instant, style: ProgressStyle) {
+        self.custom_style = true;
         self.style = style;
         self.style.set_tab_width(self.tab_width);
+        self.state.est.set_smoothing(self.style.eta_smoothing);
+        self.state.est.set_rate_window(self.style.rate_window);
+
+        // The new template may have a different layout than what is on screen, so clear and
+        // redraw everything right away instead of patching the old frame on the next tick.
+        if self.last_redraw.is_some() {
+            self.draw_target.invalidate();
+            let _ = self.draw(true, now);
+        }
+    }
+
+    /// Advances the bar by `weight` because one of its children finished
+    pub(crate) fn advance_from_child(&mut self, now: Instant, weight: u64) {
+        if self.state.is_finished() {
+            return;
+        }
+
+        self.state.pos.inc(weight);
+        self.update_estimate_and_draw(now);
+    }
+
+    /// The index of the last member below this bar in its `MultiProgress` that belongs to it
+    ///
+    /// `idx` is the index of this bar. Children are locked while this bar is locked, like when
+    /// finishing.
+    pub(crate) fn last_descendant_index(&self, idx: usize) -> usize {
+        self.children
+            .iter()
+            .rev()
+            .filter_map(Weak::upgrade)
+            .find_map(|child| {
+                let child = child.lock().ignore_poison();
+                let (_, child_idx) = child.draw_target.remote()?;
+                Some(child.last_descendant_index(child_idx))
+            })
+            .unwrap_or(idx)
+    }
+
+    /// Creates a state with the same configuration, but a fresh position and timing
+    pub(crate) fn duplicate(
+        &self,
+        now: Instant,
+        draw_target: ProgressDrawTarget,
+        pos: Arc<AtomicPosition>,
+    ) -> Self {
+        let mut new = Self::new(self.state.len, draw_target, pos);
+        new.on_finish = self.on_finish.clone();
+        new.style = self.style.clone();
+        new.tab_width = self.tab_width;
+        new.custom_style = self.custom_style;
+        new.custom_tab_width = self.custom_tab_width;
+        new.min_redraw_interval = self.min_redraw_interval;
+        new.min_visible = self.min_visible;
+        new.render_delay = self.render_delay;
+        new.bell_on_finish = self.bell_on_finish;
+        new.bell_on_failure = self.bell_on_failure;
+        new.title_percent = self
+            .title_percent
+            .as_ref()
+            .map(|title| TitlePercent::new(title.focused.clone()));
+        new.tag = self.tag.clone();
+        new.min_verbosity = self.min_verbosity;
+
+        new.state.set_range_start(self.state.start);
+        new.state.message = self.state.message.clone();
+        new.state.prefix = self.state.prefix.clone();
+        new.state.est.set_smoothing(new.style.eta_smoothing);
+        new.state.est.set_rate_window(new.style.rate_window);
+
+        // The style carries per-bar statistics, which must not be shared with this bar
+        for tracker in new.style.format_map.values_mut() {
+            tracker.reset(&new.state, now);
+        }
+        if let Some(item_times) = &mut new.style.item_times {
+            item_times.reset(now);
+        }
+        new.style.smoothed_rate.reset(now);
+        new
+    }
+
+    /// Applies the defaults of a `MultiProgress` to settings that were not set explicitly
+    pub(crate) fn inherit_defaults(
+        &mut self,
+        style: Option<&ProgressStyle>,
+        tab_width: Option<usize>,
+    ) {
+        if let (Some(tab_width), false) = (tab_width, self.custom_tab_width) {
+            self.apply_tab_width(tab_width);
+        }
+        if let (Some(style), false) = (style, self.custom_style) {
+            self.style = style.clone();
+            self.style.set_tab_width(self.tab_width);
+            self.state.est.set_smoothing(self.style.eta_smoothing);
+            self.state.est.set_rate_window(self.style.rate_window);
+        }
+    }
+
+    pub(crate) fn set_total_duration(&mut self, now: Instant, total: Duration) {
+        if !self.state.timer {
+            return;
+        }
+        self.state.len = Some(duration_to_millis(total));
+        self.tick(now);
+    }
+
+    pub(crate) fn set_message(&mut self, now: Instant, msg: Cow<'static, str>) {
+        self.message_queue.clear();
+        self.message_until = None;
+        self.state.message = TabExpandedString::new(msg, self.tab_width);
+        self.update_estimate_and_draw(now);
+    }
+
+    pub(crate) fn enqueue_message(
+        &mut self,
+        now: Instant,
+        msg: Cow<'static, str>,
+        min_display: Duration,
+    ) {
+        // Drop the oldest messages rather than falling further and further behind
+        if self.message_queue.len() >= MAX_QUEUED_MESSAGES {
+            self.message_queue.pop_front();
+        }
+        self.message_queue.push_back((msg, min_display));
+        if self.advance_message_queue(now) {
+            self.update_estimate_and_draw(now);
+        }
+    }
+
+    /// Shows the next queued message if the current one was displayed long enough
+    ///
+    /// Returns `true` if the message was changed.
+    fn advance_message_queue(&mut self, now: Instant) -> bool {
+        if matches!(self.message_until, Some(until) if now < until) {
+            return false;
+        }
+
+        match self.message_queue.pop_front() {
+            Some((msg, min_display)) => {
+                self.state.message = TabExpandedString::new(msg, self.tab_width);
+                self.message_until = Some(now + min_display);
+                true
+            }
+            None => {
+                self.message_until = None;
+                false
+            }
+        }
     }
 
     pub(crate) fn tick(&mut self, now: Instant) {
-        self.state.tick = self.state.tick.saturating_add(1);
+        if self.advance_tick(now) {
+            return;
+        }
         self.update_estimate_and_draw(now);
     }
 
+    /// Tick from the steady ticker, which may be coalesced with other draws of a `MultiProgress`
+    pub(crate) fn steady_tick(&mut self, now: Instant) {
+        if self.advance_tick(now) {
+            return;
+        }
+        self.update_estimate(now);
+        let _ = self.draw_inner(false, true, now);
+    }
+
+    /// Returns `true` if a timer reached its total duration and was finished (and drawn)
+    fn advance_tick(&mut self, now: Instant) -> bool {
+        self.state.tick = self.state.tick.saturating_add(1);
+        self.advance_message_queue(now);
+        if !self.state.timer || self.state.is_finished() {
+            return false;
+        }
+
+        let elapsed = duration_to_millis(self.state.elapsed_at(now));
+        match self.state.len {
+            Some(len) if elapsed >= len => {
+                self.finish_using_style(now, self.on_finish.clone());
+                true
+            }
+            _ => {
+                self.state.pos.set(elapsed);
+                false
+            }
+        }
+    }
+
     pub(crate) fn update_estimate_and_draw(&mut self, now: Instant) {
+        self.update_estimate(now);
+        let _ = self.draw(false, now);
+    }
+
+    fn update_estimate(&mut self, now: Instant) {
+        // Progress made while paused is recorded with the first update after resuming
+        if self.state.paused_at.is_some() {
+            return;
+        }
+
         let pos = self.state.pos.pos.load(Ordering::Relaxed);
         self.state.est.record(pos, now);
+        self.state.update_trend(now);
+
+        if let Some(item_times) = &mut self.style.item_times {
+            item_times.record(pos, now);
+        }
+        self.style.smoothed_rate.record(pos, now);
 
         for tracker in self.style.format_map.values_mut() {
-            tracker.tick(&self.state, now);
+            tracker.update(&mut self.state, now);
         }
+    }
 
-        let _ = self.draw(false, now);
+    /// Shows the current percentage in the window title while the terminal is unfocused
+    fn update_title(&mut self) {
+        let Some(title) = &mut self.title_percent else {
+            return;
+        };
+
+        let focused = title
+            .focused
+            .as_ref()
+            .is_some_and(|focused| focused.load(Ordering::Relaxed));
+        let percent = match (focused, self.state.len) {
+            (false, Some(_)) if !self.state.is_finished() => {
+                Some((self.state.fraction() * 100.0) as u64)
+            }
+            _ => None,
+        };
+        if percent == title.shown {
+            return;
+        }
+
+        // The title of the user is saved before it is first replaced, and restored once the
+        // percentage is no longer shown
+        if title.shown.is_none() {
+            let _ = self.draw_target.push_title();
+        }
+        title.shown = percent;
+        let _ = match percent {
+            Some(percent) => self.draw_target.set_title(&format!("{percent}%")),
+            None => self.draw_target.pop_title(),
+        };
     }
 
-    pub(crate) fn println(&mut self, now: Instant, msg: &str) {
+    /// Take a snapshot of the statistics, including updates not recorded due to rate limiting
+    pub(crate) fn snapshot(&mut self, now: Instant) -> ProgressSnapshot {
+        if !self.state.is_finished() && self.state.paused_at.is_none() {
+            let pos = self.state.pos.pos.load(Ordering::Relaxed);
+            self.state.est.record(pos, now);
+        }
+        ProgressSnapshot::new(&self.state)
+    }
+
+    /// Prints `msg` above the progress bar, formatting it only if it is actually printed
+    pub(crate) fn println(&mut self, now: Instant, msg: impl fmt::Display) {
+        #[cfg(feature = "ipc")]
+        {
+            let event = || IpcEvent::Println {
+                id: self.state.id.as_deref().map(Into::into),
+                line: msg.to_string(),
+            };
+            if self.draw_target.send_ipc(true, now, event).is_some() {
+                return;
+            }
+        }
+        if self.draw_target.println_plain(|| msg.to_string()).is_some() {
+            return;
+        }
+
         let width = self.draw_target.width();
         let mut drawable = match self.draw_target.drawable(true, now) {
             Some(drawable) => drawable,
             None => return,
         };
 
+        let msg = msg.to_string();
         let mut draw_state = drawable.state();
         let lines: Vec<LineType> = msg.lines().map(|l| LineType::Text(Into::into(l))).collect();
         // Empty msg should trigger newline as we are in println
@@ -165,19 +684,39 @@ impl BarState {
         }
 
         if let Some(width) = width {
-            if !matches!(self.state.status, Status::DoneHidden) {
-                self.style
-                    .format_state(&self.state, &mut draw_state.lines, width);
-            }
+            format_lines(
+                &self.style,
+                &self.state,
+                self.depth,
+                &mut draw_state.lines,
+                width,
+            );
         }
 
         drop(draw_state);
-        let _ = drawable.draw();
+        if let Ok(true) = drawable.draw() {
+            self.last_draw = Some(now);
+        }
     }
 
     pub(crate) fn suspend<F: FnOnce() -> R, R>(&mut self, now: Instant, f: F) -> R {
+        self.suspend_inner(now, f, false)
+    }
+
+    /// Like `suspend`, but pauses the bar while `f` runs, see `ProgressBar::prompt`
+    pub(crate) fn prompt<F: FnOnce() -> R, R>(&mut self, now: Instant, f: F) -> R {
+        self.suspend_inner(now, f, true)
+    }
+
+    fn suspend_inner<F: FnOnce() -> R, R>(&mut self, now: Instant, f: F, pause: bool) -> R {
+        // A bar that was paused already stays paused afterwards
+        let paused = pause && self.stop_clock(now);
         if let Some((state, _)) = self.draw_target.remote() {
-            return state.write().unwrap().suspend(f, now);
+            let ret = state.write().ignore_poison().suspend(f, now);
+            if paused {
+                self.restart_clock(Instant::now());
+            }
+            return ret;
         }
 
         if let Some(drawable) = self.draw_target.drawable(true, now) {
@@ -185,18 +724,82 @@ impl BarState {
         }
 
         let ret = f();
-        let _ = self.draw(true, Instant::now());
+        let now = Instant::now();
+        if paused {
+            self.restart_clock(now);
+        }
+        let _ = self.draw(true, now);
         ret
     }
 
-    pub(crate) fn draw(&mut self, mut force_draw: bool, now: Instant) -> io::Result<()> {
+    pub(crate) fn draw(&mut self, force_draw: bool, now: Instant) -> io::Result<()> {
+        self.draw_inner(force_draw, false, now)
+    }
+
+    fn draw_inner(
+        &mut self,
+        mut force_draw: bool,
+        steady_tick: bool,
+        now: Instant,
+    ) -> io::Result<()> {
         // `|= self.is_finished()` should not be needed here, but we used to always draw for
         // finished progress bars, so it's kept as to not cause compatibility issues in weird cases.
         force_draw |= self.state.is_finished();
+        if !force_draw {
+            let recent = self
+                .last_redraw
+                .zip(self.min_redraw_interval)
+                .is_some_and(|(last, interval)| now.saturating_duration_since(last) < interval);
+            if recent {
+                return Ok(());
+            }
+        }
+        if self.is_render_delayed(now) {
+            return Ok(());
+        }
+        self.last_redraw = Some(now);
+
+        #[cfg(feature = "ipc")]
+        {
+            let event = || match self.state.is_finished() {
+                true => IpcEvent::Finish(IpcProgress::from_state(&self.state)),
+                false => IpcEvent::Progress(IpcProgress::from_state(&self.state)),
+            };
+            if let Some(result) = self.draw_target.send_ipc(force_draw, now, event) {
+                return result;
+            }
+        }
+        let finished = self.state.is_finished();
+        if let Some(result) = self
+            .draw_target
+            .send_plain(now, finished, || self.state.plain_line())
+        {
+            if result? {
+                self.last_draw = Some(now);
+            }
+            return Ok(());
+        }
+        self.update_title();
+
         let mut drawable = match self.draw_target.drawable(force_draw, now) {
             Some(drawable) => drawable,
             None => return Ok(()),
         };
+        if steady_tick {
+            drawable.set_steady_tick();
+        }
+
+        // `ProgressEvent::Finished` is sent once when finishing
+        let update_due = self.last_update_event.map_or(true, |last| {
+            now.saturating_duration_since(last) >= UPDATE_EVENT_INTERVAL
+        });
+        if update_due && !self.state.is_finished() {
+            self.last_update_event = Some(now);
+            drawable.notify(|index| ProgressEvent::Updated {
+                index,
+                progress: IpcProgress::from_state(&self.state),
+            });
+        }
 
         // Getting the width can be expensive; thus this should happen after checking drawable.
         let width = drawable.width();
@@ -204,14 +807,23 @@ impl BarState {
         let mut draw_state = drawable.state();
 
         if let Some(width) = width {
-            if !matches!(self.state.status, Status::DoneHidden) {
-                self.style
-                    .format_state(&self.state, &mut draw_state.lines, width);
-            }
+            format_lines(
+                &self.style,
+                &self.state,
+                self.depth,
+                &mut draw_state.lines,
+                width,
+            );
         }
 
         drop(draw_state);
-        drawable.draw()
+        if drawable.draw()? {
+            self.last_draw = Some(now);
+            if !matches!(self.state.status, Status::DoneHidden) {
+                self.first_draw.get_or_insert(now);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -224,13 +836,58 @@ impl Drop for BarState {
             return;
         }
 
-        self.finish_using_style(Instant::now(), self.on_finish.clone());
+        let now = Instant::now();
+        self.finish_with_reason(now, self.on_finish.clone(), FinishReason::Dropped);
+        self.clear_pending(now);
 
         // Notify the `MultiProgress` that we're now a zombie.
         self.draw_target.mark_zombie();
     }
 }
 
+/// Appends the rendered lines of a bar, indented by `depth` levels
+fn format_lines(
+    style: &ProgressStyle,
+    state: &ProgressState,
+    depth: usize,
+    lines: &mut Vec<LineType>,
+    width: u16,
+) {
+    if matches!(state.status, Status::DoneHidden) {
+        return;
+    }
+
+    let indent = 2 * depth;
+    let start = lines.len();
+    let width = width.saturating_sub(u16::try_from(indent).unwrap_or(u16::MAX));
+    style.format_state(state, lines, width);
+    if indent > 0 {
+        for line in &mut lines[start..] {
+            if let LineType::Bar(line) = line {
+                line.insert_str(0, &" ".repeat(indent));
+            }
+        }
+    }
+}
+
+/// Tracks the percentage shown in the terminal window title
+#[derive(Debug)]
+pub(crate) struct TitlePercent {
+    /// Whether the terminal is focused, if known
+    focused: Option<Arc<AtomicBool>>,
+    /// The percentage currently shown in the title
+    shown: Option<u64>,
+}
+
+impl TitlePercent {
+    pub(crate) fn new(focused: Option<Arc<AtomicBool>>) -> Self {
+        Self {
+            focused,
+            shown: None,
+        }
+    }
+}
+
 pub(crate) enum Reset {
     Eta,
     Elapsed,
@@ -248,6 +905,28 @@ pub struct ProgressState {
     est: Estimator,
     pub(crate) message: TabExpandedString,
     pub(crate) prefix: TabExpandedString,
+    pub(crate) id: Option<Cow<'static, str>>,
+    /// Whether the position is driven by the elapsed time in milliseconds
+    pub(crate) timer: bool,
+    finish_reason: Option<FinishReason>,
+    /// The start of the range the progress is computed over, see `ProgressBar::new_range()`
+    start: u64,
+    /// The position at the start of this session, see `ProgressBar::reset_session()`
+    session_start: u64,
+    /// Whether the recent rate is higher or lower than the average, see `{trend}`
+    trend: Trend,
+    /// The number of failed items, see `ProgressBar::inc_failures()`
+    pub(crate) failures: u64,
+    /// An externally computed ETA, see `ProgressBar::set_eta()`
+    pub(crate) eta_override: Option<EtaOverride>,
+    /// When the `MultiProgress` the bar was added to was created, see `{multi_elapsed}`
+    pub(crate) multi_started: Option<Instant>,
+    /// When the bar was paused, if it is paused, see `ProgressBar::pause()`
+    paused_at: Option<Instant>,
+    /// The time the bar was paused in total, not counting the current pause
+    paused: Duration,
+    /// The tab width of the message and prefix
+    tab_width: usize,
 }
 
 impl ProgressState {
@@ -262,7 +941,104 @@ impl ProgressState {
             est: Estimator::new(now),
             message: TabExpandedString::NoTabs("".into()),
             prefix: TabExpandedString::NoTabs("".into()),
+            id: None,
+            timer: false,
+            finish_reason: None,
+            start: 0,
+            session_start: 0,
+            trend: Trend::Steady,
+            failures: 0,
+            eta_override: None,
+            multi_started: None,
+            paused_at: None,
+            paused: Duration::ZERO,
+            tab_width: DEFAULT_TAB_WIDTH,
+        }
+    }
+
+    /// The time the bar was running at `now`, excluding the time it was paused
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.started)
+            .saturating_sub(self.paused)
+            .saturating_sub(
+                self.paused_at
+                    .map_or(Duration::ZERO, |at| now.saturating_duration_since(at)),
+            )
+    }
+
+    /// The time the estimator sees at `now`, which stands still while the bar is paused
+    fn active_now(&self, now: Instant) -> Instant {
+        self.paused_at.unwrap_or(now)
+    }
+
+    /// Indicates that the progress bar is paused, see [`ProgressBar::pause()`]
+    ///
+    /// [`ProgressBar::pause()`]: crate::ProgressBar::pause
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Compare the recent rate to the average rate since the start
+    ///
+    /// The thresholds to enter a rising or falling trend are higher than those to stay in it, so
+    /// that the trend does not flap when the rate hovers around a threshold.
+    pub(crate) fn update_trend(&mut self, now: Instant) {
+        let elapsed = duration_to_secs(self.elapsed_at(now));
+        let steps = self.pos().saturating_sub(self.start);
+        if self.is_finished() || elapsed < 1.0 || steps == 0 {
+            self.trend = Trend::Steady;
+            return;
         }
+
+        let ratio = self.est.steps_per_second(now) / (steps as f64 / elapsed);
+        self.trend = match self.trend {
+            Trend::Rising if ratio > 1.05 => Trend::Rising,
+            Trend::Falling if ratio < 0.95 => Trend::Falling,
+            _ if ratio > 1.1 => Trend::Rising,
+            _ if ratio < 0.9 => Trend::Falling,
+            _ => Trend::Steady,
+        };
+    }
+
+    /// The arrow rendered by the `{trend}` template key
+    pub(crate) fn trend_str(&self) -> &'static str {
+        match self.trend {
+            Trend::Rising => "↑",
+            Trend::Falling => "↓",
+            Trend::Steady => "→",
+        }
+    }
+
+    pub(crate) fn set_range_start(&mut self, start: u64) {
+        self.start = start;
+        self.session_start = start;
+        self.pos.set(start);
+        self.est.set_start_steps(start);
+    }
+
+    /// The number of times the progress bar ticked, which selects the spinner frame
+    ///
+    /// Custom keys registered with [`ProgressStyle::with_key()`] can use this to drive their own
+    /// animations in sync with the spinner:
+    ///
+    /// ```rust
+    /// # use indicatif::{ProgressState, ProgressStyle};
+    /// let style = ProgressStyle::with_template("{dots} {msg}")
+    ///     .unwrap()
+    ///     .with_key("dots", |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+    ///         let n = (state.tick() % 4) as usize;
+    ///         write!(w, "{:<3}", ".".repeat(n)).unwrap()
+    ///     });
+    /// ```
+    ///
+    /// [`ProgressStyle::with_key()`]: crate::ProgressStyle::with_key
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// How the progress bar ended, or `None` if it is still in progress
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.finish_reason
     }
 
     /// Indicates that the progress bar finished.
@@ -276,8 +1052,13 @@ impl ProgressState {
 
     /// Returns the completion as a floating-point number between 0 and 1
     pub fn fraction(&self) -> f32 {
-        let pos = self.pos.pos.load(Ordering::Relaxed);
-        let pct = match (pos, self.len) {
+        let pos = self
+            .pos
+            .pos
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.start);
+        let len = self.len.map(|len| len.saturating_sub(self.start));
+        let pct = match (pos, len) {
             (_, None) => 0.0,
             (_, Some(0)) => 1.0,
             (0, _) => 0.0,
@@ -286,20 +1067,72 @@ impl ProgressState {
         pct.clamp(0.0, 1.0)
     }
 
+    /// Formats the state as a single line, see `ProgressDrawTarget::plain`
+    pub(crate) fn plain_line(&self) -> String {
+        let mut line = String::new();
+        let prefix = self.prefix.expanded();
+        if !prefix.is_empty() {
+            line.push_str(prefix);
+            line.push(' ');
+        }
+
+        let _ = match self.len() {
+            Some(len) => write!(
+                line,
+                "{}% ({}/{})",
+                (self.fraction() * 100.0) as u32,
+                self.pos(),
+                len
+            ),
+            None => write!(line, "{}", self.pos()),
+        };
+        let _ = match self.finish_reason {
+            Some(reason) => write!(line, " {}", reason.as_str()),
+            None if self.len().is_some() => write!(line, " ETA {:#}", HumanDuration(self.eta())),
+            None => Ok(()),
+        };
+
+        let msg = self.message.expanded();
+        if !msg.is_empty() {
+            line.push(' ');
+            line.push_str(msg);
+        }
+        line
+    }
+
+    /// The remaining time of the external ETA at `now`, unless there is none or it is stale
+    fn external_eta(&self, now: Instant) -> Option<Duration> {
+        let o = self.eta_override.as_ref()?;
+        let age = now.saturating_duration_since(o.set_at);
+        match o.stale_after {
+            Some(stale_after) if age >= stale_after => None,
+            _ => Some(o.eta.saturating_sub(age)),
+        }
+    }
+
     /// The expected ETA
     pub fn eta(&self) -> Duration {
         if self.is_finished() {
             return Duration::new(0, 0);
         }
 
+        if let Some(eta) = self.external_eta(Instant::now()) {
+            return eta;
+        }
+
         let len = match self.len {
             Some(len) => len,
             None => return Duration::new(0, 0),
         };
 
+        // Timer bars know their total duration, so the ETA is exact
+        if self.timer {
+            return Duration::from_millis(len).saturating_sub(self.elapsed());
+        }
+
         let pos = self.pos.pos.load(Ordering::Relaxed);
 
-        let sps = self.est.steps_per_second(Instant::now());
+        let sps = self.est.steps_per_second(self.active_now(Instant::now()));
 
         // Infinite duration should only ever happen at the beginning, so in this case it's okay to
         // just show an ETA of 0 until progress starts to occur.
@@ -312,23 +1145,70 @@ impl ProgressState {
 
     /// The expected total duration (that is, elapsed time + expected ETA)
     pub fn duration(&self) -> Duration {
-        if self.len.is_none() || self.is_finished() {
+        let external = self.external_eta(Instant::now()).is_some();
+        if (self.len.is_none() && !external) || self.is_finished() {
             return Duration::new(0, 0);
         }
-        self.started.elapsed().saturating_add(self.eta())
+        self.elapsed().saturating_add(self.eta())
     }
 
     /// The number of steps per second
     pub fn per_sec(&self) -> f64 {
         if let Status::InProgress = self.status {
-            self.est.steps_per_second(Instant::now())
+            self.est.rate(self.active_now(Instant::now()))
         } else {
-            self.pos() as f64 / self.started.elapsed().as_secs_f64()
+            let elapsed = self.elapsed().as_secs_f64();
+            match elapsed > 0.0 {
+                true => self.pos().saturating_sub(self.start) as f64 / elapsed,
+                false => 0.0,
+            }
         }
     }
 
+    /// The time since the bar was created or reset, excluding the time it was paused
     pub fn elapsed(&self) -> Duration {
-        self.started.elapsed()
+        self.elapsed_at(Instant::now())
+    }
+
+    /// The time since the [`MultiProgress`] the bar was added to was created
+    ///
+    /// This is the same as [`ProgressState::elapsed()`] if the bar was not added to a
+    /// [`MultiProgress`].
+    ///
+    /// [`MultiProgress`]: crate::MultiProgress
+    pub fn multi_elapsed(&self) -> Duration {
+        self.multi_started.unwrap_or(self.started).elapsed()
+    }
+
+    /// The elapsed time formatted like the `{elapsed_precise}` template key (`HH:MM:SS`)
+    pub fn elapsed_precise(&self) -> String {
+        FormattedDuration(self.elapsed()).to_string()
+    }
+
+    /// The elapsed time formatted like the `{elapsed}` template key (e.g. `3m`)
+    pub fn elapsed_human(&self) -> String {
+        format!("{:#}", HumanDuration(self.elapsed()))
+    }
+
+    /// The ETA formatted like the `{eta_precise}` template key (`HH:MM:SS`)
+    pub fn eta_precise(&self) -> String {
+        FormattedDuration(self.eta()).to_string()
+    }
+
+    /// The ETA formatted like the `{eta}` template key (e.g. `3m`)
+    pub fn eta_human(&self) -> String {
+        format!("{:#}", HumanDuration(self.eta()))
+    }
+
+    /// The expected total duration formatted like the `{duration_precise}` template key
+    /// (`HH:MM:SS`)
+    pub fn duration_precise(&self) -> String {
+        FormattedDuration(self.duration()).to_string()
+    }
+
+    /// The expected total duration formatted like the `{duration}` template key (e.g. `3m`)
+    pub fn duration_human(&self) -> String {
+        format!("{:#}", HumanDuration(self.duration()))
     }
 
     pub fn pos(&self) -> u64 {
@@ -347,6 +1227,70 @@ impl ProgressState {
     pub fn set_len(&mut self, len: u64) {
         self.len = Some(len);
     }
+
+    /// Sets the current message, see [`ProgressBar::set_message()`]
+    ///
+    /// This is useful in [`ProgressTracker::update()`] to derive the message from the progress,
+    /// e.g. to show a hint while the rate is low. Unlike [`ProgressBar::set_message()`], this
+    /// does not discard queued messages.
+    ///
+    /// [`ProgressBar::set_message()`]: crate::ProgressBar::set_message
+    /// [`ProgressTracker::update()`]: crate::style::ProgressTracker::update
+    pub fn set_message(&mut self, msg: impl Into<Cow<'static, str>>) {
+        self.message = TabExpandedString::new(msg.into(), self.tab_width);
+    }
+
+    /// Sets the current prefix, see [`ProgressBar::set_prefix()`]
+    ///
+    /// [`ProgressBar::set_prefix()`]: crate::ProgressBar::set_prefix
+    pub fn set_prefix(&mut self, prefix: impl Into<Cow<'static, str>>) {
+        self.prefix = TabExpandedString::new(prefix.into(), self.tab_width);
+    }
+
+    /// The position corresponding to `fraction` of the range, clamped to `0.0..=1.0`
+    ///
+    /// If the length is unknown, it is set to [`FRACTION_LENGTH`].
+    pub(crate) fn pos_for_fraction(&mut self, fraction: f32) -> u64 {
+        let len = *self.len.get_or_insert(FRACTION_LENGTH);
+        let span = len.saturating_sub(self.start);
+        // Computed in `f64` so that lengths beyond the precision of `f32` are not rounded twice
+        let offset = (f64::from(fraction.clamp(0.0, 1.0)) * span as f64).round() as u64;
+        self.start + Ord::min(offset, span)
+    }
+
+    /// The start of the range the progress is computed over (0 unless created with
+    /// [`ProgressBar::new_range()`](crate::ProgressBar::new_range))
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// The progress made in this session, i.e. the position relative to the session start
+    ///
+    /// See [`ProgressBar::reset_session()`](crate::ProgressBar::reset_session).
+    pub fn session_pos(&self) -> u64 {
+        self.pos().saturating_sub(self.session_start)
+    }
+
+    /// The number of failed items
+    ///
+    /// See [`ProgressBar::inc_failures()`](crate::ProgressBar::inc_failures).
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// The number of items that did not fail, i.e. the position minus the failures
+    pub fn successes(&self) -> u64 {
+        self.pos().saturating_sub(self.failures)
+    }
+
+    pub(crate) fn reset_session(&mut self) {
+        self.session_start = self.pos();
+    }
+
+    /// The stable identifier of the progress bar, if one was set
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -404,10 +1348,10 @@ impl TabExpandedString {
 /// Double-smoothed exponentially weighted estimator
 ///
 /// This uses an exponentially weighted *time-based* estimator, meaning that it exponentially
-/// downweights old data based on its age. The rate at which this occurs is currently a constant
-/// value of 15 seconds for 90% weighting. This means that all data older than 15 seconds has a
-/// collective weight of 0.1 in the estimate, and all data older than 30 seconds has a collective
-/// weight of 0.01, and so on.
+/// downweights old data based on its age. The rate at which this occurs is configurable with
+/// [`EtaSmoothing`] and defaults to 15 seconds for 90% weighting. This means that all data older
+/// than 15 seconds has a collective weight of 0.1 in the estimate, and all data older than 30
+/// seconds has a collective weight of 0.01, and so on.
 ///
 /// The primary value exposed by `Estimator` is `steps_per_second`. This value is doubly-smoothed,
 /// meaning that is the result of using an exponentially weighted estimator (as described above) to
@@ -424,6 +1368,17 @@ pub(crate) struct Estimator {
     prev_steps: u64,
     prev_time: Instant,
     start_time: Instant,
+    /// The age in seconds at which data has a collective weight of 0.1
+    weighting_secs: f64,
+    /// The window `rate` measures over
+    window: RateWindow,
+    /// The position at `start_time`
+    start_steps: u64,
+    /// The steps and duration of the last update
+    last_update: (u64, Duration),
+    /// Positions at the updates within the window of `RateWindow::OverLast` and the last one
+    /// before it, oldest first
+    samples: VecDeque<(Instant, u64)>,
 }
 
 impl Estimator {
@@ -434,9 +1389,30 @@ impl Estimator {
             prev_steps: 0,
             prev_time: now,
             start_time: now,
+            weighting_secs: EXPONENTIAL_WEIGHTING_SECONDS,
+            window: RateWindow::default(),
+            start_steps: 0,
+            last_update: (0, Duration::ZERO),
+            samples: VecDeque::from([(now, 0)]),
         }
     }
 
+    pub(crate) fn set_smoothing(&mut self, smoothing: EtaSmoothing) {
+        self.weighting_secs = smoothing.weighting_secs();
+    }
+
+    pub(crate) fn set_rate_window(&mut self, window: RateWindow) {
+        self.window = window;
+    }
+
+    /// Set the position progress is counted from, without recording it as progress
+    fn set_start_steps(&mut self, steps: u64) {
+        self.prev_steps = steps;
+        self.start_steps = steps;
+        self.samples.clear();
+        self.samples.push_back((self.prev_time, steps));
+    }
+
     fn record(&mut self, new_steps: u64, now: Instant) {
         // sanity check: don't record data if time or steps have not advanced
         if new_steps <= self.prev_steps || now <= self.prev_time {
@@ -456,7 +1432,7 @@ impl Estimator {
         let new_steps_per_second = delta_steps as f64 / delta_t;
 
         // update the estimate: a weighted average of the old estimate and new data
-        let weight = estimator_weight(delta_t);
+        let weight = estimator_weight(delta_t, self.weighting_secs);
         self.smoothed_steps_per_sec =
             self.smoothed_steps_per_sec * weight + new_steps_per_second * (1.0 - weight);
 
@@ -467,17 +1443,48 @@ impl Estimator {
         // a source for the double smoothed estimate. See comment on normalization in
         // `steps_per_second` for details.
         let delta_t_start = duration_to_secs(now - self.start_time);
-        let total_weight = 1.0 - estimator_weight(delta_t_start);
+        let total_weight = 1.0 - estimator_weight(delta_t_start, self.weighting_secs);
         let normalized_smoothed_steps_per_sec = self.smoothed_steps_per_sec / total_weight;
 
         // determine the double smoothed value (EWA smoothing of the single EWA)
         self.double_smoothed_steps_per_sec = self.double_smoothed_steps_per_sec * weight
             + normalized_smoothed_steps_per_sec * (1.0 - weight);
 
+        self.last_update = (delta_steps, now - self.prev_time);
+        if let RateWindow::OverLast(window) = self.window {
+            // Keep the last sample before the window to measure from
+            while self.samples.len() > 1 && now - self.samples[1].0 >= window {
+                self.samples.pop_front();
+            }
+            self.samples.push_back((now, new_steps));
+        }
+
         self.prev_steps = new_steps;
         self.prev_time = now;
     }
 
+    /// Account for the position being decreased by `delta` on purpose, e.g. for re-queued work
+    ///
+    /// Unlike a backwards seek observed by `record`, this keeps the estimate: the steps taken
+    /// back are simply not counted, and redoing them later counts as progress again.
+    pub(crate) fn rewind(&mut self, delta: u64) {
+        self.prev_steps = self.prev_steps.saturating_sub(delta);
+        self.start_steps = self.start_steps.saturating_sub(delta);
+        for (_, steps) in &mut self.samples {
+            *steps = steps.saturating_sub(delta);
+        }
+    }
+
+    /// Shift the estimator's timestamps forward so that `duration` does not count as time
+    /// without progress
+    fn exclude(&mut self, duration: Duration) {
+        self.prev_time += duration;
+        self.start_time += duration;
+        for (time, _) in &mut self.samples {
+            *time += duration;
+        }
+    }
+
     /// Reset the state of the estimator. Once reset, estimates will not depend on any data prior
     /// to `now`. This does not reset the stored position of the progress bar.
     pub(crate) fn reset(&mut self, now: Instant) {
@@ -487,6 +1494,47 @@ impl Estimator {
         // only reset prev_time, not prev_steps
         self.prev_time = now;
         self.start_time = now;
+        self.start_steps = self.prev_steps;
+        self.last_update = (0, Duration::ZERO);
+        self.samples.clear();
+        self.samples.push_back((now, self.prev_steps));
+    }
+
+    /// Steps per second over the configured [`RateWindow`]
+    fn rate(&self, now: Instant) -> f64 {
+        let (steps, secs) = match self.window {
+            RateWindow::Smoothed => return self.steps_per_second(now),
+            RateWindow::Instant => {
+                // Once the next update is overdue, let the rate decay as if it were about to land
+                let (steps, duration) = self.last_update;
+                let since = now.saturating_duration_since(self.prev_time);
+                (steps, duration_to_secs(Ord::max(duration, since)))
+            }
+            RateWindow::OverLast(window) => {
+                // Measure from the last sample before the window, or the oldest one
+                let (time, steps) = self
+                    .samples
+                    .iter()
+                    .rev()
+                    .find(|&&(time, _)| now.saturating_duration_since(time) >= window)
+                    .or_else(|| self.samples.front())
+                    .copied()
+                    .unwrap_or((self.prev_time, self.prev_steps));
+                (
+                    self.prev_steps.saturating_sub(steps),
+                    duration_to_secs(now.saturating_duration_since(time)),
+                )
+            }
+            RateWindow::SinceStart => (
+                self.prev_steps.saturating_sub(self.start_steps),
+                duration_to_secs(now.saturating_duration_since(self.start_time)),
+            ),
+        };
+
+        match secs > 0.0 {
+            true => steps as f64 / secs,
+            false => 0.0,
+        }
     }
 
     /// Average time per step in seconds, using double exponential smoothing
@@ -496,7 +1544,7 @@ impl Estimator {
         // we determine how much time has passed since the last update, and treat this as a
         // pseudo-update with 0 steps.
         let delta_t = duration_to_secs(now - self.prev_time);
-        let reweight = estimator_weight(delta_t);
+        let reweight = estimator_weight(delta_t, self.weighting_secs);
 
         // Normalization of estimates:
         //
@@ -518,7 +1566,7 @@ impl Estimator {
         // in the weighted average. This sum is just W(0) - W(t_f), where t_f is the time since the
         // first sample, and W(0) = 1.
         let delta_t_start = duration_to_secs(now - self.start_time);
-        let total_weight = 1.0 - estimator_weight(delta_t_start);
+        let total_weight = 1.0 - estimator_weight(delta_t_start, self.weighting_secs);
 
         // Generate updated values for `smoothed_steps_per_sec` and `double_smoothed_steps_per_sec`
         // (sps and dsps) without storing them. Note that we normalize sps when using it as a
@@ -529,6 +1577,153 @@ impl Estimator {
     }
 }
 
+/// Sliding window of per-item durations
+///
+/// Every update that advances the position records one sample: the time since the previous
+/// update divided by the number of items completed in between. Only the most recent `window`
+/// samples are kept, bounding memory use.
+#[derive(Clone, Debug)]
+pub(crate) struct ItemTimes {
+    window: usize,
+    samples: VecDeque<Duration>,
+    prev_steps: u64,
+    prev_time: Option<Instant>,
+}
+
+impl ItemTimes {
+    pub(crate) fn new(window: usize) -> Self {
+        Self {
+            window: Ord::max(window, 1),
+            samples: VecDeque::new(),
+            prev_steps: 0,
+            prev_time: None,
+        }
+    }
+
+    fn record(&mut self, steps: u64, now: Instant) {
+        let prev_time = match self.prev_time {
+            Some(prev_time) if steps > self.prev_steps && now > prev_time => prev_time,
+            // Nothing completed (or the bar moved backwards): only (re)start the clock
+            Some(_) if steps >= self.prev_steps => return,
+            _ => {
+                self.prev_steps = steps;
+                self.prev_time = Some(now);
+                return;
+            }
+        };
+
+        let per_item = (now - prev_time).div_f64((steps - self.prev_steps) as f64);
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(per_item);
+
+        self.prev_steps = steps;
+        self.prev_time = Some(now);
+    }
+
+    fn reset(&mut self, now: Instant) {
+        self.samples.clear();
+        self.prev_steps = 0;
+        self.prev_time = Some(now);
+    }
+
+    pub(crate) fn min(&self) -> Option<Duration> {
+        self.samples.iter().min().copied()
+    }
+
+    pub(crate) fn max(&self) -> Option<Duration> {
+        self.samples.iter().max().copied()
+    }
+
+    /// The `q`-quantile (between 0 and 1) using the nearest-rank method
+    pub(crate) fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let rank = (q * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+}
+
+/// Double exponentially smoothed rate of progress
+///
+/// Every update that advances the position records one sample: the number of items completed
+/// since the previous update divided by the time in between. The samples are smoothed twice with
+/// the factor `alpha` (Brown's double exponential smoothing), which follows trends in the rate
+/// without lagging behind as much as a single smoothing pass with the same factor.
+#[derive(Clone, Debug)]
+pub(crate) struct SmoothedRate {
+    alpha: f64,
+    single: f64,
+    double: f64,
+    samples: u64,
+    prev_steps: u64,
+    prev_time: Option<Instant>,
+}
+
+impl SmoothedRate {
+    pub(crate) fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(f64::EPSILON, 1.0),
+            single: 0.0,
+            double: 0.0,
+            samples: 0,
+            prev_steps: 0,
+            prev_time: None,
+        }
+    }
+
+    fn record(&mut self, steps: u64, now: Instant) {
+        let prev_time = match self.prev_time {
+            Some(prev_time) if steps > self.prev_steps && now > prev_time => prev_time,
+            // Nothing completed (or the bar moved backwards): only (re)start the clock
+            Some(_) if steps >= self.prev_steps => return,
+            _ => {
+                self.prev_steps = steps;
+                self.prev_time = Some(now);
+                return;
+            }
+        };
+
+        let rate = (steps - self.prev_steps) as f64 / duration_to_secs(now - prev_time);
+        match self.samples {
+            // Start from the first sample instead of 0 to avoid a slow ramp-up
+            0 => (self.single, self.double) = (rate, rate),
+            _ => {
+                self.single = self.alpha * rate + (1.0 - self.alpha) * self.single;
+                self.double = self.alpha * self.single + (1.0 - self.alpha) * self.double;
+            }
+        }
+        self.samples += 1;
+
+        self.prev_steps = steps;
+        self.prev_time = Some(now);
+    }
+
+    fn reset(&mut self, now: Instant) {
+        *self = Self::new(self.alpha);
+        self.prev_time = Some(now);
+    }
+
+    /// The smoothed number of steps per second, 0 until the first sample
+    pub(crate) fn per_sec(&self) -> f64 {
+        f64::max(2.0 * self.single - self.double, 0.0)
+    }
+
+    /// The expected remaining time at the smoothed rate
+    pub(crate) fn eta(&self, state: &ProgressState) -> Duration {
+        let (len, rate) = match (state.len, self.per_sec()) {
+            (Some(len), rate) if !state.is_finished() && rate > 0.0 => (len, rate),
+            _ => return Duration::new(0, 0),
+        };
+        secs_to_duration(len.saturating_sub(state.pos()) as f64 / rate)
+    }
+}
+
 pub(crate) struct AtomicPosition {
     pub(crate) pos: AtomicU64,
     capacity: AtomicU8,
@@ -581,6 +1776,17 @@ impl AtomicPosition {
         true
     }
 
+    /// Returns whether an update at `now` would be allowed, without consuming capacity
+    pub(crate) fn would_allow(&self, now: Instant) -> bool {
+        if now < self.start {
+            return false;
+        }
+
+        let prev = self.prev.load(Ordering::Acquire);
+        let elapsed = (now - self.start).as_nanos() as u64;
+        self.capacity.load(Ordering::Acquire) > 0 || elapsed.saturating_sub(prev) >= INTERVAL
+    }
+
     fn reset(&self, now: Instant) {
         self.set(0);
         let elapsed = (now.saturating_duration_since(self.start)).as_nanos() as u64;
@@ -591,8 +1797,15 @@ impl AtomicPosition {
         self.pos.fetch_add(delta, Ordering::SeqCst);
     }
 
-    pub(crate) fn dec(&self, delta: u64) {
-        self.pos.fetch_sub(delta, Ordering::SeqCst);
+    /// Decreases the position by up to `delta` without going below 0, returning the decrease
+    pub(crate) fn dec(&self, delta: u64) -> u64 {
+        let prev = self
+            .pos
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pos| {
+                Some(pos.saturating_sub(delta))
+            })
+            .unwrap();
+        Ord::min(prev, delta)
     }
 
     pub(crate) fn set(&self, pos: u64) {
@@ -600,6 +1813,14 @@ impl AtomicPosition {
     }
 }
 
+/// The number of messages queued by `ProgressBar::set_message_enqueue` before the oldest ones
+/// are dropped
+pub(crate) const MAX_QUEUED_MESSAGES: usize = 16;
+
+/// The minimum time between `ProgressEvent::Updated` events of a progress bar, matching the
+/// default refresh rate of 20 frames per second
+const UPDATE_EVENT_INTERVAL: Duration = Duration::from_millis(50);
+
 const INTERVAL: u64 = 1_000_000;
 const MAX_BURST: u8 = 10;
 
@@ -611,7 +1832,7 @@ const MAX_BURST: u8 = 10;
 /// [`ProgressBar`]: crate::ProgressBar
 /// [`ProgressBarIter`]: crate::ProgressBarIter
 /// [`ProgressBar::is_finished`]: crate::ProgressBar::is_finished
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum ProgressFinish {
     /// Finishes the progress bar and leaves the current message
     ///
@@ -624,6 +1845,7 @@ pub enum ProgressFinish {
     /// Finishes the progress bar and completely clears it (this is the default)
     ///
     /// Same behavior as calling [`ProgressBar::finish_and_clear()`](crate::ProgressBar::finish_and_clear).
+    #[default]
     AndClear,
     /// Finishes the progress bar and leaves the current message and progress
     ///
@@ -635,13 +1857,137 @@ pub enum ProgressFinish {
     AbandonWithMessage(Cow<'static, str>),
 }
 
-impl Default for ProgressFinish {
-    fn default() -> Self {
-        Self::AndClear
+/// A consistent view of the statistics of a progress bar
+///
+/// See [`ProgressBar::snapshot()`](crate::ProgressBar::snapshot). All values are taken at the same
+/// time, which makes this useful for summaries when the progress bar is hidden (e.g. when the
+/// output is not a terminal).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ProgressSnapshot {
+    /// The identifier of the progress bar, if one was set
+    pub id: Option<String>,
+    /// The current position
+    pub pos: u64,
+    /// The current length, if any
+    pub len: Option<u64>,
+    /// The completion as a number between 0 and 1
+    pub fraction: f32,
+    /// The elapsed time
+    pub elapsed: Duration,
+    /// The expected remaining time
+    pub eta: Duration,
+    /// The number of steps per second
+    pub per_sec: f64,
+    /// The current message
+    pub message: String,
+    /// The current prefix
+    pub prefix: String,
+    /// How the progress bar ended, or `None` if it is still in progress
+    pub finish_reason: Option<FinishReason>,
+}
+
+/// An externally computed ETA, see `ProgressBar::set_eta()`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EtaOverride {
+    pub(crate) eta: Duration,
+    pub(crate) set_at: Instant,
+    /// After how long the estimator takes over again, if ever
+    pub(crate) stale_after: Option<Duration>,
+}
+
+impl ProgressSnapshot {
+    fn new(state: &ProgressState) -> Self {
+        Self {
+            id: state.id().map(Into::into),
+            pos: state.pos(),
+            len: state.len(),
+            fraction: state.fraction(),
+            elapsed: state.elapsed(),
+            eta: state.eta(),
+            per_sec: state.per_sec(),
+            message: state.message.expanded().to_string(),
+            prefix: state.prefix.expanded().to_string(),
+            finish_reason: state.finish_reason(),
+        }
+    }
+
+    /// Whether the progress bar is finished
+    pub fn is_finished(&self) -> bool {
+        self.finish_reason.is_some()
     }
 }
 
-/// Get the appropriate dilution weight for Estimator data given the data's age (in seconds)
+/// The state of a progress bar as carried by IPC events and [`ProgressEvent`]s
+///
+/// [`ProgressEvent`]: crate::ProgressEvent
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct IpcProgress {
+    /// The identifier of the progress bar, if one was set
+    ///
+    /// See [`ProgressBar::set_id`](crate::ProgressBar::set_id).
+    pub id: Option<String>,
+    /// The current position
+    pub pos: u64,
+    /// The current length, if any
+    pub len: Option<u64>,
+    /// The current message
+    pub message: String,
+    /// The current prefix
+    pub prefix: String,
+}
+
+impl IpcProgress {
+    pub(crate) fn from_state(state: &ProgressState) -> Self {
+        Self {
+            id: state.id().map(Into::into),
+            pos: state.pos(),
+            len: state.len(),
+            message: state.message.expanded().to_string(),
+            prefix: state.prefix.expanded().to_string(),
+        }
+    }
+}
+
+/// How a progress bar ended
+///
+/// See [`ProgressBar::finish_reason()`](crate::ProgressBar::finish_reason).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FinishReason {
+    /// The progress bar was finished, e.g. with [`ProgressBar::finish()`]
+    ///
+    /// [`ProgressBar::finish()`]: crate::ProgressBar::finish
+    Finished,
+    /// The progress bar was abandoned, e.g. with [`ProgressBar::abandon()`]
+    ///
+    /// [`ProgressBar::abandon()`]: crate::ProgressBar::abandon
+    Abandoned,
+    /// The progress bar failed, see [`ProgressBar::fail()`]
+    ///
+    /// [`ProgressBar::fail()`]: crate::ProgressBar::fail
+    Failed,
+    /// The progress bar was dropped before it was finished
+    Dropped,
+}
+
+impl FinishReason {
+    /// The name rendered by the `{finish_reason}` template key
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Finished => "finished",
+            Self::Abandoned => "abandoned",
+            Self::Failed => "failed",
+            Self::Dropped => "dropped",
+        }
+    }
+}
+
+/// Get the appropriate dilution weight for Estimator data given the data's age and the
+/// exponential weighting seconds `ews` (both in seconds)
 ///
 /// Whenever an update occurs, we will create a new estimate using a weight `w_i` like so:
 ///
@@ -665,15 +2011,21 @@ impl Default for ProgressFinish {
 ///
 /// Notice that the constraint implies that estimates are independent of the durations of the
 /// samples, a very useful feature.
-fn estimator_weight(age: f64) -> f64 {
-    const EXPONENTIAL_WEIGHTING_SECONDS: f64 = 15.0;
-    0.1_f64.powf(age / EXPONENTIAL_WEIGHTING_SECONDS)
+fn estimator_weight(age: f64, ews: f64) -> f64 {
+    0.1_f64.powf(age / ews)
 }
 
+/// The default `ews` of the [`Estimator`], see [`estimator_weight`]
+const EXPONENTIAL_WEIGHTING_SECONDS: f64 = 15.0;
+
 fn duration_to_secs(d: Duration) -> f64 {
     d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1_000_000_000f64
 }
 
+fn duration_to_millis(d: Duration) -> u64 {
+    u64::try_from(d.as_millis()).unwrap_or(u64::MAX)
+}
+
 fn secs_to_duration(s: f64) -> Duration {
     let secs = s.trunc() as u64;
     let nanos = (s.fract() * 1_000_000_000f64) as u32;
@@ -687,6 +2039,14 @@ pub(crate) enum Status {
     DoneHidden,
 }
 
+/// Direction of the recent rate relative to the average rate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Trend {
+    Rising,
+    Steady,
+    Falling,
+}
+
 pub(crate) const DEFAULT_TAB_WIDTH: usize = 8;
 
 #[cfg(test)]
@@ -762,6 +2122,166 @@ mod tests {
         assert_eq!(est.steps_per_second(now), double_target);
     }
 
+    #[test]
+    fn pause_excludes_time() {
+        let start = Instant::now();
+        let pos = Arc::new(AtomicPosition::new());
+        let mut bar = BarState::new(Some(1000), ProgressDrawTarget::hidden(), pos.clone());
+        bar.state.started = start;
+        bar.state.est = Estimator::new(start);
+
+        let paused_at = start + Duration::from_secs(10);
+        pos.set(50);
+        bar.pause(paused_at);
+        assert!(bar.state.is_paused());
+        let sps = bar.state.est.steps_per_second(paused_at);
+
+        let later = paused_at + Duration::from_secs(1800);
+        assert_eq!(bar.state.elapsed_at(later), Duration::from_secs(10));
+        pos.set(60);
+        bar.update_estimate(later);
+        assert_eq!(
+            bar.state.est.steps_per_second(bar.state.active_now(later)),
+            sps
+        );
+
+        let resumed_at = paused_at + Duration::from_secs(3600);
+        bar.resume(resumed_at);
+        assert!(!bar.state.is_paused());
+        assert_eq!(bar.state.elapsed_at(resumed_at), Duration::from_secs(10));
+        assert_eq!(bar.state.est.steps_per_second(resumed_at), sps);
+        assert_eq!(
+            bar.state.elapsed_at(resumed_at + Duration::from_secs(5)),
+            Duration::from_secs(15)
+        );
+
+        bar.reset(resumed_at, Reset::Elapsed);
+        assert_eq!(bar.state.elapsed_at(resumed_at), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_trend() {
+        let now = Instant::now();
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(Some(1000), pos.clone());
+        state.started = now - Duration::from_secs(10);
+
+        // 10 steps per second on average, the last 100 steps at 50 steps per second
+        pos.set(100);
+        state.est = Estimator::new(now - Duration::from_secs(2));
+        state.est.record(100, now);
+        state.update_trend(now);
+        assert_eq!(state.trend_str(), "↑");
+
+        // A rate slightly above average keeps the rising trend, but does not start one
+        state.est.smoothed_steps_per_sec = 0.0;
+        state.est.double_smoothed_steps_per_sec = 10.7;
+        state.est.start_time = now - Duration::from_secs(3600);
+        state.est.prev_time = now;
+        state.update_trend(now);
+        assert_eq!(state.trend_str(), "↑");
+        state.trend = Trend::Steady;
+        state.update_trend(now);
+        assert_eq!(state.trend_str(), "→");
+
+        state.est.double_smoothed_steps_per_sec = 5.0;
+        state.update_trend(now);
+        assert_eq!(state.trend_str(), "↓");
+    }
+
+    #[test]
+    fn test_estimator_smoothing() {
+        assert!(
+            (EtaSmoothing::default().weighting_secs() - EXPONENTIAL_WEIGHTING_SECONDS).abs() < 1e-6
+        );
+
+        // after a burst at 100 steps/s, the rate drops to 1 step/s
+        let rates = [Duration::from_secs(1), Duration::from_secs(30)].map(|half_life| {
+            let mut now = Instant::now();
+            let mut est = Estimator::new(now);
+            est.set_smoothing(EtaSmoothing::ExponentialDecay { half_life });
+            let mut pos = 0;
+            for rate in [100; 10].into_iter().chain([1; 10]) {
+                now += Duration::from_secs(1);
+                pos += rate;
+                est.record(pos, now);
+            }
+            est.steps_per_second(now)
+        });
+
+        // a short half-life follows the new rate more closely
+        assert!(rates[0] < 5.0);
+        assert!(rates[1] > 20.0);
+    }
+
+    #[test]
+    fn test_estimator_rate_window() {
+        // 100 steps/s for 10 seconds, then 10 steps/s for 5 seconds
+        let rate = |window| {
+            let mut now = Instant::now();
+            let mut est = Estimator::new(now);
+            est.set_rate_window(window);
+            let mut pos = 0;
+            for rate in [100; 10].into_iter().chain([10; 5]) {
+                now += Duration::from_secs(1);
+                pos += rate;
+                est.record(pos, now);
+            }
+            (est.rate(now), est.rate(now + Duration::from_secs(4)))
+        };
+
+        assert_eq!(rate(RateWindow::Instant), (10.0, 2.5));
+        assert_eq!(
+            rate(RateWindow::OverLast(Duration::from_secs(2))),
+            (10.0, 0.0)
+        );
+        assert_eq!(
+            rate(RateWindow::OverLast(Duration::from_secs(10))),
+            (55.0, 15.0)
+        );
+        assert_eq!(rate(RateWindow::SinceStart), (70.0, 1050.0 / 19.0));
+
+        let (smoothed, _) = rate(RateWindow::Smoothed);
+        assert!(smoothed > 10.0 && smoothed < 100.0);
+    }
+
+    #[test]
+    fn test_estimator_rewind() {
+        let mut now = Instant::now();
+        let mut est = Estimator::new(now);
+        for pos in 1..=10 {
+            now += Duration::from_secs(1);
+            est.record(pos, now);
+        }
+        let rate = est.steps_per_second(now);
+        assert!(rate > 0.0);
+
+        // re-queuing items keeps the estimate instead of resetting it
+        est.rewind(5);
+        est.record(5, now);
+        assert_eq!(est.steps_per_second(now), rate);
+
+        now += Duration::from_secs(1);
+        est.record(6, now);
+        assert!(est.steps_per_second(now) > 0.0);
+    }
+
+    #[test]
+    fn test_estimator_exclude() {
+        let mut now = Instant::now();
+        let mut est = Estimator::new(now);
+
+        now += Duration::from_secs(1);
+        est.record(10, now);
+        now += Duration::from_secs(1);
+        let rate = est.steps_per_second(now);
+
+        // time spent in a prompt does not count as time without progress
+        est.exclude(Duration::from_secs(30));
+        now += Duration::from_secs(30);
+        assert_eq!(est.steps_per_second(now), rate);
+    }
+
     #[test]
     fn test_estimator_rewind_position() {
         let mut now = Instant::now();
@@ -804,6 +2324,70 @@ mod tests {
         assert_eq!(est.steps_per_second(now), 1.0);
     }
 
+    #[test]
+    fn test_formatted_durations() {
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(Some(10), pos);
+        state.started = Instant::now() - Duration::from_secs(3 * 3600 + 25 * 60 + 7);
+        state.status = Status::DoneVisible;
+
+        assert_eq!(state.elapsed_precise(), "03:25:07");
+        assert_eq!(state.elapsed_human(), "3h");
+        assert_eq!(state.eta_precise(), "00:00:00");
+        assert_eq!(state.eta_human(), "0s");
+    }
+
+    #[test]
+    fn test_smoothed_rate() {
+        let mut now = Instant::now();
+        let pos = Arc::new(AtomicPosition::new());
+        let state = ProgressState::new(Some(100), pos.clone());
+        let mut rate = SmoothedRate::new(0.2);
+        rate.record(0, now);
+        assert_eq!(rate.per_sec(), 0.0);
+        assert_eq!(rate.eta(&state), Duration::ZERO);
+
+        // batches alternating between 1 and 3 items per second average out to 2
+        let mut steps = 0;
+        for batch in [1, 3].repeat(10) {
+            now += Duration::from_secs(1);
+            steps += batch;
+            rate.record(steps, now);
+        }
+        assert!((rate.per_sec() - 2.0).abs() < 0.5);
+
+        pos.set(60);
+        let eta = rate.eta(&state).as_secs_f64();
+        assert!((15.0..30.0).contains(&eta));
+    }
+
+    #[test]
+    fn test_item_times() {
+        let mut now = Instant::now();
+        let mut times = ItemTimes::new(10);
+        times.record(0, now);
+        assert_eq!(times.quantile(0.95), None);
+
+        for (steps, millis) in [(1, 10), (2, 30), (4, 20), (5, 50)] {
+            now += Duration::from_millis(millis);
+            times.record(steps, now);
+        }
+
+        assert_eq!(times.min(), Some(Duration::from_millis(10)));
+        assert_eq!(times.max(), Some(Duration::from_millis(50)));
+        assert_eq!(times.quantile(0.5), Some(Duration::from_millis(10)));
+        assert_eq!(times.quantile(0.95), Some(Duration::from_millis(50)));
+
+        // The window only retains the most recent samples
+        let mut times = ItemTimes::new(2);
+        times.record(0, now);
+        for steps in 1..=3 {
+            now += Duration::from_millis(steps * 10);
+            times.record(steps, now);
+        }
+        assert_eq!(times.min(), Some(Duration::from_millis(20)));
+    }
+
     #[test]
     fn test_duration_stuff() {
         let duration = Duration::new(42, 100_000_000);
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
+use std::env;
 use std::fmt::{self, Write};
 use std::mem;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
-use console::{measure_text_width, Style};
+use console::{colors_enabled, measure_text_width, Color, Style};
 #[cfg(feature = "unicode-segmentation")]
 use unicode_segmentation::UnicodeSegmentation;
 #[cfg(target_arch = "wasm32")]
@@ -15,19 +18,42 @@ use crate::format::{
     BinaryBytes, DecimalBytes, FormattedDuration, HumanBytes, HumanCount, HumanDuration,
     HumanFloatCount,
 };
-use crate::state::{ProgressState, TabExpandedString, DEFAULT_TAB_WIDTH};
+use crate::state::{ItemTimes, ProgressState, SmoothedRate, TabExpandedString, DEFAULT_TAB_WIDTH};
 
 #[derive(Clone)]
 pub struct ProgressStyle {
     tick_strings: Vec<Box<str>>,
     progress_chars: Vec<Box<str>>,
     template: Template,
+    // compact template used when the target is narrower than the given width
+    narrow_template: Option<(u16, Template)>,
     // how unicode-big each char in progress_chars is
     char_width: usize,
+    bar_resolution: BarResolution,
     tab_width: usize,
+    gutter_width: usize,
+    // divisor and suffix used to render `{pos}` and `{len}`
+    unit_scale: Option<(u64, Box<str>)>,
+    message_overflow: Overflow,
+    // supplies per-cell intensities rendered by `{bar}` instead of the fill
+    cell_source: Option<Arc<CellSourceFn>>,
+    // the global range covered by this bar, used to render `{percent}`
+    percent_range: Option<(f32, f32)>,
     pub(crate) format_map: HashMap<&'static str, Box<dyn ProgressTracker>>,
+    pub(crate) item_times: Option<ItemTimes>,
+    pub(crate) eta_smoothing: EtaSmoothing,
+    pub(crate) rate_window: RateWindow,
+    pub(crate) smoothed_rate: SmoothedRate,
+    // render keys that depend on the length as empty if it is unknown
+    hide_length_dependent_keys: bool,
+    // the bar is rendered in red once the number of failures exceeds this
+    failure_threshold: Option<u64>,
+    // colors the filled part of the bar passes through from start to end
+    bar_gradient: Vec<Color>,
 }
 
+type CellSourceFn = dyn Fn(&ProgressState, usize) -> Vec<f32> + Send + Sync;
+
 #[cfg(feature = "unicode-segmentation")]
 fn segment(s: &str) -> Vec<Box<str>> {
     UnicodeSegmentation::graphemes(s, true)
@@ -50,19 +76,19 @@ fn measure(s: &str) -> usize {
     s.chars().count()
 }
 
-/// finds the unicode-aware width of the passed grapheme cluters
-/// panics on an empty parameter, or if the characters are not equal-width
-fn width(c: &[Box<str>]) -> usize {
-    c.iter()
-        .map(|s| measure(s.as_ref()))
-        .fold(None, |acc, new| {
-            match acc {
-                None => return Some(new),
-                Some(old) => assert_eq!(old, new, "got passed un-equal width progress characters"),
-            }
-            acc
-        })
-        .unwrap()
+/// Pads the passed grapheme clusters to equal width and returns that width
+///
+/// Narrower clusters are padded with spaces on the right, so that e.g. emoji and ASCII characters
+/// can be mixed. Panics on an empty parameter.
+fn pad_to_equal_width(c: &mut [Box<str>]) -> usize {
+    let width = c.iter().map(|s| measure(s)).max().unwrap();
+    for s in c.iter_mut() {
+        let pad = width - measure(s);
+        if pad > 0 {
+            *s = format!("{s}{}", " ".repeat(pad)).into();
+        }
+    }
+    width
 }
 
 impl ProgressStyle {
@@ -76,6 +102,13 @@ impl ProgressStyle {
         Self::new(Template::from_str("{spinner} {msg}").unwrap())
     }
 
+    /// Returns the default progress bar style for timers
+    ///
+    /// See [`ProgressBar::new_timer()`](crate::ProgressBar::new_timer).
+    pub fn default_timer() -> Self {
+        Self::new(Template::from_str("{wide_bar} {elapsed_precise}/{duration_precise}").unwrap())
+    }
+
     /// Sets the template string for the progress bar
     ///
     /// Review the [list of template keys](../index.html#templates) for more information.
@@ -83,14 +116,33 @@ impl ProgressStyle {
         Ok(Self::new(Template::from_str(template)?))
     }
 
+    /// Sets the template string for the progress bar, rejecting anything it can't render
+    ///
+    /// Unlike [`ProgressStyle::with_template()`], which renders unknown keys as empty and
+    /// ignores unknown style names, this returns an error for keys that aren't in the
+    /// [list of template keys](../index.html#templates), invalid style strings and unterminated
+    /// placeholders. Use it to validate templates from configuration files. To allow custom
+    /// keys, add them with [`ProgressStyle::with_key()`] and then call
+    /// [`ProgressStyle::template_strict()`].
+    pub fn with_template_strict(template: &str) -> Result<Self, TemplateError> {
+        Ok(Self::new(Template::parse_strict(
+            template,
+            DEFAULT_TAB_WIDTH,
+            |_| false,
+        )?))
+    }
+
     pub(crate) fn set_tab_width(&mut self, new_tab_width: usize) {
         self.tab_width = new_tab_width;
         self.template.set_tab_width(new_tab_width);
+        if let Some((_, template)) = &mut self.narrow_template {
+            template.set_tab_width(new_tab_width);
+        }
     }
 
     fn new(template: Template) -> Self {
-        let progress_chars = segment("█░");
-        let char_width = width(&progress_chars);
+        let mut progress_chars = segment("█░");
+        let char_width = pad_to_equal_width(&mut progress_chars);
         Self {
             tick_strings: "⠁⠁⠉⠙⠚⠒⠂⠂⠒⠲⠴⠤⠄⠄⠤⠠⠠⠤⠦⠖⠒⠐⠐⠒⠓⠋⠉⠈⠈ "
                 .chars()
@@ -98,9 +150,23 @@ impl ProgressStyle {
                 .collect(),
             progress_chars,
             char_width,
+            bar_resolution: BarResolution::default(),
             template,
+            narrow_template: None,
             format_map: HashMap::default(),
             tab_width: DEFAULT_TAB_WIDTH,
+            gutter_width: DEFAULT_GUTTER_WIDTH,
+            unit_scale: None,
+            message_overflow: Overflow::default(),
+            cell_source: None,
+            percent_range: None,
+            item_times: None,
+            eta_smoothing: EtaSmoothing::default(),
+            rate_window: RateWindow::default(),
+            smoothed_rate: SmoothedRate::new(DEFAULT_SMOOTHING_ALPHA),
+            hide_length_dependent_keys: false,
+            failure_threshold: None,
+            bar_gradient: Vec::new(),
         }
     }
 
@@ -137,7 +203,8 @@ impl ProgressStyle {
     /// Sets the progress characters `(filled, current, to do)`
     ///
     /// You can pass more than three for a more detailed display.
-    /// All passed grapheme clusters need to be of equal width.
+    /// Grapheme clusters of differing widths (e.g. `"🟩🟨⬜"` mixed with ASCII) are padded with
+    /// spaces to the width of the widest one.
     pub fn progress_chars(mut self, s: &str) -> Self {
         self.progress_chars = segment(s);
         // Format bar will panic with some potentially confusing message, better to panic here
@@ -146,16 +213,158 @@ impl ProgressStyle {
             self.progress_chars.len() >= 2,
             "at least 2 progress chars required"
         );
-        self.char_width = width(&self.progress_chars);
+        self.char_width = pad_to_equal_width(&mut self.progress_chars);
+        self
+    }
+
+    /// Sets how finely the bar shows progress within a single cell
+    ///
+    /// With [`BarResolution::Eighths`], the bar is drawn with full blocks and the last cell shows
+    /// the remaining progress in eighths (`▏▎▍▌▋▊▉`), regardless of the fine-grained [progress
+    /// chars][Self::progress_chars()]. Only the last progress char is still used, for the part
+    /// that is yet to be done. This makes small increments visible on short bars.
+    pub fn bar_resolution(mut self, resolution: BarResolution) -> Self {
+        self.bar_resolution = resolution;
+        self
+    }
+
+    /// Renders the bar from per-cell intensities instead of the completion fraction
+    ///
+    /// `f` receives the state and the number of cells in the bar, and returns an intensity
+    /// between 0 and 1 for each cell, e.g. the completeness of the shard shown in that cell.
+    /// Intensities are mapped onto the [progress chars][Self::progress_chars()], from the first
+    /// (1.0) to the last one (0.0), so a shading sequence like `"█▓▒░ "` works best. Missing
+    /// values are rendered as empty cells.
+    ///
+    /// ```rust
+    /// # use indicatif::{ProgressState, ProgressStyle};
+    /// let style = ProgressStyle::with_template("{wide_bar} {pos}/{len}")
+    ///     .unwrap()
+    ///     .progress_chars("█▓▒░ ")
+    ///     .with_cell_source(|_: &ProgressState, cells| {
+    ///         (0..cells).map(|i| i as f32 / cells as f32).collect()
+    ///     });
+    /// ```
+    pub fn with_cell_source(
+        mut self,
+        f: impl Fn(&ProgressState, usize) -> Vec<f32> + Send + Sync + 'static,
+    ) -> Self {
+        self.cell_source = Some(Arc::new(f));
+        self
+    }
+
+    /// Maps `{percent}` and `{percent_precise}` into the range from `start` to `end`
+    ///
+    /// `start` and `end` are fractions of a larger job covered by this progress bar. For example,
+    /// if this stage covers 40–70% of a pipeline, `percent_of(0.4, 0.7)` renders `{percent}` as 40
+    /// when the bar starts and as 70 when it is complete. The bar itself still shows the local
+    /// progress.
+    pub fn percent_of(mut self, start: f32, end: f32) -> Self {
+        self.percent_range = Some((start, end));
         self
     }
 
+    /// The fraction rendered by `{percent}`, see [`ProgressStyle::percent_of`]
+    fn percent_fraction(&self, state: &ProgressState) -> f32 {
+        let fraction = state.fraction();
+        match self.percent_range {
+            Some((start, end)) => start + (end - start) * fraction,
+            None => fraction,
+        }
+    }
+
     /// Adds a custom key that owns a [`ProgressTracker`] to the template
     pub fn with_key<S: ProgressTracker + 'static>(mut self, key: &'static str, f: S) -> Self {
         self.format_map.insert(key, Box::new(f));
         self
     }
 
+    /// Enables per-item duration statistics over the last `window` updates
+    ///
+    /// This is required for the `{min_item_time}`, `{max_item_time}` and `{p95_item_time}` keys
+    /// to render anything. Every update that advances the position records the average time per
+    /// item since the previous update; only the most recent `window` samples are retained, so
+    /// memory use stays bounded.
+    pub fn item_time_stats(mut self, window: usize) -> Self {
+        self.item_times = Some(ItemTimes::new(window));
+        self
+    }
+
+    /// Renders `{bar}` and `{wide_bar}` in red once more than `threshold` items failed
+    ///
+    /// This overrides the style of the bar set in the template. See
+    /// [`ProgressBar::inc_failures()`](crate::ProgressBar::inc_failures).
+    pub fn failure_threshold(mut self, threshold: u64) -> Self {
+        self.failure_threshold = Some(threshold);
+        self
+    }
+
+    /// Colors the filled part of `{bar}` and `{wide_bar}` with a sequence of colors
+    ///
+    /// The bar is split into as many equally wide sections as there are colors, and the filled
+    /// cells of each section are drawn in its color, so that a gradient like
+    /// `[Color::Red, Color::Yellow, Color::Green]` turns greener as progress is made. The part
+    /// that is yet to be done keeps the alternative style of the template. Bars rendered with
+    /// [`ProgressStyle::with_cell_source()`] are not colored.
+    ///
+    /// ```rust
+    /// # use console::Color;
+    /// # use indicatif::ProgressStyle;
+    /// let style = ProgressStyle::with_template("{bar:40} {pos}/{len}")
+    ///     .unwrap()
+    ///     .bar_gradient(&[Color::Red, Color::Yellow, Color::Green]);
+    /// ```
+    pub fn bar_gradient(mut self, colors: &[Color]) -> Self {
+        self.bar_gradient = colors.to_vec();
+        self
+    }
+
+    /// Sets whether keys that depend on the length are empty if the length is unknown (default:
+    /// false)
+    ///
+    /// Without a length, as with spinners, the progress bar cannot estimate its remaining time
+    /// or completion percentage, so these keys are rendered like for a progress bar at 0%. When
+    /// enabled, `{bar}`, `{wide_bar}`, `{percent}`, `{eta}`, `{duration}`,
+    /// `{time_left_or_total}` and their variants are instead rendered as empty while the length
+    /// is unknown (the latter still shows the elapsed time once finished), so a single template
+    /// works for both progress bars and spinners.
+    pub fn hide_length_dependent_keys(mut self, hide: bool) -> Self {
+        self.hide_length_dependent_keys = hide;
+        self
+    }
+
+    /// Sets the smoothing factor for `{smoothed_per_sec}` and `{smoothed_eta}` (default: 0.3)
+    ///
+    /// These keys smooth the rate measured at each update twice with the factor `alpha`, between
+    /// 0 and 1. Lower values give steadier estimates when the rate fluctuates a lot between
+    /// updates, for example between batches of a training loop, while higher values follow
+    /// changes in the rate more quickly.
+    pub fn smoothing_alpha(mut self, alpha: f64) -> Self {
+        self.smoothed_rate = SmoothedRate::new(alpha);
+        self
+    }
+
+    /// Sets how the rate behind `{eta}`, `{per_sec}` and related keys is smoothed
+    ///
+    /// See [`EtaSmoothing`] for the available strategies. Will panic if the half-life of
+    /// [`EtaSmoothing::ExponentialDecay`] is zero.
+    pub fn with_eta_smoothing(mut self, smoothing: EtaSmoothing) -> Self {
+        // The estimator would divide by zero and render NaN, better to panic here with a message
+        // explicitly informing of the problem
+        let EtaSmoothing::ExponentialDecay { half_life } = smoothing;
+        assert!(!half_life.is_zero(), "half-life must be greater than zero");
+        self.eta_smoothing = smoothing;
+        self
+    }
+
+    /// Sets the window over which `{per_sec}`, `{bytes_per_sec}` and related keys measure the rate
+    ///
+    /// `{eta}` keeps using the smoothed rate, see [`ProgressStyle::with_eta_smoothing()`].
+    pub fn rate_window(mut self, window: RateWindow) -> Self {
+        self.rate_window = window;
+        self
+    }
+
     /// Sets the template string for the progress bar
     ///
     /// Review the [list of template keys](../index.html#templates) for more information.
@@ -164,13 +373,163 @@ impl ProgressStyle {
         Ok(self)
     }
 
+    /// Sets the template string for the progress bar, rejecting anything it can't render
+    ///
+    /// Keys added with [`ProgressStyle::with_key()`] before calling this are accepted in
+    /// addition to the built-in ones. See [`ProgressStyle::with_template_strict()`].
+    pub fn template_strict(mut self, s: &str) -> Result<Self, TemplateError> {
+        let format_map = &self.format_map;
+        self.template =
+            Template::parse_strict(s, self.tab_width, |key| format_map.contains_key(key))?;
+        Ok(self)
+    }
+
+    /// Sets a compact template that is used when the target is narrower than `min_width` columns
+    ///
+    /// This keeps progress bars legible in narrow terminals without any application-side logic.
+    /// Review the [list of template keys](../index.html#templates) for more information.
+    pub fn with_narrow_template(
+        mut self,
+        min_width: u16,
+        template: &str,
+    ) -> Result<Self, TemplateError> {
+        let template = Template::from_str_with_tab_width(template, self.tab_width)?;
+        self.narrow_template = Some((min_width, template));
+        Ok(self)
+    }
+
+    /// Sets the width of the gutter rendered by the `{gutter}` key (default: 12)
+    ///
+    /// The gutter renders the prefix right-aligned in a fixed-width column, like the verbs in
+    /// cargo's output. Using the same gutter width for all bars of a [`MultiProgress`] and
+    /// printing log lines with [`ProgressBar::println_with_gutter()`] keeps a consistent left
+    /// edge for the text that follows.
+    ///
+    /// [`MultiProgress`]: crate::MultiProgress
+    /// [`ProgressBar::println_with_gutter()`]: crate::ProgressBar::println_with_gutter
+    pub fn with_gutter(mut self, width: usize) -> Self {
+        self.gutter_width = width;
+        self
+    }
+
+    /// Renders `{pos}` and `{len}` divided by `scale`, with one decimal and the given suffix
+    ///
+    /// For example, `with_unit_scale(1000, "k")` renders a position of 12345 as `12.3k`. Unlike
+    /// `{human_pos}`, which groups digits, this keeps large counts (e.g. row counts in the
+    /// millions) short. A `scale` of 0 is treated as 1.
+    pub fn with_unit_scale(mut self, scale: u64, suffix: &str) -> Self {
+        self.unit_scale = Some((Ord::max(scale, 1), suffix.into()));
+        self
+    }
+
+    /// Sets how `{wide_msg}` handles messages wider than the available space
+    ///
+    /// Messages are truncated by default.
+    pub fn message_overflow(mut self, overflow: Overflow) -> Self {
+        self.message_overflow = overflow;
+        self
+    }
+
+    fn write_count(&self, buf: &mut String, count: u64) {
+        match &self.unit_scale {
+            Some((scale, suffix)) => buf
+                .write_fmt(format_args!("{:.1}{suffix}", count as f64 / *scale as f64))
+                .unwrap(),
+            None => buf.write_fmt(format_args!("{count}")).unwrap(),
+        }
+    }
+
+    /// Writes a numeric key with the number format of its placeholder
+    ///
+    /// Returns `false` if the key isn't rendered as a plain number, or if the number format
+    /// doesn't change how it's rendered.
+    fn write_number(
+        &self,
+        buf: &mut String,
+        key: &str,
+        state: &ProgressState,
+        thousands: bool,
+        precision: Option<u8>,
+    ) -> bool {
+        let precision = precision.map(usize::from);
+        let count = match key {
+            "pos" => Some(state.pos()),
+            "len" => Some(state.len().unwrap_or(state.pos())),
+            "session_pos" => Some(state.session_pos()),
+            "failures" => Some(state.failures()),
+            "successes" => Some(state.successes()),
+            _ => None,
+        };
+
+        let (num, suffix, thousands) = match (count, &self.unit_scale) {
+            (Some(count), Some((scale, suffix))) => {
+                let num = format!("{:.1}", count as f64 / *scale as f64);
+                (num, &**suffix, thousands)
+            }
+            (Some(count), None) => (count.to_string(), "", thousands),
+            (None, _) => {
+                let percent = f64::from(self.percent_fraction(state)) * 100.0;
+                // Rates are always rendered with thousands separators, so only the precision
+                // changes how they look
+                let (value, default_precision, suffix, thousands) = match (key, precision) {
+                    ("percent", _) => (percent, 0, "", thousands),
+                    ("percent_precise", _) => (percent, 3, "", thousands),
+                    ("per_sec", Some(_)) => (state.per_sec(), 0, "/s", true),
+                    ("smoothed_per_sec", Some(_)) => (self.smoothed_rate.per_sec(), 0, "/s", true),
+                    _ => return false,
+                };
+                let num = format!("{:.*}", precision.unwrap_or(default_precision), value);
+                (num, suffix, thousands)
+            }
+        };
+
+        let num = match thousands {
+            true => group_thousands(&num),
+            false => num,
+        };
+        buf.push_str(&num);
+        buf.push_str(suffix);
+        true
+    }
+
+    /// Formats `label` in the gutter followed by `msg`, using the style of the `{gutter}` key
+    pub(crate) fn format_gutter_line(&self, label: &str, msg: &str) -> String {
+        let padded = PaddedStringDisplay {
+            str: label,
+            width: self.gutter_width,
+            align: Alignment::Right,
+            fill: ' ',
+            truncate: false,
+        };
+
+        let style = self.template.parts.iter().find_map(|part| match part {
+            TemplatePart::Placeholder { key, style, .. } if key == "gutter" => style.as_ref(),
+            _ => None,
+        });
+
+        match style {
+            Some(style) => format!("{} {msg}", style.apply_to(padded)),
+            None => format!("{padded} {msg}"),
+        }
+    }
+
     fn current_tick_str(&self, state: &ProgressState) -> &str {
+        &self.tick_strings[self.current_frame_index(state)]
+    }
+
+    /// The index of the spinner frame for `state`, the last one being the finished frame
+    fn current_frame_index(&self, state: &ProgressState) -> usize {
         match state.is_finished() {
-            true => self.get_final_tick_str(),
-            false => self.get_tick_str(state.tick),
+            true => self.tick_strings.len() - 1,
+            false => (state.tick as usize) % (self.tick_strings.len() - 1),
         }
     }
 
+    /// Returns the number of spinner frames, not including the final frame
+    pub fn spinner_frame_count(&self) -> usize {
+        self.tick_strings.len() - 1
+    }
+
     /// Returns the tick string for a given number
     pub fn get_tick_str(&self, idx: u64) -> &str {
         &self.tick_strings[(idx as usize) % (self.tick_strings.len() - 1)]
@@ -181,9 +540,23 @@ impl ProgressStyle {
         &self.tick_strings[self.tick_strings.len() - 1]
     }
 
-    fn format_bar(&self, fract: f32, width: usize, alt_style: Option<&Style>) -> BarDisplay<'_> {
+    fn format_bar<'a>(
+        &'a self,
+        state: &ProgressState,
+        width: usize,
+        alt_style: Option<&'a TemplateStyle>,
+    ) -> BarDisplay<'a> {
         // The number of clusters from progress_chars to write (rounding down).
         let width = width / self.char_width;
+        if let Some(source) = &self.cell_source {
+            return self.format_cells(source(state, width), width);
+        }
+
+        let fract = state.fraction();
+        if self.bar_resolution == BarResolution::Eighths {
+            return self.format_eighths(fract, width, alt_style);
+        }
+
         // The number of full clusters (including a fractional component for a partially-full one).
         let fill = fract * width as f32;
         // The number of entirely full clusters (by truncating `fill`).
@@ -191,6 +564,11 @@ impl ProgressStyle {
         // 1 if the bar is not entirely empty or full (meaning we need to draw the "current"
         // character between the filled and "to do" segment), 0 otherwise.
         let head = usize::from(fill > 0.0 && entirely_filled < width);
+        // Without a "current" entry the head is a "to do" entry, which a gradient shouldn't color
+        let head = match self.progress_chars.len() {
+            2 if !self.bar_gradient.is_empty() => 0,
+            _ => head,
+        };
 
         let cur = if head == 1 {
             // Number of fine-grained progress entries in progress_chars.
@@ -218,9 +596,79 @@ impl ProgressStyle {
 
         BarDisplay {
             chars: &self.progress_chars,
+            cells: Vec::new(),
             filled: entirely_filled,
             cur,
-            rest: alt_style.unwrap_or(&Style::new()).apply_to(rest),
+            gradient: &self.bar_gradient,
+            width,
+            rest: StyledDisplay {
+                style: alt_style,
+                val: rest,
+            },
+        }
+    }
+
+    fn format_eighths<'a>(
+        &'a self,
+        fract: f32,
+        width: usize,
+        alt_style: Option<&'a TemplateStyle>,
+    ) -> BarDisplay<'a> {
+        static EIGHTHS: OnceLock<Vec<Box<str>>> = OnceLock::new();
+        let chars = EIGHTHS.get_or_init(|| segment("█▉▊▋▌▍▎▏"));
+
+        // The block characters are a single column wide, wider "to do" clusters can't line up
+        // with them
+        let (todo, width) = match self.char_width {
+            1 => (&*self.progress_chars[self.progress_chars.len() - 1], width),
+            w => (" ", width * w),
+        };
+
+        // The number of filled eighths, rounding down so that the bar is only full when done
+        let eighths = ((fract * (width * 8) as f32) as usize).min(width * 8);
+        let filled = eighths / 8;
+        // Index of the partially filled cell in `chars`, from 7/8 (1) down to 1/8 (7)
+        let cur = match eighths % 8 {
+            0 => None,
+            part => Some(8 - part),
+        };
+
+        let bg = width - filled - usize::from(cur.is_some());
+        let rest = RepeatedStringDisplay { str: todo, num: bg };
+
+        BarDisplay {
+            chars,
+            cells: Vec::new(),
+            filled,
+            cur,
+            gradient: &self.bar_gradient,
+            width,
+            rest: StyledDisplay {
+                style: alt_style,
+                val: rest,
+            },
+        }
+    }
+
+    fn format_cells(&self, mut values: Vec<f32>, width: usize) -> BarDisplay<'_> {
+        let levels = self.progress_chars.len() - 1;
+        values.resize(width, 0.0);
+        let cells = values
+            .iter()
+            .map(|v| levels - (v.clamp(0.0, 1.0) * levels as f32).round() as usize)
+            .collect();
+
+        BarDisplay {
+            chars: &self.progress_chars,
+            cells,
+            filled: 0,
+            cur: None,
+            gradient: &[],
+            width,
+            rest: StyledDisplay {
+                style: None,
+                val: RepeatedStringDisplay { str: "", num: 0 },
+            },
         }
     }
 
@@ -234,21 +682,67 @@ impl ProgressStyle {
         let mut buf = String::new();
         let mut wide = None;
 
+        let template = match &self.narrow_template {
+            Some((min_width, template)) if target_width < *min_width => template,
+            _ => &self.template,
+        };
+
         let pos = state.pos();
         let len = state.len().unwrap_or(pos);
-        for part in &self.template.parts {
+        let hide_length_dependent = self.hide_length_dependent_keys && state.len().is_none();
+        let failure_style = match self.failure_threshold {
+            Some(threshold) if state.failures() > threshold => {
+                Some(TemplateStyle::from(Style::new().red()))
+            }
+            _ => None,
+        };
+        // for every enclosing `{if}`: whether its parent is shown and whether its condition holds
+        let mut conditions: Vec<(bool, bool)> = Vec::new();
+        for part in &template.parts {
+            let shown = conditions
+                .last()
+                .map_or(true, |&(parent, cond)| parent && cond);
+            match part {
+                TemplatePart::If { condition, negate } => {
+                    conditions.push((shown, condition.eval(state) != *negate));
+                    continue;
+                }
+                TemplatePart::Else => {
+                    if let Some((_, cond)) = conditions.last_mut() {
+                        *cond = !*cond;
+                    }
+                    continue;
+                }
+                TemplatePart::EndIf => {
+                    conditions.pop();
+                    continue;
+                }
+                _ if !shown => continue,
+                _ => {}
+            }
+
             match part {
                 TemplatePart::Placeholder {
                     key,
                     align,
                     width,
+                    fill,
                     truncate,
+                    wrap,
+                    thousands,
+                    precision,
                     style,
                     alt_style,
                 } => {
                     buf.clear();
                     if let Some(tracker) = self.format_map.get(key.as_str()) {
                         tracker.write(state, &mut TabRewriter(&mut buf, self.tab_width));
+                    } else if hide_length_dependent && is_length_dependent(key, state) {
+                        // rendered as empty without a length
+                    } else if (*thousands || precision.is_some())
+                        && self.write_number(&mut buf, key, state, *thousands, *precision)
+                    {
+                        // rendered with the number format of the placeholder
                     } else {
                         match key.as_str() {
                             "wide_bar" => {
@@ -259,34 +753,68 @@ impl ProgressStyle {
                                 .write_fmt(format_args!(
                                     "{}",
                                     self.format_bar(
-                                        state.fraction(),
+                                        state,
                                         width.unwrap_or(20) as usize,
                                         alt_style.as_ref(),
                                     )
                                 ))
                                 .unwrap(),
                             "spinner" => buf.push_str(self.current_tick_str(state)),
+                            "spinner_frame_index" => buf
+                                .write_fmt(format_args!("{}", self.current_frame_index(state)))
+                                .unwrap(),
                             "wide_msg" => {
-                                wide = Some(WideElement::Message { align });
+                                wide = Some(WideElement::Message { align, wrap: *wrap });
                                 buf.push('\x00');
                             }
                             "msg" => buf.push_str(state.message.expanded()),
                             "prefix" => buf.push_str(state.prefix.expanded()),
-                            "pos" => buf.write_fmt(format_args!("{pos}")).unwrap(),
+                            "gutter" => buf
+                                .write_fmt(format_args!(
+                                    "{}",
+                                    PaddedStringDisplay {
+                                        str: state.prefix.expanded(),
+                                        width: self.gutter_width,
+                                        align: Alignment::Right,
+                                        fill: ' ',
+                                        truncate: false,
+                                    }
+                                ))
+                                .unwrap(),
+                            "id" => buf.push_str(state.id().unwrap_or_default()),
+                            "trend" => buf.push_str(state.trend_str()),
+                            "finish_reason" => buf.push_str(
+                                state.finish_reason().map_or("", |reason| reason.as_str()),
+                            ),
+                            "pos" => self.write_count(&mut buf, pos),
+                            "failures" => self.write_count(&mut buf, state.failures()),
+                            "successes" => self.write_count(&mut buf, state.successes()),
+                            "session_pos" => self.write_count(&mut buf, state.session_pos()),
                             "human_pos" => {
                                 buf.write_fmt(format_args!("{}", HumanCount(pos))).unwrap();
                             }
-                            "len" => buf.write_fmt(format_args!("{len}")).unwrap(),
+                            "len" => self.write_count(&mut buf, len),
                             "human_len" => {
                                 buf.write_fmt(format_args!("{}", HumanCount(len))).unwrap();
                             }
                             "percent" => buf
-                                .write_fmt(format_args!("{:.*}", 0, state.fraction() * 100f32))
+                                .write_fmt(format_args!(
+                                    "{:.*}",
+                                    0,
+                                    self.percent_fraction(state) * 100f32
+                                ))
                                 .unwrap(),
                             "percent_precise" => buf
-                                .write_fmt(format_args!("{:.*}", 3, state.fraction() * 100f32))
+                                .write_fmt(format_args!(
+                                    "{:.*}",
+                                    3,
+                                    self.percent_fraction(state) * 100f32
+                                ))
                                 .unwrap(),
                             "bytes" => buf.write_fmt(format_args!("{}", HumanBytes(pos))).unwrap(),
+                            "session_bytes" => buf
+                                .write_fmt(format_args!("{}", HumanBytes(state.session_pos())))
+                                .unwrap(),
                             "total_bytes" => {
                                 buf.write_fmt(format_args!("{}", HumanBytes(len))).unwrap();
                             }
@@ -308,6 +836,18 @@ impl ProgressStyle {
                             "elapsed" => buf
                                 .write_fmt(format_args!("{:#}", HumanDuration(state.elapsed())))
                                 .unwrap(),
+                            "multi_elapsed_precise" => buf
+                                .write_fmt(format_args!(
+                                    "{}",
+                                    FormattedDuration(state.multi_elapsed())
+                                ))
+                                .unwrap(),
+                            "multi_elapsed" => buf
+                                .write_fmt(format_args!(
+                                    "{:#}",
+                                    HumanDuration(state.multi_elapsed())
+                                ))
+                                .unwrap(),
                             "per_sec" => buf
                                 .write_fmt(format_args!("{}/s", HumanFloatCount(state.per_sec())))
                                 .unwrap(),
@@ -326,28 +866,72 @@ impl ProgressStyle {
                                     BinaryBytes(state.per_sec() as u64)
                                 ))
                                 .unwrap(),
+                            "smoothed_per_sec" => buf
+                                .write_fmt(format_args!(
+                                    "{}/s",
+                                    HumanFloatCount(self.smoothed_rate.per_sec())
+                                ))
+                                .unwrap(),
+                            "smoothed_eta" => buf
+                                .write_fmt(format_args!(
+                                    "{:#}",
+                                    HumanDuration(self.smoothed_rate.eta(state))
+                                ))
+                                .unwrap(),
                             "eta_precise" => buf
                                 .write_fmt(format_args!("{}", FormattedDuration(state.eta())))
                                 .unwrap(),
                             "eta" => buf
                                 .write_fmt(format_args!("{:#}", HumanDuration(state.eta())))
                                 .unwrap(),
+                            "time_left_or_total_precise" => {
+                                let time = match state.is_finished() {
+                                    true => state.elapsed(),
+                                    false => state.eta(),
+                                };
+                                buf.write_fmt(format_args!("{}", FormattedDuration(time)))
+                                    .unwrap()
+                            }
+                            "time_left_or_total" => {
+                                let time = match state.is_finished() {
+                                    true => state.elapsed(),
+                                    false => state.eta(),
+                                };
+                                buf.write_fmt(format_args!("{:#}", HumanDuration(time)))
+                                    .unwrap()
+                            }
                             "duration_precise" => buf
                                 .write_fmt(format_args!("{}", FormattedDuration(state.duration())))
                                 .unwrap(),
                             "duration" => buf
                                 .write_fmt(format_args!("{:#}", HumanDuration(state.duration())))
                                 .unwrap(),
+                            "min_item_time" => {
+                                write_item_time(&mut buf, self.item_times.as_ref(), |t| t.min());
+                            }
+                            "max_item_time" => {
+                                write_item_time(&mut buf, self.item_times.as_ref(), |t| t.max());
+                            }
+                            "p95_item_time" => {
+                                write_item_time(&mut buf, self.item_times.as_ref(), |t| {
+                                    t.quantile(0.95)
+                                });
+                            }
                             _ => (),
                         }
                     };
 
+                    let style = match key.as_str() {
+                        "bar" | "wide_bar" if failure_style.is_some() => &failure_style,
+                        _ => style,
+                    };
                     match width {
                         Some(width) => {
                             let padded = PaddedStringDisplay {
                                 str: &buf,
                                 width: *width as usize,
                                 align: *align,
+                                fill: *fill,
                                 truncate: *truncate,
                             };
                             match style {
@@ -367,6 +951,7 @@ impl ProgressStyle {
                 TemplatePart::NewLine => {
                     self.push_line(lines, &mut cur, state, &mut buf, target_width, &wide);
                 }
+                TemplatePart::If { .. } | TemplatePart::Else | TemplatePart::EndIf => {}
             }
         }
 
@@ -405,6 +990,37 @@ impl ProgressStyle {
     }
 }
 
+/// Whether the key cannot be rendered meaningfully without a length
+fn is_length_dependent(key: &str, state: &ProgressState) -> bool {
+    match key {
+        "bar" | "wide_bar" | "percent" | "percent_precise" | "eta" | "eta_precise" | "duration"
+        | "duration_precise" | "smoothed_eta" => true,
+        "time_left_or_total" | "time_left_or_total_precise" => !state.is_finished(),
+        _ => false,
+    }
+}
+
+fn write_item_time(
+    buf: &mut String,
+    item_times: Option<&ItemTimes>,
+    f: impl FnOnce(&ItemTimes) -> Option<Duration>,
+) {
+    let d = match item_times.and_then(f) {
+        Some(d) => d,
+        None => return,
+    };
+
+    // Item times are frequently sub-second, which `HumanDuration` would round to zero
+    if d < Duration::from_millis(1) {
+        buf.write_fmt(format_args!("{}µs", d.as_micros())).unwrap();
+    } else if d < Duration::from_secs(1) {
+        buf.write_fmt(format_args!("{}ms", d.as_millis())).unwrap();
+    } else {
+        buf.write_fmt(format_args!("{:#}", HumanDuration(d)))
+            .unwrap();
+    }
+}
+
 struct TabRewriter<'a>(&'a mut dyn fmt::Write, usize);
 
 impl Write for TabRewriter<'_> {
@@ -416,8 +1032,13 @@ impl Write for TabRewriter<'_> {
 
 #[derive(Clone, Copy)]
 enum WideElement<'a> {
-    Bar { alt_style: &'a Option<Style> },
-    Message { align: &'a Alignment },
+    Bar {
+        alt_style: &'a Option<TemplateStyle>,
+    },
+    Message {
+        align: &'a Alignment,
+        wrap: Option<u16>,
+    },
 }
 
 impl WideElement<'_> {
@@ -433,19 +1054,31 @@ impl WideElement<'_> {
         match self {
             Self::Bar { alt_style } => cur.replace(
                 '\x00',
-                &format!(
-                    "{}",
-                    style.format_bar(state.fraction(), left, alt_style.as_ref())
-                ),
+                &format!("{}", style.format_bar(state, left, alt_style.as_ref())),
             ),
-            WideElement::Message { align } => {
+            WideElement::Message { align, wrap } => {
+                let msg = state.message.expanded();
+                if let Some(max_lines) = wrap {
+                    if left > 0 && measure_text_width(msg) > left {
+                        return wrap_message(cur, msg, left, *align, max_lines.into());
+                    }
+                }
+
+                if let Overflow::Marquee { speed } = style.message_overflow {
+                    if measure_text_width(msg) > left {
+                        let offset = (state.tick as usize).wrapping_mul(speed);
+                        return cur.replace('\x00', &marquee(msg, left, offset));
+                    }
+                }
+
                 buf.clear();
                 buf.write_fmt(format_args!(
                     "{}",
                     PaddedStringDisplay {
-                        str: state.message.expanded(),
+                        str: msg,
                         width: left,
                         align: *align,
+                        fill: ' ',
                         truncate: true,
                     }
                 ))
@@ -469,6 +1102,33 @@ struct Template {
 
 impl Template {
     fn from_str_with_tab_width(s: &str, tab_width: usize) -> Result<Self, TemplateError> {
+        Self::parse(s, tab_width, false)
+    }
+
+    /// Parses a template, rejecting unknown keys, invalid styles and unterminated placeholders
+    ///
+    /// `is_custom_key` decides whether keys that aren't built in are accepted.
+    fn parse_strict(
+        s: &str,
+        tab_width: usize,
+        is_custom_key: impl Fn(&str) -> bool,
+    ) -> Result<Self, TemplateError> {
+        let template = Self::parse(s, tab_width, true)?;
+        for part in &template.parts {
+            if let TemplatePart::Placeholder { key, .. } = part {
+                if !KNOWN_KEYS.contains(&key.as_str()) && !is_custom_key(key) {
+                    return Err(TemplateError {
+                        kind: TemplateErrorKind::UnknownKey(key.clone()),
+                        state: State::Key,
+                    });
+                }
+            }
+        }
+
+        Ok(template)
+    }
+
+    fn parse(s: &str, tab_width: usize, strict: bool) -> Result<Self, TemplateError> {
         use State::*;
         let (mut state, mut parts, mut buf) = (Literal, vec![], String::new());
         for c in s.chars() {
@@ -488,6 +1148,29 @@ impl Template {
                 (Literal, c) => (Literal, Some(c)),
                 (DoubleClose, '}') => (Literal, None),
                 (MaybeOpen, '{') => (Literal, Some('{')),
+                (Key, ' ') if buf == "if" => {
+                    buf.clear();
+                    (Condition, None)
+                }
+                (Condition, '}') => {
+                    let (negate, name) = match buf.strip_prefix('!') {
+                        Some(name) => (true, name),
+                        None => (false, buf.as_str()),
+                    };
+                    let condition = match TemplateCondition::from_name(name) {
+                        Some(condition) => condition,
+                        None => {
+                            return Err(TemplateError {
+                                kind: TemplateErrorKind::UnknownCondition(buf),
+                                state,
+                            })
+                        }
+                    };
+                    buf.clear();
+                    parts.push(TemplatePart::If { condition, negate });
+                    (Literal, None)
+                }
+                (Condition, c) => (Condition, Some(c)),
                 (MaybeOpen | Key, c) if c.is_ascii_whitespace() => {
                     // If we find whitespace where the variable key is supposed to go,
                     // backtrack and act as if this was a literal.
@@ -510,7 +1193,11 @@ impl Template {
                         key: mem::take(&mut buf),
                         align: Alignment::Left,
                         width: None,
+                        fill: ' ',
                         truncate: true,
+                        wrap: None,
+                        thousands: false,
+                        precision: None,
                         style: None,
                         alt_style: None,
                     });
@@ -528,6 +1215,26 @@ impl Template {
 
                     (Width, None)
                 }
+                (Align, '0') | (Width, '0') if buf.is_empty() => {
+                    // Zero padding of numbers pads on the left, ignoring the alignment like
+                    // `format!("{:<04}")`. For other keys, the `0` is part of the width.
+                    match parts.last_mut() {
+                        Some(TemplatePart::Placeholder {
+                            key, align, fill, ..
+                        }) if NUMBER_KEYS.contains(&key.as_str()) => {
+                            *align = Alignment::Right;
+                            *fill = '0';
+                            (Width, None)
+                        }
+                        _ if strict => {
+                            return Err(TemplateError {
+                                kind: TemplateErrorKind::UnexpectedChar('0'),
+                                state,
+                            })
+                        }
+                        _ => (Width, Some('0')),
+                    }
+                }
                 (Align, c @ '0'..='9') => (Width, Some(c)),
                 (Align | Width, '!') => {
                     if let Some(TemplatePart::Placeholder { truncate, .. }) = parts.last_mut() {
@@ -535,6 +1242,37 @@ impl Template {
                     }
                     (Width, None)
                 }
+                (Align | Width, ',') => {
+                    if let Some(TemplatePart::Placeholder { thousands, .. }) = parts.last_mut() {
+                        *thousands = true;
+                    }
+                    (Width, None)
+                }
+                (Align | Width, '~') => match parts.last() {
+                    Some(TemplatePart::Placeholder { key, .. })
+                        if FLOAT_KEYS.contains(&key.as_str()) =>
+                    {
+                        (Precision, None)
+                    }
+                    _ => {
+                        return Err(TemplateError {
+                            kind: TemplateErrorKind::UnexpectedChar('~'),
+                            state,
+                        })
+                    }
+                },
+                (Precision, c @ '0'..='9') => (Precision, Some(c)),
+                (Precision, '.') => (FirstStyle, None),
+                (Precision, '}') => (Literal, None),
+                (Align | Width, '+') => {
+                    if let Some(TemplatePart::Placeholder { wrap, .. }) = parts.last_mut() {
+                        *wrap = Some(u16::MAX);
+                    }
+                    (WrapLines, None)
+                }
+                (WrapLines, c @ '0'..='9') => (WrapLines, Some(c)),
+                (WrapLines, '.') => (FirstStyle, None),
+                (WrapLines, '}') => (Literal, None),
                 (Align, '.') => (FirstStyle, None),
                 (Align, '}') => (Literal, None),
                 (Width, c @ '0'..='9') => (Width, Some(c)),
@@ -545,38 +1283,90 @@ impl Template {
                 (FirstStyle, c) => (FirstStyle, Some(c)),
                 (AltStyle, '}') => (Literal, None),
                 (AltStyle, c) => (AltStyle, Some(c)),
-                (st, c) => return Err(TemplateError { next: c, state: st }),
+                (st, c) => {
+                    return Err(TemplateError {
+                        kind: TemplateErrorKind::UnexpectedChar(c),
+                        state: st,
+                    })
+                }
             };
 
             match (state, new.0) {
                 (MaybeOpen, Key) if !buf.is_empty() => parts.push(TemplatePart::Literal(
                     TabExpandedString::new(mem::take(&mut buf).into(), tab_width),
                 )),
+                (Key, Literal) if buf == "else" || buf == "endif" => {
+                    parts.push(match buf.as_str() {
+                        "else" => TemplatePart::Else,
+                        _ => TemplatePart::EndIf,
+                    });
+                    buf.clear();
+                }
                 (Key, Align | Literal) if !buf.is_empty() => {
                     parts.push(TemplatePart::Placeholder {
                         key: mem::take(&mut buf),
                         align: Alignment::Left,
                         width: None,
+                        fill: ' ',
                         truncate: false,
+                        wrap: None,
+                        thousands: false,
+                        precision: None,
                         style: None,
                         alt_style: None,
                     });
                 }
-                (Width, FirstStyle | Literal) if !buf.is_empty() => {
+                (Width, Precision | WrapLines | FirstStyle | Literal) if !buf.is_empty() => {
                     if let Some(TemplatePart::Placeholder { width, .. }) = parts.last_mut() {
-                        *width = Some(buf.parse().unwrap());
+                        *width = match buf.parse() {
+                            Ok(w) => Some(w),
+                            Err(_) => {
+                                return Err(TemplateError {
+                                    kind: TemplateErrorKind::InvalidNumber(buf),
+                                    state,
+                                })
+                            }
+                        };
+                        buf.clear();
+                    }
+                }
+                (Precision, FirstStyle | Literal) => {
+                    if let Some(TemplatePart::Placeholder { precision, .. }) = parts.last_mut() {
+                        *precision = match buf.parse() {
+                            Ok(p) => Some(p),
+                            Err(_) => {
+                                return Err(TemplateError {
+                                    kind: TemplateErrorKind::InvalidNumber(buf),
+                                    state,
+                                })
+                            }
+                        };
                         buf.clear();
                     }
                 }
+                (WrapLines, FirstStyle | Literal) if !buf.is_empty() => {
+                    if let Some(TemplatePart::Placeholder { wrap, .. }) = parts.last_mut() {
+                        *wrap = Some(buf.parse().unwrap_or(u16::MAX));
+                        buf.clear();
+                    }
+                }
+                (FirstStyle, AltStyle | Literal) | (AltStyle, Literal)
+                    if strict && !is_valid_style(&buf) =>
+                {
+                    return Err(TemplateError {
+                        kind: TemplateErrorKind::InvalidStyle(buf),
+                        state,
+                    });
+                }
                 (FirstStyle, AltStyle | Literal) if !buf.is_empty() => {
                     if let Some(TemplatePart::Placeholder { style, .. }) = parts.last_mut() {
-                        *style = Some(Style::from_dotted_str(&buf));
+                        *style = Some(TemplateStyle::from_dotted_str(&buf));
                         buf.clear();
                     }
                 }
                 (AltStyle, Literal) if !buf.is_empty() => {
                     if let Some(TemplatePart::Placeholder { alt_style, .. }) = parts.last_mut() {
-                        *alt_style = Some(Style::from_dotted_str(&buf));
+                        *alt_style = Some(TemplateStyle::from_dotted_str(&buf));
                         buf.clear();
                     }
                 }
@@ -589,6 +1379,45 @@ impl Template {
             }
         }
 
+        // every `{else}` and `{endif}` needs an open `{if}`, with at most one `{else}` each
+        let mut open = Vec::new();
+        for part in &parts {
+            let balanced = match part {
+                TemplatePart::If { .. } => {
+                    open.push(false);
+                    true
+                }
+                TemplatePart::Else => match open.last_mut() {
+                    Some(seen_else) if !*seen_else => {
+                        *seen_else = true;
+                        true
+                    }
+                    _ => false,
+                },
+                TemplatePart::EndIf => open.pop().is_some(),
+                _ => true,
+            };
+            if !balanced {
+                return Err(TemplateError {
+                    kind: TemplateErrorKind::UnbalancedCondition,
+                    state,
+                });
+            }
+        }
+        if !open.is_empty() {
+            return Err(TemplateError {
+                kind: TemplateErrorKind::UnbalancedCondition,
+                state,
+            });
+        }
+
+        if strict && state != Literal {
+            return Err(TemplateError {
+                kind: TemplateErrorKind::Unterminated,
+                state,
+            });
+        }
+
         if matches!(state, Literal | DoubleClose) && !buf.is_empty() {
             parts.push(TemplatePart::Literal(TabExpandedString::new(
                 buf.into(),
@@ -612,36 +1441,234 @@ impl Template {
     }
 }
 
+/// Returns whether every dot-separated part of `s` is a style name known to [`Style`] or a
+/// hex color
+fn is_valid_style(s: &str) -> bool {
+    s.split('.').all(|part| {
+        Rgb::parse(part.strip_prefix("on_").unwrap_or(part)).is_some()
+            || Style::from_dotted_str(part) != Style::new()
+    })
+}
+
+/// The keys whose number format can be set with `{key:,}` and `{key:.N}`
+const NUMBER_KEYS: &[&str] = &[
+    "pos",
+    "len",
+    "session_pos",
+    "failures",
+    "successes",
+    "percent",
+    "percent_precise",
+    "per_sec",
+    "smoothed_per_sec",
+];
+
+/// Keys rendered as numbers with decimals, which accept a precision (`{per_sec:~2}`)
+const FLOAT_KEYS: &[&str] = &["percent_precise", "per_sec", "smoothed_per_sec"];
+
+/// Inserts commas between groups of three digits of the integer part of `num`
+fn group_thousands(num: &str) -> String {
+    let (sign, num) = match num.strip_prefix('-') {
+        Some(num) => ("-", num),
+        None => ("", num),
+    };
+    let (int, frac) = match num.find('.') {
+        Some(idx) => num.split_at(idx),
+        None => (num, ""),
+    };
+
+    let mut grouped = String::from(sign);
+    for (idx, c) in int.chars().enumerate() {
+        if idx > 0 && (int.len() - idx) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped.push_str(frac);
+    grouped
+}
+
+/// The keys rendered by `ProgressStyle::format_state()` without a custom tracker
+const KNOWN_KEYS: &[&str] = &[
+    "wide_bar",
+    "bar",
+    "spinner",
+    "spinner_frame_index",
+    "wide_msg",
+    "msg",
+    "prefix",
+    "gutter",
+    "id",
+    "trend",
+    "finish_reason",
+    "pos",
+    "failures",
+    "successes",
+    "session_pos",
+    "human_pos",
+    "len",
+    "human_len",
+    "percent",
+    "percent_precise",
+    "bytes",
+    "session_bytes",
+    "total_bytes",
+    "decimal_bytes",
+    "decimal_total_bytes",
+    "binary_bytes",
+    "binary_total_bytes",
+    "elapsed_precise",
+    "elapsed",
+    "multi_elapsed_precise",
+    "multi_elapsed",
+    "per_sec",
+    "bytes_per_sec",
+    "decimal_bytes_per_sec",
+    "binary_bytes_per_sec",
+    "smoothed_per_sec",
+    "smoothed_eta",
+    "eta_precise",
+    "eta",
+    "time_left_or_total_precise",
+    "time_left_or_total",
+    "duration_precise",
+    "duration",
+    "min_item_time",
+    "max_item_time",
+    "p95_item_time",
+];
+
 #[derive(Debug)]
 pub struct TemplateError {
+    kind: TemplateErrorKind,
     state: State,
-    next: char,
 }
 
-impl fmt::Display for TemplateError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "TemplateError: unexpected character {:?} in state {:?}",
-            self.next, self.state
-        )
+impl TemplateError {
+    /// Returns what was wrong with the template
+    pub fn kind(&self) -> &TemplateErrorKind {
+        &self.kind
     }
 }
 
-impl std::error::Error for TemplateError {}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-enum TemplatePart {
-    Literal(TabExpandedString),
-    Placeholder {
-        key: String,
-        align: Alignment,
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TemplateErrorKind::UnexpectedChar(next) => write!(
+                f,
+                "TemplateError: unexpected character {:?} in state {:?}",
+                next, self.state
+            ),
+            TemplateErrorKind::UnknownKey(key) => {
+                write!(f, "TemplateError: unknown key {key:?}")
+            }
+            TemplateErrorKind::InvalidStyle(style) => {
+                write!(f, "TemplateError: invalid style {style:?}")
+            }
+            TemplateErrorKind::InvalidNumber(n) => write!(
+                f,
+                "TemplateError: invalid number {:?} in state {:?}",
+                n, self.state
+            ),
+            TemplateErrorKind::UnknownCondition(condition) => {
+                write!(f, "TemplateError: unknown condition {condition:?}")
+            }
+            TemplateErrorKind::UnbalancedCondition => {
+                write!(f, "TemplateError: unbalanced {{if}}, {{else}} or {{endif}}")
+            }
+            TemplateErrorKind::Unterminated => write!(
+                f,
+                "TemplateError: unexpected end of template in state {:?}",
+                self.state
+            ),
+        }
+    }
+}
+
+/// The kind of a [`TemplateError`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TemplateErrorKind {
+    /// A character that isn't valid at its position
+    UnexpectedChar(char),
+    /// A key that is neither built in nor added with [`ProgressStyle::with_key()`] (strict
+    /// parsing only)
+    UnknownKey(String),
+    /// A style string with an unknown part, like `red.bolt` (strict parsing only)
+    InvalidStyle(String),
+    /// A width that doesn't fit into a `u16`
+    InvalidNumber(String),
+    /// A template ending inside a placeholder, like `{pos` (strict parsing only)
+    Unterminated,
+    /// An `{if}` with a condition other than `len`, `msg`, `prefix` or `finished`
+    UnknownCondition(String),
+    /// An `{else}` or `{endif}` without a matching `{if}`, or an `{if}` without `{endif}`
+    UnbalancedCondition,
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(TabExpandedString),
+    Placeholder {
+        key: String,
+        align: Alignment,
         width: Option<u16>,
+        /// The character used for padding, `0` for zero padding (`{pos:04}`)
+        fill: char,
         truncate: bool,
-        style: Option<Style>,
-        alt_style: Option<Style>,
+        /// Maximum number of lines to wrap onto (`wide_msg` only)
+        wrap: Option<u16>,
+        /// Whether to group the digits of numbers with commas (`{pos:,}`)
+        thousands: bool,
+        /// Number of decimals of numbers (`{per_sec:~2}`)
+        precision: Option<u8>,
+        style: Option<TemplateStyle>,
+        alt_style: Option<TemplateStyle>,
     },
     NewLine,
+    /// Start of a conditional section (`{if len}` or `{if !len}`)
+    If {
+        condition: TemplateCondition,
+        negate: bool,
+    },
+    Else,
+    EndIf,
+}
+
+/// A condition of a `{if ...}` section
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TemplateCondition {
+    /// The length of the progress bar is known
+    Len,
+    /// The message is not empty
+    Msg,
+    /// The prefix is not empty
+    Prefix,
+    /// The progress bar is finished
+    Finished,
+}
+
+impl TemplateCondition {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "len" => Self::Len,
+            "msg" => Self::Msg,
+            "prefix" => Self::Prefix,
+            "finished" => Self::Finished,
+            _ => return None,
+        })
+    }
+
+    fn eval(self, state: &ProgressState) -> bool {
+        match self {
+            Self::Len => state.len().is_some(),
+            Self::Msg => !state.message.expanded().is_empty(),
+            Self::Prefix => !state.prefix.expanded().is_empty(),
+            Self::Finished => state.is_finished(),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -652,19 +1679,62 @@ enum State {
     Key,
     Align,
     Width,
+    Precision,
+    WrapLines,
     FirstStyle,
     AltStyle,
+    Condition,
 }
 
 struct BarDisplay<'a> {
     chars: &'a [Box<str>],
+    /// Indices into `chars` for each cell, if rendered from a cell source
+    cells: Vec<usize>,
     filled: usize,
     cur: Option<usize>,
-    rest: console::StyledObject<RepeatedStringDisplay<'a>>,
+    /// Colors of the sections of the filled part, see `ProgressStyle::bar_gradient()`
+    gradient: &'a [Color],
+    /// The number of cells in the bar
+    width: usize,
+    rest: StyledDisplay<'a, RepeatedStringDisplay<'a>>,
+}
+
+impl BarDisplay<'_> {
+    /// Writes the filled part with the color of each cell's gradient section
+    fn fmt_gradient(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut segment = String::new();
+        let mut color = 0;
+        let cells = self.filled + usize::from(self.cur.is_some());
+        for i in 0..cells {
+            let section = (i * self.gradient.len() / self.width).min(self.gradient.len() - 1);
+            if section != color && !segment.is_empty() {
+                let style = Style::new().fg(self.gradient[color]);
+                write!(f, "{}", style.apply_to(&segment))?;
+                segment.clear();
+            }
+            color = section;
+            match self.cur {
+                Some(cur) if i == self.filled => segment.push_str(&self.chars[cur]),
+                _ => segment.push_str(&self.chars[0]),
+            }
+        }
+        if !segment.is_empty() {
+            let style = Style::new().fg(self.gradient[color]);
+            write!(f, "{}", style.apply_to(&segment))?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for BarDisplay<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for &cell in &self.cells {
+            f.write_str(&self.chars[cell])?;
+        }
+        if !self.gradient.is_empty() {
+            self.fmt_gradient(f)?;
+            return self.rest.fmt(f);
+        }
         for _ in 0..self.filled {
             f.write_str(&self.chars[0])?;
         }
@@ -675,6 +1745,216 @@ impl fmt::Display for BarDisplay<'_> {
     }
 }
 
+/// A style from a template, whose hex colors may need 24-bit color escape sequences
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TemplateStyle {
+    style: Style,
+    /// 24-bit colors, only set if the terminal supports them
+    fg: Option<Rgb>,
+    bg: Option<Rgb>,
+}
+
+impl TemplateStyle {
+    /// Parses a dotted style string like [`Style::from_dotted_str()`], which may also contain
+    /// `#rrggbb` and `on_#rrggbb` colors
+    fn from_dotted_str(s: &str) -> Self {
+        Self::parse(s, ColorDepth::detect())
+    }
+
+    fn parse(s: &str, depth: ColorDepth) -> Self {
+        let mut rv = Self::default();
+        let mut names = Vec::new();
+        for part in s.split('.') {
+            match part.strip_prefix("on_").and_then(Rgb::parse) {
+                Some(bg) => rv.bg = Some(bg),
+                None => match Rgb::parse(part) {
+                    Some(fg) => rv.fg = Some(fg),
+                    None => names.push(part),
+                },
+            }
+        }
+        rv.style = Style::from_dotted_str(&names.join("."));
+
+        // Terminals without 24-bit colors get the closest color they support
+        match depth {
+            ColorDepth::TrueColor => {}
+            ColorDepth::Ansi256 => {
+                if let Some(fg) = rv.fg.take() {
+                    rv.style = rv.style.color256(fg.to_ansi256());
+                }
+                if let Some(bg) = rv.bg.take() {
+                    rv.style = rv.style.on_color256(bg.to_ansi256());
+                }
+            }
+            ColorDepth::Ansi16 => {
+                if let Some(fg) = rv.fg.take() {
+                    rv.style = match fg.to_ansi16() {
+                        (color, false) => rv.style.fg(color),
+                        (color, true) => rv.style.fg(color).bright(),
+                    };
+                }
+                if let Some(bg) = rv.bg.take() {
+                    rv.style = match bg.to_ansi16() {
+                        (color, false) => rv.style.bg(color),
+                        (color, true) => rv.style.bg(color).on_bright(),
+                    };
+                }
+            }
+        }
+        rv
+    }
+
+    fn apply_to<D: fmt::Display>(&self, val: D) -> StyledDisplay<'_, D> {
+        StyledDisplay {
+            style: Some(self),
+            val,
+        }
+    }
+}
+
+impl From<Style> for TemplateStyle {
+    fn from(style: Style) -> Self {
+        Self {
+            style,
+            fg: None,
+            bg: None,
+        }
+    }
+}
+
+struct StyledDisplay<'a, D> {
+    style: Option<&'a TemplateStyle>,
+    val: D,
+}
+
+impl<D: fmt::Display> fmt::Display for StyledDisplay<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(style) = self.style else {
+            return self.val.fmt(f);
+        };
+
+        let rgb = colors_enabled() && (style.fg.is_some() || style.bg.is_some());
+        if rgb {
+            if let Some(Rgb(r, g, b)) = style.fg {
+                write!(f, "\x1b[38;2;{r};{g};{b}m")?;
+            }
+            if let Some(Rgb(r, g, b)) = style.bg {
+                write!(f, "\x1b[48;2;{r};{g};{b}m")?;
+            }
+        }
+        write!(f, "{}", style.style.apply_to(&self.val))?;
+        // The console style only resets what it set itself
+        if rgb && style.style == Style::new() {
+            f.write_str("\x1b[0m")?;
+        }
+        Ok(())
+    }
+}
+
+/// The colors the terminal supports, see [`TemplateStyle`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Guesses the colors the terminal supports from the `COLORTERM` and `TERM` variables
+    fn detect() -> Self {
+        static DEPTH: OnceLock<ColorDepth> = OnceLock::new();
+        *DEPTH.get_or_init(|| {
+            let colorterm = env::var("COLORTERM").unwrap_or_default();
+            let term = env::var("TERM").unwrap_or_default();
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                Self::TrueColor
+            } else if term.contains("256color") {
+                Self::Ansi256
+            } else {
+                Self::Ansi16
+            }
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rgb(u8, u8, u8);
+
+impl Rgb {
+    /// Parses a `#rrggbb` color
+    fn parse(s: &str) -> Option<Self> {
+        let hex = s.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /// The closest color of the 6x6x6 cube or the gray ramp of the 256-color palette
+    fn to_ansi256(self) -> u8 {
+        let Self(r, g, b) = self;
+        if r == g && g == b {
+            // The gray ramp goes from 8 to 238 in steps of 10
+            return match r {
+                0..=3 => 16,
+                244..=255 => 231,
+                _ => 232 + (r.saturating_sub(3) / 10).min(23),
+            };
+        }
+        // The cube levels are 0, 95, 135, 175, 215 and 255
+        let level = |c: u8| match c {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (c - 35) / 40,
+        };
+        16 + 36 * level(r) + 6 * level(g) + level(b)
+    }
+
+    /// The closest of the 16 basic colors, and whether it's the bright variant
+    fn to_ansi16(self) -> (Color, bool) {
+        const PALETTE: [(u8, u8, u8); 16] = [
+            (0, 0, 0),
+            (205, 0, 0),
+            (0, 205, 0),
+            (205, 205, 0),
+            (0, 0, 238),
+            (205, 0, 205),
+            (0, 205, 205),
+            (229, 229, 229),
+            (127, 127, 127),
+            (255, 0, 0),
+            (0, 255, 0),
+            (255, 255, 0),
+            (92, 92, 255),
+            (255, 0, 255),
+            (0, 255, 255),
+            (255, 255, 255),
+        ];
+        const COLORS: [Color; 8] = [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+        ];
+
+        let distance = |&(r, g, b): &(u8, u8, u8)| {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(self.0, r) + d(self.1, g) + d(self.2, b)
+        };
+        let (idx, _) = PALETTE
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, color)| distance(color))
+            .unwrap();
+        (COLORS[idx % 8], idx >= 8)
+    }
+}
+
 struct RepeatedStringDisplay<'a> {
     str: &'a str,
     num: usize,
@@ -693,6 +1973,7 @@ struct PaddedStringDisplay<'a> {
     str: &'a str,
     width: usize,
     align: Alignment,
+    fill: char,
     truncate: bool,
 }
 
@@ -723,16 +2004,193 @@ impl fmt::Display for PaddedStringDisplay<'_> {
         };
 
         for _ in 0..left_pad {
-            f.write_char(' ')?;
+            f.write_char(self.fill)?;
         }
         f.write_str(self.str)?;
         for _ in 0..right_pad {
-            f.write_char(' ')?;
+            f.write_char(self.fill)?;
         }
         Ok(())
     }
 }
 
+const DEFAULT_GUTTER_WIDTH: usize = 12;
+const DEFAULT_SMOOTHING_ALPHA: f64 = 0.3;
+
+/// How the rate of progress is smoothed for estimates like `{eta}` and `{per_sec}`
+///
+/// See [`ProgressStyle::with_eta_smoothing()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EtaSmoothing {
+    /// Exponentially weighted moving average over the progress history
+    ///
+    /// The weight of progress made halves every `half_life`, so a short half-life follows
+    /// changes in the rate quickly while a long one evens out bursty workloads. The default
+    /// half-life is about 4.5 seconds, meaning that progress older than 15 seconds has a
+    /// collective weight of 10% in the estimate.
+    ExponentialDecay {
+        /// The time after which the weight of progress made is halved
+        half_life: Duration,
+    },
+}
+
+impl EtaSmoothing {
+    /// The time after which the weight of progress made drops to 10%, in seconds
+    pub(crate) fn weighting_secs(&self) -> f64 {
+        match self {
+            Self::ExponentialDecay { half_life } => half_life.as_secs_f64() * 10_f64.log2(),
+        }
+    }
+}
+
+impl Default for EtaSmoothing {
+    fn default() -> Self {
+        Self::ExponentialDecay {
+            half_life: Duration::from_secs_f64(15.0 / 10_f64.log2()),
+        }
+    }
+}
+
+/// The window over which `{per_sec}`, `{bytes_per_sec}` and related keys measure the rate
+///
+/// See [`ProgressStyle::rate_window()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RateWindow {
+    /// The smoothed rate also used for `{eta}` (this is the default)
+    ///
+    /// See [`EtaSmoothing`].
+    #[default]
+    Smoothed,
+    /// The rate of the last update
+    ///
+    /// If the next update takes longer than the last one, the rate decays as if it were about
+    /// to land.
+    Instant,
+    /// The average rate over the given duration up to now
+    OverLast(Duration),
+    /// The average rate since the bar was created or reset
+    SinceStart,
+}
+
+/// How finely `{bar}` and `{wide_bar}` show progress within a cell
+///
+/// See [`ProgressStyle::bar_resolution()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BarResolution {
+    /// Use the [progress chars][ProgressStyle::progress_chars()] as configured (this is the
+    /// default)
+    #[default]
+    Chars,
+    /// Show the progress within the last cell in eighths with Unicode block elements
+    Eighths,
+}
+
+/// How a message wider than the available space is displayed
+///
+/// See [`ProgressStyle::message_overflow()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Overflow {
+    /// Cut off the part of the message that does not fit (this is the default)
+    #[default]
+    Truncate,
+    /// Scroll the message horizontally by `speed` columns per tick
+    ///
+    /// Combine with [`ProgressBar::enable_steady_tick()`] to scroll at a steady pace.
+    ///
+    /// [`ProgressBar::enable_steady_tick()`]: crate::ProgressBar::enable_steady_tick
+    Marquee {
+        /// The number of columns to scroll by per tick
+        speed: usize,
+    },
+}
+
+/// Replaces the wide message placeholder in `cur` with the first `width` columns of `msg`,
+/// continuing the message on up to `max_lines - 1` lines aligned with the first one
+fn wrap_message(
+    cur: String,
+    msg: &str,
+    width: usize,
+    align: Alignment,
+    max_lines: usize,
+) -> String {
+    let mut lines = vec![String::new()];
+    let mut cols = 0;
+    for word in msg.split(' ') {
+        if cols > 0 && cols + 1 + measure_text_width(word) > width {
+            lines.push(String::new());
+            cols = 0;
+        } else if cols > 0 {
+            lines.last_mut().unwrap().push(' ');
+            cols += 1;
+        }
+
+        // Words longer than the available width are split anywhere
+        for c in word.chars() {
+            let c_width = measure_text_width(c.encode_utf8(&mut [0; 4]));
+            if cols + c_width > width {
+                lines.push(String::new());
+                cols = 0;
+            }
+            lines.last_mut().unwrap().push(c);
+            cols += c_width;
+        }
+    }
+    lines.truncate(Ord::max(max_lines, 1));
+
+    let (before, after) = cur.split_once('\x00').unwrap_or((&cur, ""));
+    let first = PaddedStringDisplay {
+        str: &lines[0],
+        width,
+        align,
+        fill: ' ',
+        truncate: true,
+    }
+    .to_string();
+
+    let mut out = String::from(before);
+    match after.is_empty() {
+        true => out.push_str(first.trim_end()),
+        false => out.push_str(&first),
+    }
+    out.push_str(after);
+
+    let indent = " ".repeat(measure_text_width(before));
+    for line in &lines[1..] {
+        out.push('\n');
+        out.push_str(&indent);
+        out.push_str(line);
+    }
+    out
+}
+
+/// Separator shown between the end and the start of a scrolling message
+const MARQUEE_GAP: &str = "   ";
+
+/// Renders `width` columns of `msg` scrolled by `offset` chars, wrapping around
+fn marquee(msg: &str, width: usize, offset: usize) -> String {
+    let plain = console::strip_ansi_codes(msg);
+    let cycle = plain.chars().chain(MARQUEE_GAP.chars());
+    let cycle_len = plain.chars().count() + MARQUEE_GAP.len();
+
+    let mut out = String::new();
+    let mut cols = 0;
+    for c in cycle.clone().chain(cycle).skip(offset % cycle_len) {
+        let c_width = measure_text_width(c.encode_utf8(&mut [0; 4]));
+        if cols + c_width > width {
+            break;
+        }
+        out.push(c);
+        cols += c_width;
+    }
+
+    out.extend(std::iter::repeat(' ').take(width - cols));
+    out
+}
+
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 enum Alignment {
     Left,
@@ -746,6 +2204,15 @@ pub trait ProgressTracker: Send + Sync {
     fn clone_box(&self) -> Box<dyn ProgressTracker>;
     /// Notifies the progress tracker of a tick event
     fn tick(&mut self, state: &ProgressState, now: Instant);
+    /// Notifies the progress tracker of a tick event, allowing it to change the state
+    ///
+    /// This is called instead of [`ProgressTracker::tick()`], which the default implementation
+    /// forwards to. Trackers can override it to set the message or prefix with
+    /// [`ProgressState::set_message()`] and [`ProgressState::set_prefix()`], e.g. to show a
+    /// "slow network" hint while the rate is low, without a separate thread.
+    fn update(&mut self, state: &mut ProgressState, now: Instant) {
+        self.tick(state, now);
+    }
     /// Notifies the progress tracker of a reset event
     fn reset(&mut self, state: &ProgressState, now: Instant);
     /// Provides access to the progress bar display buffer for custom messages
@@ -837,6 +2304,126 @@ mod tests {
         pb.finish_and_clear();
     }
 
+    #[test]
+    fn eighths_resolution() {
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(Some(80), pos);
+        let style = ProgressStyle::with_template("{bar:4}")
+            .unwrap()
+            .progress_chars("#>-")
+            .bar_resolution(BarResolution::Eighths);
+
+        let mut render = |pos| {
+            state.set_pos(pos);
+            let mut buf = Vec::new();
+            style.format_state(&state, &mut buf, 16);
+            buf
+        };
+        assert_eq!(&render(0)[0], "----");
+        assert_eq!(&render(3)[0], "▏---");
+        assert_eq!(&render(10)[0], "▌---");
+        assert_eq!(&render(20)[0], "█---");
+        assert_eq!(&render(35)[0], "█▊--");
+        assert_eq!(&render(79)[0], "███▉");
+        assert_eq!(&render(80)[0], "████");
+    }
+
+    #[test]
+    fn gradient() {
+        set_colors_enabled(true);
+
+        let pos = Arc::new(AtomicPosition::new());
+        pos.set(5);
+        let state = ProgressState::new(Some(6), pos);
+        let style = ProgressStyle::with_template("{bar:6}")
+            .unwrap()
+            .progress_chars("#-")
+            .bar_gradient(&[Color::Red, Color::Yellow, Color::Green]);
+
+        let mut buf = Vec::new();
+        style.format_state(&state, &mut buf, 16);
+        assert_eq!(
+            &buf[0],
+            "\u{1b}[31m##\u{1b}[0m\u{1b}[33m##\u{1b}[0m\u{1b}[32m#\u{1b}[0m-"
+        );
+    }
+
+    #[test]
+    fn hex_colors() {
+        set_colors_enabled(true);
+
+        let style = TemplateStyle::parse("#ff8800.bold", ColorDepth::TrueColor);
+        assert_eq!(
+            style.apply_to("x").to_string(),
+            "\u{1b}[38;2;255;136;0m\u{1b}[1mx\u{1b}[0m"
+        );
+        let style = TemplateStyle::parse("on_#333333", ColorDepth::TrueColor);
+        assert_eq!(
+            style.apply_to("x").to_string(),
+            "\u{1b}[48;2;51;51;51mx\u{1b}[0m"
+        );
+
+        assert_eq!(
+            TemplateStyle::parse("#ff8800.on_#333333", ColorDepth::Ansi256),
+            TemplateStyle::from(Style::new().color256(208).on_color256(236))
+        );
+        assert_eq!(
+            TemplateStyle::parse("#ff8800.on_#ffffff", ColorDepth::Ansi16),
+            TemplateStyle::from(Style::new().yellow().on_white().on_bright())
+        );
+
+        assert!(ProgressStyle::with_template_strict("{bar:40.#ff8800/on_#333333}").is_ok());
+        assert!(ProgressStyle::with_template_strict("{bar:40.#ff88}").is_err());
+    }
+
+    #[test]
+    fn tracker_sets_message() {
+        #[derive(Clone)]
+        struct Hint;
+
+        impl ProgressTracker for Hint {
+            fn clone_box(&self) -> Box<dyn ProgressTracker> {
+                Box::new(self.clone())
+            }
+
+            fn tick(&mut self, _: &ProgressState, _: Instant) {}
+
+            fn update(&mut self, state: &mut ProgressState, _: Instant) {
+                if state.pos() >= 5 {
+                    state.set_message("halfway\tthere");
+                    state.set_prefix("[hint]");
+                }
+            }
+
+            fn reset(&mut self, _: &ProgressState, _: Instant) {}
+
+            fn write(&self, _: &ProgressState, _: &mut dyn fmt::Write) {}
+        }
+
+        use crate::ProgressBar;
+
+        let pb = ProgressBar::hidden()
+            .with_tab_width(2)
+            .with_message("start");
+        pb.set_length(10);
+        pb.set_style(
+            ProgressStyle::with_template("{prefix} {msg}{hint}")
+                .unwrap()
+                .with_key("hint", Hint),
+        );
+
+        pb.inc(1);
+        assert_eq!(pb.message(), "start");
+        pb.inc(4);
+        assert_eq!(pb.message(), "halfway  there");
+        assert_eq!(pb.prefix(), "[hint]");
+
+        let mut buf = Vec::new();
+        let state = pb.state();
+        state.style.format_state(&state.state, &mut buf, 80);
+        assert_eq!(&buf[0], "[hint] halfway  there");
+    }
+
     use crate::state::TabExpandedString;
 
     #[test]
@@ -901,6 +2488,149 @@ mod tests {
         assert_eq!(&buf[0], "\u{1b}[31m\u{1b}[44m XXX \u{1b}[0m");
     }
 
+    #[test]
+    fn template_width_overflow() {
+        let err = Template::from_str("{pos:99999}").unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &TemplateErrorKind::InvalidNumber("99999".to_owned())
+        );
+    }
+
+    #[test]
+    fn template_conditions() {
+        const WIDTH: u16 = 80;
+        let pos = Arc::new(AtomicPosition::new());
+        pos.set(3);
+        let mut state = ProgressState::new(None, pos);
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template(
+            "{if len}{pos}/{len}{else}{pos} items{endif}{if !msg} -{if prefix} {prefix}{endif}{endif}",
+        )
+        .unwrap();
+        style.format_state(&state, &mut buf, WIDTH);
+        assert_eq!(&buf[0], "3 items -");
+
+        buf.clear();
+        state.set_len(10);
+        state.prefix = TabExpandedString::new("pre".into(), DEFAULT_TAB_WIDTH);
+        style.format_state(&state, &mut buf, WIDTH);
+        assert_eq!(&buf[0], "3/10 - pre");
+
+        buf.clear();
+        state.message = TabExpandedString::new("msg".into(), DEFAULT_TAB_WIDTH);
+        style.format_state(&state, &mut buf, WIDTH);
+        assert_eq!(&buf[0], "3/10");
+
+        for template in [
+            "{else}",
+            "{if len}",
+            "{endif}",
+            "{if len}{else}{else}{endif}",
+        ] {
+            let err = Template::from_str(template).unwrap_err();
+            assert_eq!(err.kind(), &TemplateErrorKind::UnbalancedCondition);
+        }
+        let err = Template::from_str("{if size}{endif}").unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &TemplateErrorKind::UnknownCondition("size".to_owned())
+        );
+        assert!(ProgressStyle::with_template_strict("{if !len}{pos}{endif}").is_ok());
+    }
+
+    #[test]
+    fn number_format() {
+        set_colors_enabled(true);
+
+        const WIDTH: u16 = 80;
+        let pos = Arc::new(AtomicPosition::new());
+        pos.set(1_234_567);
+        let state = ProgressState::new(Some(4_000_000), pos);
+        let mut buf = Vec::new();
+
+        let render = |template: &str, buf: &mut Vec<LineType>| {
+            buf.clear();
+            let style = ProgressStyle::with_template(template).unwrap();
+            style.format_state(&state, buf, WIDTH);
+            buf[0].as_ref().to_owned()
+        };
+
+        assert_eq!(render("{pos:,}/{len:,}", &mut buf), "1,234,567/4,000,000");
+        assert_eq!(render("[{pos:>11,}]", &mut buf), "[  1,234,567]");
+        assert_eq!(render("{percent_precise:~1}", &mut buf), "30.9");
+        assert_eq!(render("{per_sec:>9~2}", &mut buf), "   0.00/s");
+        assert_eq!(
+            render("{pos:,.red}", &mut buf),
+            "\u{1b}[31m1,234,567\u{1b}[0m"
+        );
+        assert_eq!(
+            render("{percent_precise:~1.red}", &mut buf),
+            "\u{1b}[31m30.9\u{1b}[0m"
+        );
+        // a number after the dot is a color, also for numeric keys
+        assert_eq!(
+            render("{pos:.208}", &mut buf),
+            "\u{1b}[38;5;208m1234567\u{1b}[0m"
+        );
+        assert!(Template::from_str("{percent:.300}").is_ok());
+
+        assert_eq!(group_thousands("-1234567.891"), "-1,234,567.891");
+        assert_eq!(group_thousands("123"), "123");
+        // only keys with decimals have a precision
+        assert_eq!(
+            Template::from_str("{pos:~2}").unwrap_err().kind(),
+            &TemplateErrorKind::UnexpectedChar('~')
+        );
+        assert_eq!(
+            Template::from_str("{per_sec:~999}").unwrap_err().kind(),
+            &TemplateErrorKind::InvalidNumber("999".to_owned())
+        );
+        assert_eq!(
+            Template::from_str("{per_sec:~}").unwrap_err().kind(),
+            &TemplateErrorKind::InvalidNumber(String::new())
+        );
+    }
+
+    #[test]
+    fn template_strict() {
+        let tracker = |_: &ProgressState, w: &mut dyn Write| write!(w, "XXX").unwrap();
+        let kind = |template: &str| {
+            ProgressStyle::with_template_strict(template)
+                .err()
+                .map(|e| e.kind().clone())
+        };
+
+        assert_eq!(kind("{bar:40.cyan/blue} {pos:>7}/{len:7} {msg}"), None);
+        assert_eq!(kind("{{literal}} { spaced"), None);
+        assert_eq!(
+            kind("{pos} {foo}"),
+            Some(TemplateErrorKind::UnknownKey("foo".to_owned()))
+        );
+        assert_eq!(
+            kind("{bar:.red.bolt}"),
+            Some(TemplateErrorKind::InvalidStyle("red.bolt".to_owned()))
+        );
+        assert_eq!(
+            kind("{bar:.red/on_blu}"),
+            Some(TemplateErrorKind::InvalidStyle("on_blu".to_owned()))
+        );
+        assert_eq!(kind("{pos"), Some(TemplateErrorKind::Unterminated));
+        assert_eq!(kind("{pos}}"), Some(TemplateErrorKind::Unterminated));
+        assert_eq!(
+            kind("{pos:x}"),
+            Some(TemplateErrorKind::UnexpectedChar('x'))
+        );
+
+        // the lenient parser accepts all of these
+        assert!(ProgressStyle::with_template("{foo:.red.bolt} {pos").is_ok());
+
+        let style = ProgressStyle::default_bar().with_key("foo", tracker);
+        assert!(style.clone().template_strict("{foo:.208} {pos}").is_ok());
+        assert!(style.template_strict("{bar}").is_ok());
+    }
+
     #[test]
     fn align_truncation() {
         const WIDTH: u16 = 10;
@@ -926,6 +2656,53 @@ mod tests {
         assert_eq!(&buf[0], "fghijklmno");
     }
 
+    #[test]
+    fn percent_of() {
+        let pos = Arc::new(AtomicPosition::new());
+        let state = ProgressState::new(Some(10), pos.clone());
+        let style = ProgressStyle::with_template("{percent} {percent_precise}")
+            .unwrap()
+            .percent_of(0.4, 0.7);
+        let mut buf = Vec::new();
+
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "40 40.000");
+
+        buf.clear();
+        pos.set(5);
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "55 55.000");
+
+        buf.clear();
+        pos.set(10);
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "70 70.000");
+    }
+
+    #[test]
+    fn zero_padding() {
+        let pos = Arc::new(AtomicPosition::new());
+        pos.set(4);
+        let state = ProgressState::new(Some(100), pos);
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("{pos:03}/{len:03}").unwrap();
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "004/100");
+
+        buf.clear();
+        let style = ProgressStyle::with_template("{pos:<04}|{pos:04!}|{pos:4}").unwrap();
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "0004|0004|4   ");
+
+        // Other keys are not zero padded, and strict parsing rejects the `0`
+        buf.clear();
+        let style = ProgressStyle::with_template("{msg:>04}|").unwrap();
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "    |");
+        assert!(ProgressStyle::with_template_strict("{msg:04}").is_err());
+    }
+
     #[test]
     fn wide_element_style() {
         set_colors_enabled(true);
@@ -961,6 +2738,168 @@ mod tests {
         assert_eq!(&buf[0], "\u{1b}[31m\u{1b}[44m foobar \u{1b}[0m");
     }
 
+    #[test]
+    fn wrap_message() {
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(Some(10), pos);
+        state.message = TabExpandedString::NoTabs("compiling some rather long crate names".into());
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("> {wide_msg:+3} {pos}").unwrap();
+        style.format_state(&state, &mut buf, 20);
+        assert_eq!(
+            buf.iter().map(AsRef::as_ref).collect::<Vec<&str>>(),
+            ["> compiling some   0", "  rather long", "  crate names"]
+        );
+
+        buf.clear();
+        let style = ProgressStyle::with_template("> {wide_msg:+2}").unwrap();
+        style.format_state(&state, &mut buf, 20);
+        assert_eq!(
+            buf.iter().map(AsRef::as_ref).collect::<Vec<&str>>(),
+            ["> compiling some", "  rather long crate"]
+        );
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn progress_chars_unequal_width() {
+        const WIDTH: u16 = 80;
+        let pos = Arc::new(AtomicPosition::new());
+        let state = ProgressState::new(Some(10), pos.clone());
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("{bar:6}")
+            .unwrap()
+            .progress_chars("🟩🟨-");
+        style.format_state(&state, &mut buf, WIDTH);
+        assert_eq!(&buf[0], "- - - ");
+
+        buf.clear();
+        pos.set(5);
+        style.format_state(&state, &mut buf, WIDTH);
+        assert_eq!(&buf[0], "🟩🟨- ");
+    }
+
+    #[test]
+    fn cell_source() {
+        const WIDTH: u16 = 80;
+        let pos = Arc::new(AtomicPosition::new());
+        let state = ProgressState::new(Some(10), pos);
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("[{bar:4}]")
+            .unwrap()
+            .progress_chars("█▓▒░ ")
+            .with_cell_source(|_: &ProgressState, cells| {
+                assert_eq!(cells, 4);
+                vec![1.0, 0.5, 0.0]
+            });
+        style.format_state(&state, &mut buf, WIDTH);
+        assert_eq!(&buf[0], "[█▒  ]");
+    }
+
+    #[test]
+    fn marquee_message() {
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(None, pos);
+        state.message = TabExpandedString::NoTabs("hello world".into());
+        let style = ProgressStyle::with_template("[{wide_msg}]")
+            .unwrap()
+            .message_overflow(Overflow::Marquee { speed: 2 });
+
+        let mut buf = Vec::new();
+        style.format_state(&state, &mut buf, 8);
+        assert_eq!(&buf[0], "[hello ]");
+
+        buf.clear();
+        state.tick = 3;
+        style.format_state(&state, &mut buf, 8);
+        assert_eq!(&buf[0], "[world ]");
+
+        buf.clear();
+        state.tick = 6;
+        style.format_state(&state, &mut buf, 8);
+        assert_eq!(&buf[0], "[  hell]");
+
+        // Messages that fit are not scrolled
+        buf.clear();
+        style.format_state(&state, &mut buf, 20);
+        assert_eq!(&buf[0], "[hello world       ]");
+    }
+
+    #[test]
+    fn spinner_frame_index() {
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(None, pos);
+        let style = ProgressStyle::with_template("{spinner_frame_index}")
+            .unwrap()
+            .tick_chars("abcd");
+        assert_eq!(style.spinner_frame_count(), 3);
+
+        let mut buf = Vec::new();
+        state.tick = 4;
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "1");
+    }
+
+    #[test]
+    fn unit_scale() {
+        let pos = Arc::new(AtomicPosition::new());
+        pos.set(12_345);
+        let state = ProgressState::new(Some(45_000), pos);
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("{pos}/{len}")
+            .unwrap()
+            .with_unit_scale(1000, "k");
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "12.3k/45.0k");
+    }
+
+    #[test]
+    fn gutter() {
+        let pos = Arc::new(AtomicPosition::new());
+        let mut state = ProgressState::new(Some(10), pos);
+        state.prefix = TabExpandedString::NoTabs("Compiling".into());
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("{gutter} {pos}/{len}").unwrap();
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "   Compiling 0/10");
+        assert_eq!(
+            style.format_gutter_line("Finished", "release"),
+            "    Finished release"
+        );
+
+        buf.clear();
+        let style = style.with_gutter(5);
+        style.format_state(&state, &mut buf, 80);
+        assert_eq!(&buf[0], "Compiling 0/10");
+        assert_eq!(style.format_gutter_line("Done", "x"), " Done x");
+    }
+
+    #[test]
+    fn narrow_template() {
+        let pos = Arc::new(AtomicPosition::new());
+        pos.set(3);
+        let state = ProgressState::new(Some(10), pos);
+        let mut buf = Vec::new();
+
+        let style = ProgressStyle::with_template("{bar:10} {pos}/{len}")
+            .unwrap()
+            .progress_chars("#-")
+            .with_narrow_template(20, "{percent}%")
+            .unwrap();
+
+        style.format_state(&state, &mut buf, 20);
+        assert_eq!(&buf[0], "###------- 3/10");
+
+        buf.clear();
+        style.format_state(&state, &mut buf, 19);
+        assert_eq!(&buf[0], "30%");
+    }
+
     #[test]
     fn multiline_handling() {
         const WIDTH: u16 = 80;
@@ -998,4 +2937,12 @@ mod tests {
         assert_eq!(&buf[2], "bar");
         assert_eq!(&buf[3], "baz");
     }
+
+    #[test]
+    #[should_panic(expected = "half-life must be greater than zero")]
+    fn zero_half_life() {
+        ProgressStyle::default_bar().with_eta_smoothing(EtaSmoothing::ExponentialDecay {
+            half_life: Duration::ZERO,
+        });
+    }
 }
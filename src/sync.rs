@@ -0,0 +1,15 @@
+use std::sync::{LockResult, PoisonError};
+
+/// Recovers the guard of a poisoned lock instead of panicking
+///
+/// Progress bars only hold what is displayed, so a thread that panics while updating them
+/// should not make every other thread reporting progress panic as well.
+pub(crate) trait IgnorePoison<T> {
+    fn ignore_poison(self) -> T;
+}
+
+impl<T> IgnorePoison<T> for LockResult<T> {
+    fn ignore_poison(self) -> T {
+        self.unwrap_or_else(PoisonError::into_inner)
+    }
+}
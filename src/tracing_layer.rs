@@ -0,0 +1,479 @@
+//! A [`tracing`] layer drawing spans as progress bars and printing events above them
+//!
+//! Events written directly to the terminal by another layer interleave with the redraws of
+//! progress bars. [`IndicatifLayer`] instead prints events with [`MultiProgress::println()`],
+//! and shows a spinner for every span while it is open, indented below the spinner of its
+//! parent span.
+//!
+//! ```rust,no_run
+//! use indicatif::tracing_layer::IndicatifLayer;
+//! use indicatif::MultiProgress;
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! let subscriber = tracing_subscriber::registry().with(IndicatifLayer::new(MultiProgress::new()));
+//! tracing::subscriber::set_global_default(subscriber).unwrap();
+//! ```
+//!
+//! [`tracing`]: https://docs.rs/tracing
+
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex, Once, Weak};
+use std::thread;
+use std::time::Duration;
+
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::sync::IgnorePoison;
+use crate::{MultiProgress, ProgressBar, ProgressState, ProgressStyle, WeakProgressBar};
+
+/// A [`Layer`] showing spans as progress bars of a [`MultiProgress`] and printing events above
+/// them
+///
+/// The style of span progress bars can use these placeholders in addition to the usual ones:
+///
+/// * `span_name`: the name of the span.
+/// * `span_fields`: the fields of the span, formatted as `key=value` pairs.
+/// * `span_child_prefix`: two spaces for every ancestor of the span, to indent nested spans.
+///
+/// A progress bar is only created once a span is entered for the first time, and only for spans
+/// accepted by the [span filter](IndicatifLayer::with_span_filter()), which by default accepts
+/// spans at the `INFO` level or above. The spinners of all spans are ticked by a single
+/// background thread, see [`IndicatifLayer::with_tick_interval()`].
+///
+/// Events are printed as `LEVEL target: message key=value`, indented like the span they occur
+/// in. If the [`MultiProgress`] draws to a terminal that is not user attended (for example
+/// because stderr is redirected to a file), events are written to it as plain lines instead.
+/// Nothing is written if the [`MultiProgress`] is [hidden](crate::ProgressDrawTarget::hidden()).
+pub struct IndicatifLayer {
+    multi: MultiProgress,
+    span_style: ProgressStyle,
+    span_filter: Box<dyn Fn(&Metadata<'_>) -> bool + Send + Sync>,
+    tick_interval: Duration,
+    /// The progress bars of entered spans, ticked by the ticker thread
+    ticking: Arc<Mutex<Vec<WeakProgressBar>>>,
+    ticker: Once,
+}
+
+impl IndicatifLayer {
+    /// Creates a layer drawing spans and printing events through `multi`
+    pub fn new(multi: MultiProgress) -> Self {
+        Self {
+            multi,
+            span_style: ProgressStyle::with_template(
+                "{span_child_prefix}{spinner} {span_name} {span_fields}",
+            )
+            .unwrap(),
+            span_filter: Box::new(|metadata| *metadata.level() <= Level::INFO),
+            tick_interval: Duration::from_millis(100),
+            ticking: Arc::default(),
+            ticker: Once::new(),
+        }
+    }
+
+    /// Sets the style of the progress bars shown for spans
+    pub fn with_span_style(mut self, style: ProgressStyle) -> Self {
+        self.span_style = style;
+        self
+    }
+
+    /// Sets which spans are shown as progress bars
+    ///
+    /// Spans for which `filter` returns `false` are not shown; their children are indented as if
+    /// they were children of the closest shown ancestor. By default, spans at the `INFO` level or
+    /// above are shown.
+    ///
+    /// ```rust
+    /// # use indicatif::tracing_layer::IndicatifLayer;
+    /// # use indicatif::MultiProgress;
+    /// let layer = IndicatifLayer::new(MultiProgress::new())
+    ///     .with_span_filter(|metadata| metadata.target().starts_with("my_app"));
+    /// ```
+    pub fn with_span_filter(
+        mut self,
+        filter: impl Fn(&Metadata<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.span_filter = Box::new(filter);
+        self
+    }
+
+    /// Sets the interval at which the spinners of spans are ticked
+    ///
+    /// All spinners are ticked by a single background thread, which is started when the first
+    /// span is shown. A zero interval disables ticking, so spinners only advance when the
+    /// fields of their span are recorded. The default is 100ms.
+    pub fn with_tick_interval(mut self, interval: Duration) -> Self {
+        self.tick_interval = interval;
+        self
+    }
+
+    /// Returns the [`MultiProgress`] spans are drawn on
+    pub fn multi(&self) -> MultiProgress {
+        self.multi.clone()
+    }
+
+    /// Ticks `pb` from the ticker thread, starting it if needed
+    fn tick_steadily(&self, pb: &ProgressBar) {
+        if self.tick_interval.is_zero() {
+            return;
+        }
+
+        let mut ticking = self.ticking.lock().ignore_poison();
+        ticking.retain(|pb| pb.upgrade().is_some());
+        ticking.push(pb.downgrade());
+        drop(ticking);
+
+        self.ticker.call_once(|| {
+            let (ticking, interval) = (Arc::downgrade(&self.ticking), self.tick_interval);
+            thread::spawn(move || ticker(ticking, interval));
+        });
+    }
+}
+
+/// Ticks the progress bars in `ticking` until the layer is dropped
+fn ticker(ticking: Weak<Mutex<Vec<WeakProgressBar>>>, interval: Duration) {
+    loop {
+        thread::sleep(interval);
+        let Some(ticking) = ticking.upgrade() else {
+            return;
+        };
+
+        // Don't hold the lock while drawing, so that new spans are not blocked
+        let bars = ticking
+            .lock()
+            .ignore_poison()
+            .iter()
+            .filter_map(WeakProgressBar::upgrade)
+            .collect::<Vec<_>>();
+        drop(ticking);
+        for pb in bars.iter().filter(|pb| !pb.is_finished()) {
+            pb.tick();
+        }
+    }
+}
+
+impl fmt::Debug for IndicatifLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IndicatifLayer")
+            .field("multi", &self.multi)
+            .field("tick_interval", &self.tick_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for IndicatifLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        if !(self.span_filter)(span.metadata()) {
+            return;
+        }
+
+        // The progress bar is only created once the span is entered
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(SpanBar {
+            fields: Arc::new(Mutex::new(fields.0)),
+            node: None,
+        });
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        let fields = match span.extensions().get::<SpanBar>() {
+            Some(SpanBar { node: None, fields }) => fields.clone(),
+            _ => return,
+        };
+
+        // The closest ancestor shown as a progress bar, and the number of shown ancestors
+        let mut ancestors = span.scope().skip(1).filter_map(|ancestor| {
+            let extensions = ancestor.extensions();
+            extensions.get::<SpanBar>()?.node.clone()
+        });
+        let parent = ancestors.next();
+        let depth = parent.as_ref().map_or(0, |_| 1 + ancestors.count());
+
+        let name = span.name();
+        let child_prefix = "  ".repeat(depth);
+        let style = self
+            .span_style
+            .clone()
+            .with_key("span_name", move |_: &ProgressState, w: &mut dyn Write| {
+                let _ = w.write_str(name);
+            })
+            .with_key("span_child_prefix", {
+                move |_: &ProgressState, w: &mut dyn Write| {
+                    let _ = w.write_str(&child_prefix);
+                }
+            })
+            .with_key("span_fields", {
+                let fields = fields.clone();
+                move |_: &ProgressState, w: &mut dyn Write| {
+                    let _ = w.write_str(&fields.lock().ignore_poison());
+                }
+            });
+        let pb = ProgressBar::new_spinner().with_style(style);
+
+        // Place the progress bar below the existing descendants of its parent
+        let pb = match parent.as_ref().and_then(|parent| parent.last_index()) {
+            Some(idx) => self.multi.insert_after_index(idx, pb),
+            None => self.multi.add(pb),
+        };
+        pb.tick();
+        self.tick_steadily(&pb);
+
+        let node = Arc::new(SpanNode {
+            pb,
+            children: Mutex::default(),
+        });
+        if let Some(parent) = parent {
+            let mut children = parent.children.lock().ignore_poison();
+            children.retain(|child| child.strong_count() > 0);
+            children.push(Arc::downgrade(&node));
+        }
+        let mut extensions = span.extensions_mut();
+        if let Some(bar) = extensions.get_mut::<SpanBar>() {
+            bar.node = Some(node);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let extensions = span.extensions();
+        if let Some(bar) = extensions.get::<SpanBar>() {
+            let mut fields = bar.fields.lock().ignore_poison();
+            let mut recorded = Fields(fields.clone());
+            values.record(&mut recorded);
+            *fields = recorded.0;
+            drop(fields);
+            if let Some(node) = &bar.node {
+                node.pb.tick();
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Like the bar of the innermost shown span, which is indented by its shown ancestors
+        let shown = ctx.event_scope(event).map_or(0, |scope| {
+            scope
+                .filter(|span| {
+                    let extensions = span.extensions();
+                    extensions
+                        .get::<SpanBar>()
+                        .is_some_and(|bar| bar.node.is_some())
+                })
+                .count()
+        });
+        let depth = shown.saturating_sub(1);
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let metadata = event.metadata();
+        let line = format!(
+            "{:>5} {}{}: {}",
+            metadata.level(),
+            "  ".repeat(depth),
+            metadata.target(),
+            fields.0
+        );
+        let _ = match self.multi.is_hidden() {
+            true => self.multi.write_unattended_line(&line),
+            false => self.multi.println(&line),
+        };
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let bar = span.extensions_mut().remove::<SpanBar>();
+        if let Some(SpanBar {
+            node: Some(node), ..
+        }) = bar
+        {
+            node.pb.finish_and_clear();
+            self.multi.remove(&node.pb);
+        }
+    }
+}
+
+/// The state of a span accepted by the span filter, stored in its extensions
+struct SpanBar {
+    fields: Arc<Mutex<String>>,
+    /// The progress bar, once the span was entered
+    node: Option<Arc<SpanNode>>,
+}
+
+/// The progress bar of a span and the progress bars of its descendants
+struct SpanNode {
+    pb: ProgressBar,
+    children: Mutex<Vec<Weak<SpanNode>>>,
+}
+
+impl SpanNode {
+    /// The index of the last progress bar of this span and its descendants
+    fn last_index(&self) -> Option<usize> {
+        let children = self.children.lock().ignore_poison();
+        children
+            .iter()
+            .rev()
+            .filter_map(Weak::upgrade)
+            .find_map(|child| child.last_index())
+            .or_else(|| self.pb.index())
+    }
+}
+
+/// Formats fields as `key=value` pairs, with the message first
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.push(format_args!("{value}")),
+            name => self.push(format_args!("{name}={value}")),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.push(format_args!("{value:?}")),
+            name => self.push(format_args!("{name}={value:?}")),
+        }
+    }
+}
+
+impl Fields {
+    fn push(&mut self, args: fmt::Arguments<'_>) {
+        if !self.0.is_empty() {
+            self.0.push(' ');
+        }
+        let _ = self.0.write_fmt(args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use std::time::Duration;
+
+    use super::IndicatifLayer;
+    use crate::progress_bar::TICKER_TEST;
+    use crate::{MultiProgress, ProgressDrawTarget};
+
+    #[test]
+    fn spans_are_added_and_removed() {
+        let _guard = TICKER_TEST.lock().unwrap();
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+        let subscriber = tracing_subscriber::registry().with(IndicatifLayer::new(multi.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("outer", id = 1).entered();
+            let inner = tracing::info_span!("inner").entered();
+            assert_eq!(multi.summary().lines().count(), 3);
+
+            drop(inner);
+            drop(outer);
+        });
+
+        assert!(!multi.summary().contains("running"));
+    }
+
+    #[cfg(feature = "in_memory")]
+    #[test]
+    fn draws_nested_spans_and_events() {
+        let _guard = TICKER_TEST.lock().unwrap();
+        use crate::{InMemoryTerm, ProgressStyle};
+
+        let in_mem = InMemoryTerm::new(10, 80);
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(
+            in_mem.clone(),
+        )));
+        let layer = IndicatifLayer::new(multi).with_span_style(
+            ProgressStyle::with_template("{span_child_prefix}{span_name} {span_fields}").unwrap(),
+        );
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("outer", id = 1).entered();
+            let inner = tracing::info_span!("inner", file = "a.txt").entered();
+            tracing::warn!(attempt = 2, "retrying");
+            assert_eq!(
+                in_mem.contents(),
+                " WARN   indicatif::tracing_layer::tests: retrying attempt=2\n\
+                 outer id=1\n  inner file=a.txt"
+            );
+
+            drop(inner);
+            drop(outer);
+        });
+    }
+
+    #[cfg(feature = "in_memory")]
+    #[test]
+    fn children_are_ordered_and_filtered() {
+        let _guard = TICKER_TEST.lock().unwrap();
+        use crate::{InMemoryTerm, ProgressStyle};
+
+        let in_mem = InMemoryTerm::new(10, 80);
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(
+            in_mem.clone(),
+        )));
+        let layer = IndicatifLayer::new(multi)
+            .with_span_style(
+                ProgressStyle::with_template("{span_child_prefix}{span_name}").unwrap(),
+            )
+            .with_tick_interval(Duration::ZERO);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let outer = tracing::info_span!("outer").entered();
+            // Spans are only shown once entered
+            let first = tracing::info_span!("first");
+            assert_eq!(in_mem.contents(), "outer");
+
+            let nested = first.in_scope(|| tracing::info_span!("nested"));
+            nested.in_scope(|| {});
+            // Spans below the level of the filter are skipped
+            let second = tracing::debug_span!("skipped")
+                .in_scope(|| tracing::info_span!("second"))
+                .entered();
+            assert_eq!(in_mem.contents(), "outer\n  first\n    nested\n  second");
+
+            // Events in skipped spans are indented like the innermost shown span
+            tracing::debug_span!("skipped").in_scope(|| tracing::warn!("slow"));
+            assert_eq!(
+                in_mem.contents(),
+                " WARN   indicatif::tracing_layer::tests: slow\n\
+                 outer\n  first\n    nested\n  second"
+            );
+
+            drop(second);
+            drop(nested);
+            drop(first);
+            drop(outer);
+        });
+        assert_eq!(
+            in_mem.contents(),
+            " WARN   indicatif::tracing_layer::tests: slow"
+        );
+    }
+}
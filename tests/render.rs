@@ -1,10 +1,12 @@
 #![cfg(feature = "in_memory")]
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use indicatif::{
     InMemoryTerm, MultiProgress, MultiProgressAlignment, ProgressBar, ProgressDrawTarget,
-    ProgressFinish, ProgressStyle, TermLike,
+    ProgressFinish, ProgressIterator, ProgressStyle, TermLike,
 };
 use pretty_assertions::assert_eq;
 
@@ -1954,3 +1956,1292 @@ Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec nec viverra massa
 . Nunc nisl lectus, auctor in lorem eu, maximus elementum est."#
     );
 }
+
+#[test]
+fn progress_bar_id_placeholder() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("[{id}] {pos}/{len}").unwrap());
+
+    pb.tick();
+    assert_eq!(in_mem.contents(), "[] 0/10");
+
+    pb.set_id("crate:serde");
+    assert_eq!(in_mem.contents(), "[crate:serde] 0/10");
+    assert_eq!(pb.id().as_deref(), Some("crate:serde"));
+}
+
+#[test]
+fn progress_bar_taller_than_terminal() {
+    let in_mem = InMemoryTerm::new(3, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("1\n2\n3\n4\n{pos}/{len}").unwrap());
+
+    pb.tick();
+    assert_eq!(in_mem.contents(), "1\n2\n...");
+    assert!(pb.is_truncated());
+
+    pb.set_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+    pb.tick();
+    assert_eq!(in_mem.contents(), "0/10");
+    assert!(!pb.is_truncated());
+}
+
+#[test]
+fn progress_bar_last_rendered_lines() {
+    let in_mem = InMemoryTerm::new(10, 10);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let pb = mp.add(
+        ProgressBar::new(10)
+            .with_style(ProgressStyle::with_template("{msg}\n{pos}/{len}").unwrap())
+            .with_message("a message wider than the terminal"),
+    );
+
+    assert!(pb.last_rendered_lines().is_empty());
+    assert_eq!(pb.visual_height(), 0);
+
+    pb.tick();
+    assert_eq!(
+        pb.last_rendered_lines(),
+        vec!["a message wider than the terminal", "0/10"]
+    );
+    assert_eq!(pb.visual_height(), 5);
+
+    pb.finish_and_clear();
+    assert!(pb.last_rendered_lines().is_empty());
+}
+
+#[test]
+fn progress_bar_attach_spinner() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("a"),
+    );
+    let pb2 = mp.add(ProgressBar::new(10).with_style(style).with_message("b"));
+
+    pb1.tick();
+    pb2.tick();
+    let spinner = pb1.attach_spinner("sub-step");
+    spinner.tick();
+    assert_eq!(in_mem.contents(), "a 0/10\n⠁ sub-step\nb 0/10");
+
+    pb1.finish_with_message("done");
+    assert!(spinner.is_finished());
+    assert_eq!(in_mem.contents(), "done 10/10\nb 0/10");
+}
+
+#[test]
+fn multi_progress_header() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+
+    let header = mp.set_header(
+        ProgressBar::new(2).with_style(ProgressStyle::with_template("total {pos}/{len}").unwrap()),
+    );
+    let pb1 = mp.insert(
+        0,
+        ProgressBar::new(10).with_finish(ProgressFinish::AndLeave),
+    );
+    let pb2 = mp.add(ProgressBar::new(5));
+
+    header.tick();
+    pb1.tick();
+    pb2.tick();
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+total 0/2
+░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░ 0/10
+░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░ 0/5"#
+            .trim_start()
+    );
+
+    // Finished bars stay below the header instead of moving into the scrollback
+    drop(pb1);
+    header.inc(1);
+    mp.println("log line").unwrap();
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+log line
+total 1/2
+██████████████████████████████████████████████████████████████████████████ 10/10
+░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░ 0/5"#
+            .trim_start()
+    );
+
+    // Replacing the header removes the previous one
+    let header = mp.set_header(
+        ProgressBar::new(2).with_style(ProgressStyle::with_template("stage 2").unwrap()),
+    );
+    header.tick();
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+log line
+stage 2
+██████████████████████████████████████████████████████████████████████████ 10/10
+░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░ 0/5"#
+            .trim_start()
+    );
+}
+
+#[test]
+fn multi_progress_footer() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+
+    let footer = mp.set_footer(
+        ProgressBar::new(1)
+            .with_style(ProgressStyle::with_template("press q to cancel").unwrap())
+            .with_finish(ProgressFinish::AndLeave),
+    );
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(ProgressStyle::with_template("{msg} {pos}/{len}").unwrap())
+            .with_message("first")
+            .with_finish(ProgressFinish::AndLeave),
+    );
+    let pb2 = mp.add(
+        ProgressBar::new(10)
+            .with_style(ProgressStyle::with_template("{msg} {pos}/{len}").unwrap())
+            .with_message("second"),
+    );
+
+    footer.tick();
+    pb1.tick();
+    pb2.tick();
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+first 0/10
+second 0/10
+press q to cancel"#
+            .trim_start()
+    );
+
+    // Finished bars at the top still move into the scrollback, above the footer
+    drop(pb1);
+    pb2.inc(1);
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+first 10/10
+second 1/10
+press q to cancel"#
+            .trim_start()
+    );
+
+    // The footer is cleared when dropped
+    drop(footer);
+    pb2.inc(1);
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+first 10/10
+second 2/10"#
+            .trim_start()
+    );
+}
+
+#[test]
+fn finish_reason_placeholder() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {finish_reason}").unwrap();
+
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("first")
+            .with_finish(ProgressFinish::AndLeave),
+    );
+    let pb2 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style)
+            .with_message("second"),
+    );
+
+    pb1.tick();
+    pb2.fail();
+    drop(pb1);
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+first dropped
+second failed"#
+            .trim_start()
+    );
+}
+
+#[test]
+fn multi_progress_absolute_positioning() {
+    let in_mem = InMemoryTerm::new(6, 20);
+    let mp = MultiProgress::with_draw_target(
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())).with_absolute_positioning(),
+    );
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("one"),
+    );
+    let pb2 = mp.add(ProgressBar::new(10).with_style(style).with_message("two"));
+    pb1.tick();
+    pb2.tick();
+    assert_eq!(in_mem.contents(), "\n\n\n\none 0/10\ntwo 0/10");
+
+    // Partial lines written by other code do not interfere with the bars
+    in_mem.write_str("partial").unwrap();
+    pb1.inc(1);
+    assert_eq!(in_mem.contents(), "partial\n\n\n\none 1/10\ntwo 0/10");
+
+    in_mem.write_line("").unwrap();
+    mp.println("hello").unwrap();
+    assert_eq!(in_mem.contents(), "partial\nhello\n\n\none 1/10\ntwo 0/10");
+
+    pb2.finish_and_clear();
+    assert_eq!(in_mem.contents(), "partial\nhello\n\n\n\none 1/10");
+
+    drop(pb1);
+    drop(pb2);
+    mp.clear().unwrap();
+    assert_eq!(in_mem.contents(), "partial\nhello");
+}
+
+#[test]
+fn spinner_set_tick() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        None,
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(
+        ProgressStyle::with_template("{spinner} {msg}")
+            .unwrap()
+            .tick_chars("abcd"),
+    )
+    .with_message("working");
+
+    pb.set_tick(2);
+    assert_eq!(in_mem.contents(), "c working");
+
+    pb.set_tick(4);
+    assert_eq!(in_mem.contents(), "b working");
+
+    pb.tick();
+    assert_eq!(in_mem.contents(), "c working");
+}
+
+#[test]
+fn multi_progress_frame_decorator() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    mp.set_frame_decorator(|lines| {
+        lines.retain(|line| !line.starts_with("hidden"));
+        if lines.len() > 1 {
+            lines.insert(1, "----".into());
+        }
+    });
+
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("one"),
+    );
+    let pb2 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("hidden"),
+    );
+    let pb3 = mp.add(ProgressBar::new(10).with_style(style).with_message("three"));
+
+    pb1.tick();
+    pb2.tick();
+    pb3.tick();
+    mp.println("log").unwrap();
+    assert_eq!(
+        in_mem.contents(),
+        r#"
+log
+one 0/10
+----
+three 0/10"#
+            .trim_start()
+    );
+}
+
+#[test]
+fn multi_progress_delta_encoding() {
+    let in_mem = InMemoryTerm::new(10, 20);
+    let mp = MultiProgress::with_draw_target(
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())).with_delta_encoding(),
+    );
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("one"),
+    );
+    let pb2 = mp.add(ProgressBar::new(10).with_style(style).with_message("two"));
+    pb1.tick();
+    pb2.tick();
+    assert_eq!(in_mem.contents(), "one 0/10\ntwo 0/10");
+
+    pb1.inc(1);
+    assert_eq!(in_mem.contents(), "one 1/10\ntwo 0/10");
+
+    pb2.set_position(10);
+    assert_eq!(in_mem.contents(), "one 1/10\ntwo 10/10");
+
+    // Shorter lines clear the rest of the row
+    pb1.set_message("1");
+    assert_eq!(in_mem.contents(), "1 1/10\ntwo 10/10");
+    pb2.set_message("2");
+    assert_eq!(in_mem.contents(), "1 1/10\n2 10/10");
+
+    mp.println("hello").unwrap();
+    assert_eq!(in_mem.contents(), "hello\n1 1/10\n2 10/10");
+
+    pb1.inc(1);
+    assert_eq!(in_mem.contents(), "hello\n1 2/10\n2 10/10");
+
+    mp.clear().unwrap();
+    assert_eq!(in_mem.contents(), "hello");
+}
+
+#[test]
+fn multi_progress_delta_encoding_after_resize() {
+    let in_mem = InMemoryTerm::new(10, 20);
+    let mp = MultiProgress::with_draw_target(
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())).with_delta_encoding(),
+    );
+    let style = ProgressStyle::with_template("{bar:10} {pos}").unwrap();
+
+    let pb1 = mp.add(ProgressBar::new(10).with_style(style.clone()));
+    let pb2 = mp.add(ProgressBar::new(10).with_style(style));
+    pb1.tick();
+    pb2.tick();
+    assert_eq!(in_mem.contents(), "░░░░░░░░░░ 0\n░░░░░░░░░░ 0");
+
+    // The lines no longer fit, so the frame is drawn in full instead of as a delta
+    in_mem.resize(10, 8);
+    in_mem.reset();
+    pb1.inc(1);
+    assert_eq!(in_mem.contents(), "█░░░░░░░\n░░ 1\n░░░░░░░░\n░░ 0");
+
+    in_mem.resize(10, 20);
+    in_mem.reset();
+    pb2.inc(1);
+    assert_eq!(in_mem.contents(), "█░░░░░░░░░ 1\n█░░░░░░░░░ 1");
+}
+
+#[test]
+fn min_redraw_interval() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+    pb.set_min_redraw_interval(Duration::from_secs(3600));
+
+    pb.tick();
+    assert_eq!(in_mem.contents(), "0/10");
+
+    pb.set_position(5);
+    assert_eq!(in_mem.contents(), "0/10");
+    assert_eq!(pb.position(), 5);
+
+    pb.finish();
+    assert_eq!(in_mem.contents(), "10/10");
+}
+
+#[test]
+fn set_style_redraws_layout() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}\n{msg}").unwrap());
+    pb.set_message("detailed");
+    assert_eq!(in_mem.contents(), "0/10\ndetailed");
+
+    pb.set_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+    assert_eq!(in_mem.contents(), "0/10");
+
+    pb.set_style(ProgressStyle::with_template("[{pos}] {msg}").unwrap());
+    assert_eq!(in_mem.contents(), "[0] detailed");
+}
+
+#[test]
+fn identical_frames_are_skipped() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len} {msg}").unwrap());
+
+    pb.set_message("working");
+    assert_eq!(in_mem.contents(), "0/10 working");
+    in_mem.moves_since_last_check();
+
+    pb.tick();
+    pb.set_message("working");
+    assert_eq!(in_mem.moves_since_last_check(), "");
+
+    pb.inc(1);
+    assert_eq!(in_mem.contents(), "1/10 working");
+    assert_ne!(in_mem.moves_since_last_check(), "");
+
+    // Forced draws are not skipped, even if the frame is identical
+    pb.abandon();
+    assert_eq!(in_mem.contents(), "1/10 working");
+    assert_ne!(in_mem.moves_since_last_check(), "");
+}
+
+#[test]
+fn title_percent_while_unfocused() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+    let focused = Arc::new(AtomicBool::new(true));
+    pb.enable_title_percent(Some(focused.clone()));
+
+    pb.set_position(3);
+    assert!(!in_mem.moves_since_last_check().contains("\\u{1b}]0;"));
+
+    focused.store(false, Ordering::Relaxed);
+    pb.set_position(5);
+    let moves = in_mem.moves_since_last_check();
+    assert!(moves.contains(r#"Str("\u{1b}[22;0t")"#));
+    assert!(moves.contains(r#"Str("\u{1b}]0;50%\u{7}")"#));
+
+    // The title is restored instead of cleared
+    focused.store(true, Ordering::Relaxed);
+    pb.set_position(6);
+    let moves = in_mem.moves_since_last_check();
+    assert!(moves.contains(r#"Str("\u{1b}[23;0t")"#));
+    assert!(!moves.contains("\u{1b}]0;"));
+
+    focused.store(false, Ordering::Relaxed);
+    pb.set_position(7);
+    assert!(in_mem
+        .moves_since_last_check()
+        .contains(r#"Str("\u{1b}]0;70%\u{7}")"#));
+    pb.finish();
+    assert!(in_mem
+        .moves_since_last_check()
+        .contains(r#"Str("\u{1b}[23;0t")"#));
+}
+
+#[test]
+fn multi_progress_tags() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {pos}").unwrap();
+    let a = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("a"),
+    );
+    let b = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("b")
+            .group("network"),
+    );
+    let c = mp.add(ProgressBar::new(10).with_style(style).with_message("c"));
+    c.clone().group("network");
+
+    a.tick();
+    b.tick();
+    c.tick();
+    assert_eq!(in_mem.contents(), "a 0\nb 0\nc 0");
+
+    mp.set_tag_visible("network", false);
+    assert_eq!(in_mem.contents(), "a 0");
+
+    b.inc(1);
+    a.inc(1);
+    assert_eq!(in_mem.contents(), "a 1");
+
+    mp.set_tag_visible("network", true);
+    assert_eq!(in_mem.contents(), "a 1\nb 1\nc 0");
+
+    mp.remove_tag("network");
+    a.inc(1);
+    assert_eq!(in_mem.contents(), "a 2");
+    assert!(b.is_hidden());
+}
+
+#[test]
+fn multi_progress_verbosity() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg}").unwrap();
+    let total = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("total")
+            .with_min_verbosity(-1),
+    );
+    let step = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("step"),
+    );
+    let detail = mp.add(
+        ProgressBar::new(10)
+            .with_style(style)
+            .with_message("detail"),
+    );
+    detail.clone().with_min_verbosity(1);
+
+    total.tick();
+    step.tick();
+    detail.tick();
+    assert_eq!(in_mem.contents(), "total\nstep");
+
+    mp.set_verbosity(1);
+    assert_eq!(in_mem.contents(), "total\nstep\ndetail");
+
+    mp.set_verbosity(-1);
+    assert_eq!(in_mem.contents(), "total");
+}
+
+#[test]
+fn time_left_or_total() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{time_left_or_total_precise} {eta_precise}").unwrap())
+    .with_elapsed(Duration::from_secs(65));
+
+    pb.tick();
+    let contents = in_mem.contents();
+    let (time, eta) = contents.split_once(' ').unwrap();
+    assert_eq!(time, eta);
+
+    pb.abandon();
+    assert!(in_mem.contents().starts_with("00:01:05 "));
+}
+
+#[test]
+fn multi_progress_auto_remove() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let total = mp.add(
+        ProgressBar::new(3)
+            .with_style(style.clone())
+            .with_message("total"),
+    );
+    total.tick();
+
+    for (i, name) in ["a", "b", "c"].into_iter().enumerate() {
+        let pb = mp.add_with_auto_remove(
+            ProgressBar::new(10)
+                .with_style(style.clone())
+                .with_message(name),
+        );
+        pb.inc(5);
+        assert!(in_mem
+            .contents()
+            .ends_with(&format!("total {i}/3\n{name} 5/10")));
+        match name {
+            "b" => pb.finish(),
+            _ => pb.finish_and_clear(),
+        }
+        total.inc(1);
+    }
+
+    assert_eq!(in_mem.contents(), "b 10/10\ntotal 3/3");
+    // Only the header and `total` are left
+    assert_eq!(mp.summary().lines().count(), 2);
+}
+
+#[test]
+fn multi_progress_print_summary() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let pb = mp.add(
+        ProgressBar::new(10)
+            .with_style(ProgressStyle::with_template("{msg} {pos}/{len}").unwrap())
+            .with_message("a"),
+    );
+    pb.inc(5);
+
+    mp.print_summary().unwrap();
+    let contents = in_mem.contents();
+    let lines = contents.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("name  total"));
+    assert!(lines[1].starts_with("a     5/10"));
+    assert_eq!(lines[2], "a 5/10");
+}
+
+#[test]
+fn multi_progress_defaults() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    mp.set_default_style(ProgressStyle::with_template("default {msg}").unwrap());
+    mp.set_default_tab_width(2);
+
+    let plain = mp.add(ProgressBar::new(10).with_message("a\tb"));
+    let custom = mp.add(
+        ProgressBar::new(10)
+            .with_style(ProgressStyle::with_template("custom {msg}").unwrap())
+            .with_tab_width(4)
+            .with_message("a\tb"),
+    );
+
+    plain.tick();
+    custom.tick();
+    assert_eq!(in_mem.contents(), "default a  b\ncustom a    b");
+}
+
+#[test]
+fn multi_progress_max_visible() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    mp.set_max_visible(2);
+    let style = ProgressStyle::with_template("{msg}").unwrap();
+    let bars = (0..5)
+        .map(|i| {
+            mp.add_with_auto_remove(
+                ProgressBar::new(1)
+                    .with_style(style.clone())
+                    .with_message(format!("bar {i}")),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for pb in &bars {
+        pb.tick();
+    }
+    assert_eq!(in_mem.contents(), "bar 0\nbar 1\n... and 3 more");
+
+    bars[0].finish_and_clear();
+    assert_eq!(in_mem.contents(), "bar 1\nbar 2\n... and 2 more");
+
+    bars[1].finish_and_clear();
+    bars[2].finish_and_clear();
+    assert_eq!(in_mem.contents(), "bar 3\nbar 4");
+}
+
+#[test]
+fn multi_progress_max_visible_scrolls_past_finished() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    mp.set_max_visible(2);
+    let style = ProgressStyle::with_template("{msg}").unwrap();
+    let bars = (0..4)
+        .map(|i| {
+            mp.add(
+                ProgressBar::new(1)
+                    .with_style(style.clone())
+                    .with_message(format!("bar {i}")),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for pb in &bars {
+        pb.tick();
+    }
+    assert_eq!(in_mem.contents(), "bar 0\nbar 1\n... and 2 more");
+
+    // Finished bars stay members, but scroll out of the window
+    bars[0].finish();
+    assert_eq!(in_mem.contents(), "bar 1\nbar 2\n... and 2 more");
+
+    // The window doesn't scroll past the last bars
+    bars[1].finish();
+    bars[2].finish();
+    bars[3].finish();
+    assert_eq!(in_mem.contents(), "bar 2\nbar 3\n... and 2 more");
+
+    bars[0].reset();
+    assert_eq!(in_mem.contents(), "bar 0\nbar 1\n... and 2 more");
+}
+
+#[test]
+fn multi_progress_set_draw_target_rebinds_members() {
+    let mp = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("a"),
+    );
+    let pb2 = mp.add(ProgressBar::new(10).with_style(style).with_message("b"));
+    pb1.inc(1);
+    pb2.inc(2);
+    assert!(pb1.is_hidden());
+
+    let first = InMemoryTerm::new(10, 80);
+    mp.set_draw_target(ProgressDrawTarget::term_like(Box::new(first.clone())));
+    assert!(!pb1.is_hidden());
+    assert_eq!(first.contents(), "a 1/10\nb 2/10");
+
+    pb2.inc(1);
+    assert_eq!(first.contents(), "a 1/10\nb 3/10");
+
+    let second = InMemoryTerm::new(10, 80);
+    mp.set_draw_target(ProgressDrawTarget::term_like(Box::new(second.clone())));
+    assert_eq!(second.contents(), "a 1/10\nb 3/10");
+
+    pb1.inc(1);
+    assert_eq!(second.contents(), "a 2/10\nb 3/10");
+    assert_eq!(first.contents(), "a 1/10\nb 3/10");
+}
+
+#[test]
+fn nested_children_advance_parent() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let parent = mp.add(ProgressBar::new(3).with_style(style).with_message("all"));
+    let other = mp.add(ProgressBar::new(1).with_message("other"));
+    other.set_style(ProgressStyle::with_template("{msg}").unwrap());
+    parent.tick();
+    other.tick();
+
+    let first = parent.add_child(5);
+    first.set_message("first");
+    let nested = first.add_child(1);
+    nested.set_message("nested");
+    let second = parent.add_child_with_weight(2, 2);
+    second.set_message("second");
+    assert_eq!(
+        in_mem.contents(),
+        "all 0/3\n  first 0/5\n    nested 0/1\n  second 0/2\nother"
+    );
+
+    nested.finish();
+    assert_eq!(first.position(), 1);
+    first.abandon();
+    assert_eq!(parent.position(), 0);
+
+    second.finish();
+    assert_eq!(parent.position(), 2);
+    assert_eq!(
+        in_mem.contents(),
+        "all 2/3\n  first 1/5\n    nested 1/1\n  second 2/2\nother"
+    );
+
+    // finishing again doesn't advance the parent twice
+    second.finish();
+    assert_eq!(parent.position(), 2);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn multi_progress_record_to_reports_errors() {
+    let in_mem = InMemoryTerm::new(10, 40);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    // Writing to /dev/full fails because the device is full
+    mp.record_to("/dev/full").unwrap();
+    let pb = mp.add(ProgressBar::new(2));
+    pb.tick();
+    assert!(mp.stop_recording().is_err());
+    assert!(mp.stop_recording().is_ok());
+}
+
+#[test]
+fn multi_progress_record_to() {
+    let in_mem = InMemoryTerm::new(10, 40);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let path = std::env::temp_dir().join(format!("indicatif-{}.cast", std::process::id()));
+    mp.record_to(&path).unwrap();
+
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let pb1 = mp.add(
+        ProgressBar::new(2)
+            .with_style(style.clone())
+            .with_message("a"),
+    );
+    let pb2 = mp.add(ProgressBar::new(2).with_style(style).with_message("b"));
+    pb1.tick();
+    pb2.tick();
+    mp.println("hello").unwrap();
+    pb2.inc(1);
+    mp.stop_recording().unwrap();
+    pb2.inc(1);
+
+    let cast = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let data = cast
+        .lines()
+        .skip(1)
+        .map(|event| {
+            event
+                .split_once(", \"o\", ")
+                .unwrap()
+                .1
+                .trim_end_matches(']')
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        cast.lines().next().unwrap(),
+        r#"{"version": 2, "width": 40, "height": 10}"#
+    );
+    assert_eq!(
+        data,
+        [
+            r#""a 0/2""#,
+            r#""\r\u001b[Ja 0/2\r\nb 0/2""#,
+            r#""\u001b[1A\r\u001b[Jhello\r\na 0/2\r\nb 0/2""#,
+            r#""\u001b[1A\r\u001b[Ja 0/2\r\nb 1/2""#,
+        ]
+    );
+}
+
+#[test]
+fn multi_progress_groups() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg}").unwrap();
+    let bar = |msg: &'static str| {
+        ProgressBar::new(1)
+            .with_style(style.clone())
+            .with_message(msg)
+    };
+
+    let downloads = mp.add_group("Downloading");
+    let builds = mp.add_group("Building");
+    let bars = [
+        downloads.add(bar("a.tar")),
+        builds.add(bar("crate-a")),
+        downloads.add(bar("b.tar")),
+        mp.add(bar("total")),
+        builds.add(bar("crate-b")),
+    ];
+    for pb in &bars {
+        pb.tick();
+    }
+    assert_eq!(
+        in_mem.contents(),
+        "Downloading\na.tar\nb.tar\n\nBuilding\ncrate-a\ncrate-b\ntotal"
+    );
+
+    mp.remove(&bars[0]);
+    mp.remove(&bars[2]);
+    let pb = downloads.add(bar("c.tar"));
+    pb.tick();
+    downloads.header().set_message("Downloaded");
+    assert_eq!(
+        in_mem.contents(),
+        "Downloaded\nc.tar\n\nBuilding\ncrate-a\ncrate-b\ntotal"
+    );
+}
+
+#[test]
+fn last_draw_time() {
+    let pb = ProgressBar::with_draw_target(Some(100), ProgressDrawTarget::hidden());
+    pb.inc(1);
+    assert_eq!(pb.last_draw_time(), None);
+
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(100),
+        ProgressDrawTarget::term_like_with_hz(Box::new(in_mem.clone()), 1),
+    );
+    pb.inc(1);
+    let first = pb.last_draw_time().unwrap();
+
+    // The rate limiter allows bursts of 20 frames, after that updates are not drawn
+    for _ in 0..30 {
+        pb.inc(1);
+    }
+    let last = pb.last_draw_time().unwrap();
+    assert!(last >= first);
+    pb.inc(1);
+    assert_eq!(pb.last_draw_time(), Some(last));
+
+    pb.finish();
+    assert!(pb.last_draw_time().unwrap() > last);
+
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let member = mp.add(ProgressBar::new(10));
+    member.inc(1);
+    assert!(member.last_draw_time().is_some());
+
+    let hidden = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    let member = hidden.add(ProgressBar::new(10));
+    member.tick();
+    assert_eq!(member.last_draw_time(), None);
+}
+
+#[test]
+fn min_visible_duration() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+    pb.set_min_visible_duration(Duration::from_millis(500));
+
+    // A progress bar that was never drawn is not shown at all
+    pb.finish_and_clear();
+    assert_eq!(in_mem.contents(), String::new());
+
+    pb.reset();
+    pb.inc(3);
+    assert_eq!(in_mem.contents(), "3/10");
+    pb.finish_and_clear();
+    assert_eq!(in_mem.contents(), "10/10");
+
+    // The finished progress bar is cleared in the background, even after it was dropped
+    drop(pb);
+    let start = std::time::Instant::now();
+    while !in_mem.contents().is_empty() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // A progress bar dropped without being finished is cleared right away
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+    pb.set_min_visible_duration(Duration::from_secs(3600));
+    pb.inc(3);
+    assert_eq!(in_mem.contents(), "3/10");
+    drop(pb);
+    assert_eq!(in_mem.contents(), String::new());
+}
+
+#[test]
+fn render_delay() {
+    let style = ProgressStyle::with_template("{pos}/{len}").unwrap();
+    let in_mem = InMemoryTerm::new(10, 80);
+    let fast = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(style.clone());
+    fast.set_render_delay(Duration::from_secs(3600));
+    fast.inc(5);
+    fast.finish();
+    assert_eq!(in_mem.contents(), String::new());
+
+    let in_mem = InMemoryTerm::new(10, 80);
+    let slow = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(style);
+    slow.set_render_delay(Duration::from_millis(200));
+    slow.enable_steady_tick(Duration::from_millis(10));
+    slow.inc(5);
+    assert_eq!(in_mem.contents(), String::new());
+
+    // The progress bar is drawn by the steady tick once the delay has passed
+    let start = std::time::Instant::now();
+    while in_mem.contents().is_empty() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(in_mem.contents(), "5/10");
+}
+
+#[test]
+fn multi_progress_batch() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg}").unwrap();
+
+    let bars = mp.batch(|mp| {
+        let bars = (0..3)
+            .map(|i| {
+                let pb = mp.add(ProgressBar::new(10).with_style(style.clone()));
+                pb.set_message(format!("bar {i}"));
+                pb
+            })
+            .collect::<Vec<_>>();
+        mp.println("added").unwrap();
+        assert_eq!(in_mem.contents(), String::new());
+        bars
+    });
+    assert_eq!(in_mem.contents(), "added\nbar 0\nbar 1\nbar 2");
+
+    mp.batch(|mp| {
+        mp.batch(|mp| mp.remove(&bars[0]));
+        mp.remove(&bars[2]);
+        assert_eq!(in_mem.contents(), "added\nbar 0\nbar 1\nbar 2");
+    });
+    assert_eq!(in_mem.contents(), "added\nbar 1");
+}
+
+#[test]
+fn hide_length_dependent_keys() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let style = ProgressStyle::with_template("{msg}{bar:5} {pos} {percent}% {eta}")
+        .unwrap()
+        .hide_length_dependent_keys(true);
+    let pb = ProgressBar::with_draw_target(
+        None,
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(style)
+    .with_message("x");
+
+    pb.tick();
+    assert_eq!(in_mem.contents(), "x      0 %");
+
+    pb.set_length(4);
+    pb.inc(2);
+    assert!(in_mem.contents().starts_with("x██░░░ 2 50% "));
+}
+
+#[test]
+fn progress_with_message() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mut iter = ["a.txt", "b.txt"]
+        .into_iter()
+        .progress_with_message(|file| format!("processing {file}"))
+        .with_style(ProgressStyle::with_template("{pos}/{len} {msg}").unwrap());
+    iter.progress
+        .set_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+
+    assert_eq!(iter.next(), Some("a.txt"));
+    assert_eq!(in_mem.contents(), "1/2 processing a.txt");
+    assert_eq!(iter.next(), Some("b.txt"));
+    assert_eq!(in_mem.contents(), "2/2 processing b.txt");
+}
+
+#[test]
+fn progress_with_message_is_lazy() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mut calls = 0;
+    let iter = (0..100)
+        .progress_with_message(|i| {
+            calls += 1;
+            i.to_string()
+        })
+        .with_style(ProgressStyle::with_template("{pos}/{len} {msg}").unwrap());
+    iter.progress
+        .set_draw_target(ProgressDrawTarget::term_like_with_hz(
+            Box::new(in_mem.clone()),
+            1,
+        ));
+
+    assert_eq!(iter.count(), 100);
+    // Only the items of the initial burst of the rate limiter are drawn
+    assert!(calls <= 20, "{calls}");
+}
+
+#[test]
+fn failures_and_successes() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(
+        ProgressStyle::with_template("{pos}/{len} ok: {successes} failed: {failures}").unwrap(),
+    );
+
+    pb.inc(2);
+    pb.inc_failures(1);
+    pb.inc(1);
+    assert_eq!(in_mem.contents(), "3/10 ok: 2 failed: 1");
+    assert_eq!(pb.failures(), 1);
+
+    pb.reset();
+    assert_eq!(pb.failures(), 0);
+}
+
+#[test]
+fn println_fmt() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+
+    pb.println_fmt(format_args!("processed {} items", 3));
+    indicatif::progress_println!(pb, "and {} more", 2);
+    assert_eq!(in_mem.contents(), "processed 3 items\nand 2 more\n0/10");
+}
+
+#[test]
+fn template_conditions() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let style = ProgressStyle::with_template(
+        "{if len}{bar:4} {pos}/{len}{else}{pos} items{endif}{if finished} done{endif}",
+    )
+    .unwrap()
+    .progress_chars("#>-");
+    let pb = ProgressBar::with_draw_target(
+        None,
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())),
+    )
+    .with_style(style);
+
+    pb.inc(2);
+    assert_eq!(in_mem.contents(), "2 items");
+
+    pb.set_length(4);
+    assert_eq!(in_mem.contents(), "##>- 2/4");
+
+    pb.finish();
+    assert_eq!(in_mem.contents(), "#### 4/4 done");
+}
+
+#[test]
+fn bell_on_finish() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let pb1 = mp.add(ProgressBar::new(10));
+    let pb2 = mp.add(ProgressBar::new(10));
+    pb1.bell_on_finish(true);
+    pb2.bell_on_failure(true);
+
+    pb1.inc(1);
+    pb2.inc(1);
+    in_mem.moves_since_last_check();
+
+    // Only rings once
+    pb1.finish();
+    pb1.finish();
+    assert_eq!(
+        in_mem
+            .moves_since_last_check()
+            .matches(r#"Str("\u{7}")"#)
+            .count(),
+        1
+    );
+
+    pb2.fail();
+    assert!(in_mem.moves_since_last_check().contains(r#"Str("\u{7}")"#));
+
+    let pb3 = mp.add(ProgressBar::new(10));
+    pb3.bell_on_finish(true);
+    pb3.abandon();
+    assert!(!in_mem.moves_since_last_check().contains(r#"Str("\u{7}")"#));
+}
+
+#[test]
+fn synchronized_output() {
+    let in_mem = InMemoryTerm::new(10, 80);
+    let pb = ProgressBar::with_draw_target(
+        Some(10),
+        ProgressDrawTarget::term_like(Box::new(in_mem.clone())).with_synchronized_output(),
+    )
+    .with_style(ProgressStyle::with_template("{pos}/{len}").unwrap());
+
+    pb.tick();
+    assert_eq!(in_mem.contents(), "0/10");
+    let moves = in_mem.moves_since_last_check();
+    assert!(moves.starts_with(r#"Str("\u{1b}[?2026h")"#));
+    assert!(moves.ends_with("Str(\"\\u{1b}[?2026l\")\nFlush\n"));
+
+    pb.inc(1);
+    assert_eq!(in_mem.contents(), "1/10");
+}
+
+#[test]
+fn multi_progress_priority_eviction() {
+    let in_mem = InMemoryTerm::new(4, 40);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+    let bar = |msg: &'static str| {
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message(msg)
+    };
+
+    let a = mp.insert_with_priority(10, bar("a"));
+    let b = mp.add(bar("b"));
+    let c = mp.insert_with_priority(5, bar("c"));
+    let d = mp.add(bar("d"));
+    for pb in [&a, &b, &c, &d] {
+        pb.tick();
+    }
+    assert_eq!(in_mem.contents(), "a 0/10\nb 0/10\nc 0/10\nd 0/10");
+
+    // The lowest priority bars are evicted first, starting from the bottom
+    let e = mp.insert_with_priority(10, bar("e"));
+    e.tick();
+    assert_eq!(in_mem.contents(), "a 0/10\nc 0/10\ne 0/10\n... and 2 more");
+
+    d.finish_and_clear();
+    assert_eq!(in_mem.contents(), "a 0/10\nb 0/10\nc 0/10\ne 0/10");
+}
+
+#[test]
+fn multi_progress_finish_into_scrollback() {
+    let in_mem = InMemoryTerm::new(10, 40);
+    let mp =
+        MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(in_mem.clone())));
+    let style = ProgressStyle::with_template("{msg} {pos}/{len}").unwrap();
+
+    let pb1 = mp.add(
+        ProgressBar::new(10)
+            .with_style(style.clone())
+            .with_message("one"),
+    );
+    let pb2 = mp.add(ProgressBar::new(10).with_style(style).with_message("two"));
+    pb1.tick();
+    pb2.tick();
+    assert_eq!(in_mem.contents(), "one 0/10\ntwo 0/10");
+
+    pb1.inc(3);
+    pb1.finish_into_scrollback();
+    assert_eq!(in_mem.contents(), "one 10/10\ntwo 0/10");
+
+    // The finished bar is no longer part of the live region
+    mp.println("hello").unwrap();
+    pb2.inc(1);
+    assert_eq!(in_mem.contents(), "one 10/10\nhello\ntwo 1/10");
+
+    drop(pb1);
+    pb2.finish_and_clear();
+    assert_eq!(in_mem.contents(), "one 10/10\nhello");
+}